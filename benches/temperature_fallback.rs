@@ -0,0 +1,78 @@
+//! Encoder reuse across temperature fallbacks
+//!
+//! Forces every window through all temperatures, then times the same
+//! transcription twice: with one encoder pass per window (the current path),
+//! and with an encoder pass per attempt (the old path).
+//!
+//! Needs a model and the `bench` feature:
+//! `HYPRVOICE_BENCH_MODEL=openai/whisper-tiny cargo bench --features bench`
+
+use hyprvoice::transcribe::Transcriber;
+use hyprvoice::transcribe::candle_engine::{BenchOptions, CandleEngine, DecodeStats};
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 3;
+
+/// Deterministic white noise (xorshift), 16kHz mono
+fn noise(secs: usize) -> Vec<f32> {
+    let mut state = 0x2545_f491_u32;
+    (0..secs * 16000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state as f32 / u32::MAX as f32 - 0.5) * 0.5
+        })
+        .collect()
+}
+
+/// Mean time of one transcription, and the work it did
+fn time_runs(engine: &mut CandleEngine, audio: &[f32]) -> anyhow::Result<(Duration, DecodeStats)> {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        engine.transcribe(audio)?;
+        elapsed += started.elapsed();
+    }
+    let stats = engine.decode_stats().clone();
+    anyhow::ensure!(
+        stats.attempts > stats.windows,
+        "no fallback happened ({})",
+        stats
+    );
+    Ok((elapsed / RUNS, stats))
+}
+
+fn main() -> anyhow::Result<()> {
+    let Ok(model) = std::env::var("HYPRVOICE_BENCH_MODEL") else {
+        println!("temperature_fallback: set HYPRVOICE_BENCH_MODEL to a model id to run");
+        return Ok(());
+    };
+
+    let mut engine = CandleEngine::with_options(&model, "en", None, false)?;
+    let mut options = BenchOptions {
+        force_fallback: true,
+        ..Default::default()
+    };
+    engine.set_bench_options(options);
+    let audio = noise(30);
+
+    // Warm-up, so the first timed run doesn't pay for kernel setup
+    engine.transcribe(&audio)?;
+
+    let (shared, shared_stats) = time_runs(&mut engine, &audio)?;
+    options.encode_per_attempt = true;
+    engine.set_bench_options(options);
+    let (per_attempt, per_attempt_stats) = time_runs(&mut engine, &audio)?;
+
+    println!("temperature_fallback ({}, {} runs each)", model, RUNS);
+    println!("  shared encoding: {:.1?} per run", shared);
+    println!("    {}", shared_stats);
+    println!("  encoding per attempt: {:.1?} per run", per_attempt);
+    println!("    {}", per_attempt_stats);
+    println!(
+        "  saved: {:.1?} per run",
+        per_attempt.saturating_sub(shared)
+    );
+    Ok(())
+}
@@ -4,12 +4,20 @@
 STATE_DIR="${XDG_STATE_HOME:-$HOME/.local/state}/hyprvoice"
 PID_FILE="${STATE_DIR}/recording.pid"
 PROCESSING_FILE="${STATE_DIR}/processing"
+LEVEL_FILE="${STATE_DIR}/level"
+PARTIAL_FILE="${STATE_DIR}/partial"
 
 # Icons (Nerd Fonts required)
 ICON_IDLE="󰔊"
 ICON_RECORDING="󰑋"
 ICON_PROCESSING="󱐋"
 
+# Input level glyphs, quietest to loudest
+LEVEL_GLYPHS=(▁ ▂ ▃ ▄ ▅ ▆ ▇ █)
+
+# Longest live transcript shown in the tooltip (most recent words are kept)
+PARTIAL_MAX_CHARS=120
+
 # Check if recording
 if [[ -f "$PID_FILE" ]]; then
     PID=$(head -n 1 "$PID_FILE" 2>/dev/null || echo "")
@@ -21,7 +29,33 @@ if [[ -f "$PID_FILE" ]]; then
         MINS=$((ELAPSED / 60))
         SECS=$((ELAPSED % 60))
         TIME_STR=$(printf "%d:%02d" "$MINS" "$SECS")
-        echo "{\"text\": \"${ICON_RECORDING} ${TIME_STR}\", \"tooltip\": \"Recording... Click to stop\", \"class\": \"recording\"}"
+
+        # Live level written by the daemon: "<percent> <rms dBFS> <peak dBFS>"
+        LEVEL_STR=""
+        if [[ -f "$LEVEL_FILE" ]]; then
+            read -r PERCENT RMS_DB _ < "$LEVEL_FILE"
+            if [[ "$PERCENT" =~ ^[0-9]+$ ]]; then
+                INDEX=$((PERCENT * ${#LEVEL_GLYPHS[@]} / 101))
+                LEVEL_STR=" ${LEVEL_GLYPHS[$INDEX]}"
+                TOOLTIP_LEVEL=" (input ${RMS_DB} dBFS)"
+            fi
+        fi
+
+        # Live partial transcript written by the daemon (single line)
+        TOOLTIP_PARTIAL=""
+        if [[ -s "$PARTIAL_FILE" ]]; then
+            PARTIAL=$(head -n 1 "$PARTIAL_FILE" 2>/dev/null || echo "")
+            if (( ${#PARTIAL} > PARTIAL_MAX_CHARS )); then
+                PARTIAL="…${PARTIAL: -PARTIAL_MAX_CHARS}"
+            fi
+            # Escape for the JSON string
+            PARTIAL=${PARTIAL//\\/\\\\}
+            PARTIAL=${PARTIAL//\"/\\\"}
+            PARTIAL=${PARTIAL//$'\t'/ }
+            [[ -n "$PARTIAL" ]] && TOOLTIP_PARTIAL="\\n${PARTIAL}"
+        fi
+
+        echo "{\"text\": \"${ICON_RECORDING}${LEVEL_STR} ${TIME_STR}\", \"tooltip\": \"Recording${TOOLTIP_LEVEL}... Click to stop${TOOLTIP_PARTIAL}\", \"class\": \"recording\"}"
         exit 0
     else
         # Stale PID file
@@ -0,0 +1,365 @@
+//! Microphone capture that survives the input device going away
+//!
+//! Wraps the queued CPAL stream. A stream error or a stall (no audio for a
+//! while) marks the device as lost; the capture then keeps trying to reopen the
+//! configured device (or the system default) and records how much audio was
+//! missed. A replacement device with a different layout is converted back to
+//! the original rate and channel count, so consumers never see a format change.
+
+use cpal::traits::DeviceTrait;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use super::source::{QueueReader, default_stream_config, open_queued_stream};
+use super::{StreamResampler, downmix_to_mono, select_input_device};
+
+/// A running stream that delivers nothing for this long counts as lost
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Delay between attempts to reopen a lost device
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Give up on a lost device after this long
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What to do when the input device is lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Reconnect {
+    /// End the capture
+    Never,
+    /// Keep trying for `RECONNECT_TIMEOUT`, then end the capture
+    Limited,
+    /// Keep trying until the capture is dropped (long-lived pre-roll stream)
+    Forever,
+}
+
+/// Audio missing from a recording because the input device was lost
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioGap {
+    /// Audio captured before the gap
+    pub position: Duration,
+    /// Length of the missing audio
+    pub lost: Duration,
+    /// Device capture resumed on, or None if it never came back
+    pub resumed_on: Option<String>,
+}
+
+impl fmt::Display for AudioGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.position.as_secs_f32();
+        write!(
+            f,
+            "Input device lost at {}:{:04.1}",
+            (secs / 60.0) as u32,
+            secs % 60.0
+        )?;
+        match &self.resumed_on {
+            Some(device) => write!(
+                f,
+                " - {:.1}s of audio missing, resumed on {}",
+                self.lost.as_secs_f32(),
+                device
+            ),
+            None => write!(f, " - recording ended early"),
+        }
+    }
+}
+
+/// Converts a replacement device's audio to the layout of the original one
+struct LayoutAdapter {
+    channels_in: u16,
+    channels_out: u16,
+    resampler: StreamResampler,
+    resampled: Vec<f32>,
+}
+
+impl LayoutAdapter {
+    fn process(&mut self, data: &[f32], out: &mut Vec<f32>) {
+        let mono = downmix_to_mono(data, self.channels_in, None);
+
+        self.resampled.clear();
+        if let Err(e) = self.resampler.process(&mono, &mut self.resampled) {
+            error!("Failed to convert replacement device audio: {}", e);
+            return;
+        }
+
+        for &sample in &self.resampled {
+            out.extend(std::iter::repeat_n(sample, self.channels_out as usize));
+        }
+    }
+}
+
+/// Queued CPAL capture that reopens the device after it is lost
+///
+/// Lives on a single thread, since CPAL streams are not `Send`.
+pub(super) struct LiveCapture {
+    /// `[audio] device` selector used to find the device again
+    selector: Option<String>,
+    reconnect: Reconnect,
+    sample_rate: u32,
+    channels: u16,
+    stream: Option<(cpal::Stream, QueueReader)>,
+    adapter: Option<LayoutAdapter>,
+    converted: Vec<f32>,
+    /// Frames delivered so far (original layout)
+    frames: usize,
+    last_data: Instant,
+    lost_at: Option<Instant>,
+    last_attempt: Option<Instant>,
+    gaps: Vec<AudioGap>,
+}
+
+impl LiveCapture {
+    /// Start capturing from `device`; a lost device is reopened by `selector`
+    pub(super) fn open(
+        device: &cpal::Device,
+        config: &cpal::SupportedStreamConfig,
+        selector: Option<String>,
+        reconnect: Reconnect,
+    ) -> anyhow::Result<Self> {
+        let stream = open_queued_stream(device, config)?;
+
+        Ok(Self {
+            selector,
+            reconnect,
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+            stream: Some(stream),
+            adapter: None,
+            converted: Vec::new(),
+            frames: 0,
+            last_data: Instant::now(),
+            lost_at: None,
+            last_attempt: None,
+            gaps: Vec::new(),
+        })
+    }
+
+    /// Take the audio queued so far; None once the device is gone for good
+    ///
+    /// Returns an empty slice while no audio is available (including while
+    /// the device is being reopened).
+    pub(super) fn read(&mut self) -> Option<&[f32]> {
+        if let Some((_, queue)) = self.stream.as_mut() {
+            match queue.take_error() {
+                Some(cpal::StreamError::DeviceNotAvailable) => {
+                    self.mark_lost("device not available");
+                },
+                // Backend errors (e.g. overruns) are often transient; a dead
+                // stream is caught by the stall check instead
+                Some(err) => warn!("Stream error: {}", err),
+                None if queue.is_empty() && self.last_data.elapsed() >= STALL_TIMEOUT => {
+                    self.mark_lost(&format!("no audio for {}s", STALL_TIMEOUT.as_secs()));
+                },
+                None => {},
+            }
+        }
+
+        if self.stream.is_none() && !self.try_reconnect() {
+            return None;
+        }
+
+        let Some((_, queue)) = self.stream.as_mut() else {
+            return Some(&[]);
+        };
+        let chunk = queue.read();
+        if chunk.is_empty() {
+            return Some(&[]);
+        }
+
+        self.last_data = Instant::now();
+        let chunk = match self.adapter.as_mut() {
+            Some(adapter) => {
+                self.converted.clear();
+                adapter.process(chunk, &mut self.converted);
+                &self.converted[..]
+            },
+            None => chunk,
+        };
+        self.frames += chunk.len() / self.channels.max(1) as usize;
+        Some(chunk)
+    }
+
+    /// Stop the stream and return anything it queued before stopping
+    pub(super) fn stop(&mut self) -> &[f32] {
+        let Some((stream, mut queue)) = self.stream.take() else {
+            return &[];
+        };
+        drop(stream);
+        queue.report_overflow();
+
+        self.converted.clear();
+        match self.adapter.as_mut() {
+            Some(adapter) => adapter.process(queue.read(), &mut self.converted),
+            None => self.converted.extend_from_slice(queue.read()),
+        }
+        &self.converted
+    }
+
+    /// When the device was lost, if capture is currently interrupted
+    pub(super) fn lost_since(&self) -> Option<Instant> {
+        self.lost_at
+    }
+
+    /// Gaps recorded since the last call
+    pub(super) fn take_gaps(&mut self) -> Vec<AudioGap> {
+        std::mem::take(&mut self.gaps)
+    }
+
+    fn position(&self) -> Duration {
+        Duration::from_secs_f64(self.frames as f64 / self.sample_rate as f64)
+    }
+
+    fn mark_lost(&mut self, reason: &str) {
+        if let Some((stream, queue)) = self.stream.take() {
+            drop(stream);
+            queue.report_overflow();
+        }
+
+        if self.reconnect == Reconnect::Never {
+            warn!("Input device lost ({})", reason);
+        } else {
+            warn!("Input device lost ({}) - reconnecting", reason);
+        }
+        self.lost_at = Some(self.last_data);
+        self.last_attempt = None;
+    }
+
+    /// Try to reopen the device; false once reconnecting has been given up
+    fn try_reconnect(&mut self) -> bool {
+        let Some(lost_at) = self.lost_at else {
+            return false;
+        };
+
+        let give_up = match self.reconnect {
+            Reconnect::Never => true,
+            Reconnect::Limited => lost_at.elapsed() >= RECONNECT_TIMEOUT,
+            Reconnect::Forever => false,
+        };
+        if give_up {
+            if self.reconnect == Reconnect::Limited {
+                error!(
+                    "Input device did not come back within {}s",
+                    RECONNECT_TIMEOUT.as_secs()
+                );
+            }
+            self.gaps.push(AudioGap {
+                position: self.position(),
+                lost: lost_at.elapsed(),
+                resumed_on: None,
+            });
+            self.lost_at = None;
+            self.reconnect = Reconnect::Never;
+            return false;
+        }
+
+        if self
+            .last_attempt
+            .is_some_and(|at| at.elapsed() < RECONNECT_INTERVAL)
+        {
+            return true;
+        }
+        self.last_attempt = Some(Instant::now());
+
+        match self.reopen() {
+            Ok(name) => {
+                let gap = AudioGap {
+                    position: self.position(),
+                    lost: lost_at.elapsed(),
+                    resumed_on: Some(name),
+                };
+                info!("{}", gap);
+                self.gaps.push(gap);
+                self.lost_at = None;
+                self.last_data = Instant::now();
+            },
+            Err(e) => info!("Input device not available yet: {:#}", e),
+        }
+        true
+    }
+
+    /// Open the configured device again, falling back to the system default
+    fn reopen(&mut self) -> anyhow::Result<String> {
+        let device = match select_input_device(self.selector.as_deref()) {
+            Ok(device) => device,
+            Err(e) if self.selector.is_some() => {
+                info!("Configured device unavailable ({:#}), trying default", e);
+                select_input_device(None)?
+            },
+            Err(e) => return Err(e),
+        };
+
+        let config = default_stream_config(&device)?;
+        let stream = open_queued_stream(&device, &config)?;
+
+        let rate = config.sample_rate().0;
+        let channels = config.channels();
+        self.adapter = if rate == self.sample_rate && channels == self.channels {
+            None
+        } else {
+            info!(
+                "Converting {}Hz/{}ch from the new device to {}Hz/{}ch",
+                rate, channels, self.sample_rate, self.channels
+            );
+            Some(LayoutAdapter {
+                channels_in: channels,
+                channels_out: self.channels,
+                resampler: StreamResampler::new(rate, self.sample_rate)?,
+                resampled: Vec::new(),
+            })
+        };
+        self.stream = Some(stream);
+
+        Ok(device.name().unwrap_or_else(|_| "Unknown".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gap_description() {
+        let resumed = AudioGap {
+            position: Duration::from_millis(72_400),
+            lost: Duration::from_millis(3_100),
+            resumed_on: Some("Built-in Microphone".to_string()),
+        };
+        assert_eq!(
+            resumed.to_string(),
+            "Input device lost at 1:12.4 - 3.1s of audio missing, resumed on Built-in Microphone"
+        );
+
+        let ended = AudioGap {
+            position: Duration::from_secs(5),
+            lost: Duration::from_secs(10),
+            resumed_on: None,
+        };
+        assert_eq!(
+            ended.to_string(),
+            "Input device lost at 0:05.0 - recording ended early"
+        );
+    }
+
+    #[test]
+    fn test_adapter_converts_to_original_layout() {
+        // Replacement device: 16kHz mono; original: 48kHz stereo
+        let mut adapter = LayoutAdapter {
+            channels_in: 1,
+            channels_out: 2,
+            resampler: StreamResampler::new(16000, 48000).unwrap(),
+            resampled: Vec::new(),
+        };
+
+        let mut out = Vec::new();
+        for _ in 0..10 {
+            adapter.process(&[0.25; 1600], &mut out);
+        }
+
+        // ~1s of 48kHz stereo (minus resampler latency), channels duplicated
+        let frames = out.len() / 2;
+        assert!(frames > 44000 && frames <= 48000, "frames: {}", frames);
+        assert!(out.chunks_exact(2).all(|f| f[0] == f[1]));
+    }
+}
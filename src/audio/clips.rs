@@ -0,0 +1,281 @@
+//! Saved recording clips: encoding, unique naming and retention
+//!
+//! The daemon can keep every recording it transcribes for debugging. Clips are
+//! written as 16-bit FLAC by default (about an eighth of the size of the old
+//! 32-bit float WAVs) and pruned after each save according to the `[audio]`
+//! retention settings.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info};
+
+/// Filename prefix of saved clips; files without it are never pruned
+const CLIP_PREFIX: &str = "recording_";
+
+/// Samples per FLAC block (~256ms at 16kHz)
+const FLAC_BLOCK_SIZE: usize = 4096;
+
+/// On-disk format for saved clips
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipFormat {
+    /// Lossless 16-bit FLAC
+    #[default]
+    Flac,
+    /// 16-bit PCM WAV
+    Wav16,
+    /// 32-bit float WAV (exact samples fed to the model, largest)
+    Wav32,
+}
+
+impl ClipFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ClipFormat::Flac => "flac",
+            ClipFormat::Wav16 | ClipFormat::Wav32 => "wav",
+        }
+    }
+}
+
+/// Limits applied to the clips directory (all unlimited by default)
+#[derive(Debug, Clone, Default)]
+pub struct ClipRetention {
+    /// Delete clips older than this
+    pub max_age: Option<Duration>,
+    /// Delete the oldest clips once the directory holds more than this many bytes
+    pub max_total_bytes: Option<u64>,
+    /// Keep at most this many clips
+    pub max_count: Option<usize>,
+}
+
+impl ClipRetention {
+    /// Whether any limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_total_bytes.is_some() || self.max_count.is_some()
+    }
+}
+
+/// Outcome of a prune pass
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    pub removed: usize,
+    pub removed_bytes: u64,
+    pub kept: usize,
+    pub kept_bytes: u64,
+}
+
+/// Write 16kHz mono samples to a new, uniquely named clip in `dir`
+pub fn save_clip(
+    samples: &[f32],
+    dir: &Path,
+    sample_rate: u32,
+    format: ClipFormat,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir).context("Failed to create audio clips directory")?;
+
+    let (path, file) = create_unique_clip(dir, format.extension())?;
+    let written = match format {
+        ClipFormat::Flac => write_flac(samples, sample_rate, file),
+        ClipFormat::Wav16 | ClipFormat::Wav32 => write_wav(samples, sample_rate, format, file),
+    };
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+
+    info!("Audio saved to: {}", path.display());
+    Ok(path)
+}
+
+/// Create a clip file named by timestamp, adding a counter if the name is taken
+///
+/// `create_new` makes the check atomic, so concurrent saves never share a file.
+fn create_unique_clip(dir: &Path, extension: &str) -> Result<(PathBuf, File)> {
+    let stem = format!(
+        "{}{}",
+        CLIP_PREFIX,
+        chrono::Local::now().format("%Y%m%d_%H%M%S_%3f")
+    );
+
+    for attempt in 0u32.. {
+        let name = match attempt {
+            0 => format!("{}.{}", stem, extension),
+            n => format!("{}_{}.{}", stem, n, extension),
+        };
+        let path = dir.join(name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", path.display()));
+            },
+        }
+    }
+
+    unreachable!("clip name counter exhausted")
+}
+
+/// Convert to 16-bit integer samples with clipping
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn write_wav(samples: &[f32], sample_rate: u32, format: ClipFormat, file: File) -> Result<()> {
+    let float = format == ClipFormat::Wav32;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: if float { 32 } else { 16 },
+        sample_format: if float {
+            hound::SampleFormat::Float
+        } else {
+            hound::SampleFormat::Int
+        },
+    };
+
+    let mut writer =
+        hound::WavWriter::new(BufWriter::new(file), spec).context("Failed to create WAV file")?;
+
+    for &sample in samples {
+        if float {
+            writer.write_sample(sample)
+        } else {
+            writer.write_sample(to_i16(sample))
+        }
+        .context("Failed to write sample")?;
+    }
+
+    writer.finalize().context("Failed to finalize WAV file")?;
+    Ok(())
+}
+
+fn write_flac(samples: &[f32], sample_rate: u32, file: File) -> Result<()> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let pcm: Vec<i32> = samples.iter().map(|&s| to_i16(s) as i32).collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| anyhow::anyhow!("Invalid FLAC encoder config: {:?}", e))?;
+    let source = flacenc::source::MemSource::from_samples(&pcm, 1, 16, sample_rate as usize);
+    let mut stream = flacenc::encode_with_fixed_block_size(&config, source, FLAC_BLOCK_SIZE)
+        .map_err(|e| anyhow::anyhow!("Failed to encode FLAC: {}", e))?;
+
+    // The encoder counts the short final block as the minimum block size, which
+    // makes decoders treat the stream as variable-blocksize and misread it
+    stream
+        .stream_info_mut()
+        .set_block_sizes(FLAC_BLOCK_SIZE, FLAC_BLOCK_SIZE)
+        .map_err(|e| anyhow::anyhow!("Invalid FLAC block size: {:?}", e))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow::anyhow!("Failed to encode FLAC: {}", e))?;
+
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(sink.as_slice())
+        .context("Failed to write FLAC file")?;
+    writer.flush().context("Failed to write FLAC file")?;
+    Ok(())
+}
+
+/// A saved clip found on disk
+struct ClipEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Saved clips in `dir`, newest first (a missing directory has no clips)
+fn list_clips(dir: &Path) -> Result<Vec<ClipEntry>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", dir.display()));
+        },
+    };
+
+    let mut clips = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+
+        let name = entry.file_name();
+        let is_clip = name.to_str().is_some_and(|n| n.starts_with(CLIP_PREFIX))
+            && path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e == "wav" || e == "flac");
+        if !is_clip {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        clips.push(ClipEntry {
+            path,
+            size: metadata.len(),
+            modified: metadata.modified()?,
+        });
+    }
+
+    clips.sort_by_key(|clip| std::cmp::Reverse(clip.modified));
+    Ok(clips)
+}
+
+/// Delete clips that fall outside the retention limits, oldest first
+///
+/// With `dry_run` nothing is deleted, but the report shows what would be.
+pub fn prune_clips(dir: &Path, retention: &ClipRetention, dry_run: bool) -> Result<PruneReport> {
+    let now = SystemTime::now();
+    let mut report = PruneReport::default();
+    // Once a limit is hit every older clip goes too, even if it would still fit
+    let mut full = false;
+
+    for clip in list_clips(dir)? {
+        let age = now.duration_since(clip.modified).unwrap_or_default();
+
+        full = full
+            || retention.max_count.is_some_and(|max| report.kept >= max)
+            || retention
+                .max_total_bytes
+                .is_some_and(|max| report.kept_bytes + clip.size > max);
+        let expired = full || retention.max_age.is_some_and(|max| age > max);
+
+        if !expired {
+            report.kept += 1;
+            report.kept_bytes += clip.size;
+            continue;
+        }
+
+        if !dry_run {
+            fs::remove_file(&clip.path)
+                .with_context(|| format!("Failed to delete {}", clip.path.display()))?;
+            debug!("Pruned audio clip: {}", clip.path.display());
+        }
+        report.removed += 1;
+        report.removed_bytes += clip.size;
+    }
+
+    if report.removed > 0 && !dry_run {
+        info!(
+            "Pruned {} audio clips ({} bytes)",
+            report.removed, report.removed_bytes
+        );
+    }
+
+    Ok(report)
+}
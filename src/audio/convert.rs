@@ -0,0 +1,36 @@
+//! Sample format conversion and channel downmixing
+//!
+//! Devices deliver interleaved audio in whatever format and channel layout
+//! they support; everything downstream works on mono f32.
+
+use cpal::{FromSample, Sample};
+
+/// Convert device samples of any CPAL format to f32, replacing the contents of `out`
+pub fn samples_to_f32<T>(data: &[T], out: &mut Vec<f32>)
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    out.clear();
+    out.extend(data.iter().map(|&s| f32::from_sample(s)));
+}
+
+/// Convert interleaved audio to mono
+///
+/// Averages all channels, or keeps only `channel` (0-based) when set.
+/// A trailing partial frame is dropped.
+pub fn downmix_to_mono(samples: &[f32], channels: u16, channel: Option<u16>) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+
+    match channel {
+        Some(index) => samples
+            .chunks_exact(channels)
+            .map(|frame| frame[(index as usize).min(channels - 1)])
+            .collect(),
+        None if channels == 1 => samples.to_vec(),
+        None => samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect(),
+    }
+}
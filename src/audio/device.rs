@@ -0,0 +1,178 @@
+//! Input device discovery and selection
+//!
+//! Devices are selected by the `[audio] device` config key, which accepts either
+//! the index shown by `hyprvoice devices` or a (case-insensitive) name substring.
+
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait};
+use tracing::{info, warn};
+
+/// Summary of an input device and the stream configurations it supports
+#[derive(Debug, Clone)]
+pub struct InputDeviceInfo {
+    /// Position in the host's device enumeration (usable as `[audio] device`)
+    pub index: usize,
+    pub name: String,
+    /// Whether this is the host's default input device
+    pub is_default: bool,
+    /// Human-readable supported configs (e.g. "2 ch, 44100-48000 Hz, i16")
+    pub configs: Vec<String>,
+}
+
+/// List every input device on the default host with its supported configs
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .context("Failed to enumerate input devices")?;
+
+    let mut infos = Vec::new();
+    for (index, device) in devices.enumerate() {
+        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+        let configs = match device.supported_input_configs() {
+            Ok(ranges) => ranges
+                .map(|range| {
+                    let min = range.min_sample_rate().0;
+                    let max = range.max_sample_rate().0;
+                    let rates = if min == max {
+                        format!("{} Hz", min)
+                    } else {
+                        format!("{}-{} Hz", min, max)
+                    };
+                    format!(
+                        "{} ch, {}, {}",
+                        range.channels(),
+                        rates,
+                        range.sample_format()
+                    )
+                })
+                .collect(),
+            Err(e) => vec![format!("(failed to query configs: {})", e)],
+        };
+
+        infos.push(InputDeviceInfo {
+            index,
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            configs,
+        });
+    }
+
+    Ok(infos)
+}
+
+/// Resolve an input device from an optional selector
+///
+/// `None`, an empty string or `"default"` selects the host default device.
+/// Otherwise the selector is tried as an index first, then as a name.
+pub fn select_input_device(selector: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    let selector = selector.map(str::trim).unwrap_or_default();
+    if selector.is_empty() || selector.eq_ignore_ascii_case("default") {
+        let device = host
+            .default_input_device()
+            .context("No input device available. Check microphone permissions.")?;
+        info!(
+            "Using default audio device: {}",
+            device.name().unwrap_or_else(|_| "Unknown".to_string())
+        );
+        return Ok(device);
+    }
+
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .context("Failed to enumerate input devices")?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "Unknown".to_string()))
+        .collect();
+
+    let index = match_device(&names, selector).with_context(|| {
+        format!(
+            "Input device '{}' not found. Available: {}\nRun 'hyprvoice devices' to list devices.",
+            selector,
+            if names.is_empty() {
+                "(none)".to_string()
+            } else {
+                names.join(", ")
+            }
+        )
+    })?;
+
+    info!("Using audio device [{}]: {}", index, names[index]);
+    devices
+        .into_iter()
+        .nth(index)
+        .context("Input device disappeared during selection")
+}
+
+/// Find the device index matching a selector
+///
+/// Numeric selectors are treated as indices. Names match exactly first
+/// (case-insensitive), then by substring; the first substring match wins.
+pub fn match_device(names: &[String], selector: &str) -> Option<usize> {
+    if let Ok(index) = selector.parse::<usize>() {
+        return (index < names.len()).then_some(index);
+    }
+
+    let needle = selector.to_lowercase();
+    if let Some(index) = names.iter().position(|n| n.to_lowercase() == needle) {
+        return Some(index);
+    }
+
+    let matches: Vec<usize> = names
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| n.to_lowercase().contains(&needle))
+        .map(|(i, _)| i)
+        .collect();
+
+    if matches.len() > 1 {
+        warn!(
+            "Device selector '{}' matches {} devices, using the first: {}",
+            selector,
+            matches.len(),
+            names[matches[0]]
+        );
+    }
+
+    matches.first().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> Vec<String> {
+        vec![
+            "default".to_string(),
+            "HD Pro Webcam C920 Analog Stereo".to_string(),
+            "USB Headset Mono".to_string(),
+            "Monitor of Built-in Audio".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_match_device_by_index() {
+        assert_eq!(match_device(&names(), "2"), Some(2));
+        assert_eq!(match_device(&names(), "9"), None);
+    }
+
+    #[test]
+    fn test_match_device_by_name() {
+        assert_eq!(match_device(&names(), "usb headset"), Some(2));
+        assert_eq!(match_device(&names(), "Webcam"), Some(1));
+        assert_eq!(match_device(&names(), "bluetooth"), None);
+    }
+
+    #[test]
+    fn test_match_device_prefers_exact_name() {
+        let names = vec!["USB Headset Mono".to_string(), "USB Headset".to_string()];
+        assert_eq!(match_device(&names, "usb headset"), Some(1));
+    }
+}
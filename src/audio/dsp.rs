@@ -0,0 +1,261 @@
+//! Audio preprocessing chain applied before transcription
+//!
+//! Quiet laptop mics and fan rumble hurt recognition, so recordings can be
+//! conditioned with a high-pass filter, a noise gate, automatic gain control
+//! and peak/RMS normalization. Every stage is optional and runs on 16kHz mono.
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::vad::rms_db;
+
+/// Default peak normalization target (dBFS)
+const PEAK_TARGET_DB: f32 = -1.0;
+
+/// Default RMS normalization and AGC target (dBFS)
+const RMS_TARGET_DB: f32 = -20.0;
+
+/// Maximum gain applied by normalization and AGC, so silence is not blown up
+const MAX_GAIN_DB: f32 = 30.0;
+
+/// Block length for level measurement in the gate and AGC
+const BLOCK_MS: u32 = 10;
+
+/// Noise gate stays open this long after the level drops below the threshold
+const GATE_HOLD_MS: u32 = 150;
+
+/// Blocks quieter than this never raise the AGC gain (treated as background)
+const AGC_FLOOR_DB: f32 = -50.0;
+
+/// Per-block AGC gain change when lowering (fast) and raising (slow), in dB
+const AGC_ATTACK_DB: f32 = 3.0;
+const AGC_RELEASE_DB: f32 = 0.3;
+
+/// Level normalization mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    #[default]
+    Off,
+    /// Scale so the loudest sample hits the target
+    Peak,
+    /// Scale so the average level hits the target (limited to avoid clipping)
+    Rms,
+}
+
+/// Preprocessing stages to apply (all off by default)
+#[derive(Debug, Clone, Default)]
+pub struct DspOptions {
+    /// High-pass cutoff in Hz, also removes DC offset
+    pub high_pass_hz: Option<f32>,
+    /// Silence blocks below this level (dBFS)
+    pub noise_gate_db: Option<f32>,
+    /// Automatic gain control towards a constant speech level
+    pub agc: bool,
+    pub normalize: Normalization,
+    /// Normalization target in dBFS (None = -1 for peak, -20 for RMS)
+    pub normalize_target_db: Option<f32>,
+}
+
+impl DspOptions {
+    fn is_noop(&self) -> bool {
+        self.high_pass_hz.is_none()
+            && self.noise_gate_db.is_none()
+            && !self.agc
+            && self.normalize == Normalization::Off
+    }
+
+    /// Whether the output for the start of a clip depends only on that start
+    ///
+    /// True unless normalization is on, which scales by the whole clip's level.
+    pub fn is_causal(&self) -> bool {
+        self.normalize == Normalization::Off
+    }
+}
+
+/// Run the enabled stages in order: high-pass, noise gate, AGC, normalization
+pub fn apply_dsp(samples: &mut [f32], sample_rate: u32, options: &DspOptions) {
+    let stages = run_stages(samples, sample_rate, options);
+    if !stages.is_empty() {
+        info!("Audio preprocessing: {}", stages.join(", "));
+    }
+}
+
+/// `apply_dsp` without logging; returns a description of each stage applied
+pub(super) fn run_stages(
+    samples: &mut [f32],
+    sample_rate: u32,
+    options: &DspOptions,
+) -> Vec<String> {
+    let mut stages = Vec::new();
+    if options.is_noop() || samples.is_empty() {
+        return stages;
+    }
+
+    if let Some(cutoff) = options.high_pass_hz {
+        high_pass(samples, sample_rate, cutoff);
+        stages.push(format!("high-pass {}Hz", cutoff));
+    }
+
+    if let Some(threshold) = options.noise_gate_db {
+        noise_gate(samples, sample_rate, threshold);
+        stages.push(format!("noise gate {}dB", threshold));
+    }
+
+    if options.agc {
+        agc(samples, sample_rate, RMS_TARGET_DB);
+        stages.push("AGC".to_string());
+    }
+
+    match options.normalize {
+        Normalization::Off => {},
+        Normalization::Peak => {
+            let target = options.normalize_target_db.unwrap_or(PEAK_TARGET_DB);
+            let gain = normalize_peak(samples, target);
+            stages.push(format!("peak normalize {:+.1}dB", gain));
+        },
+        Normalization::Rms => {
+            let target = options.normalize_target_db.unwrap_or(RMS_TARGET_DB);
+            let gain = normalize_rms(samples, target);
+            stages.push(format!("RMS normalize {:+.1}dB", gain));
+        },
+    }
+
+    stages
+}
+
+/// Second-order Butterworth high-pass filter (removes DC and low rumble)
+fn high_pass(samples: &mut [f32], sample_rate: u32, cutoff_hz: f32) {
+    let nyquist = sample_rate as f32 / 2.0;
+    if cutoff_hz <= 0.0 || cutoff_hz >= nyquist {
+        return;
+    }
+
+    // RBJ cookbook coefficients, Q = 1/sqrt(2)
+    let omega = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32;
+    let alpha = omega.sin() / std::f32::consts::SQRT_2;
+    let cos = omega.cos();
+    let a0 = 1.0 + alpha;
+    let b0 = (1.0 + cos) / 2.0 / a0;
+    let b1 = -(1.0 + cos) / a0;
+    let b2 = b0;
+    let a1 = -2.0 * cos / a0;
+    let a2 = (1.0 - alpha) / a0;
+
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for sample in samples.iter_mut() {
+        let x0 = *sample;
+        let y0 = b0 * x0 + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+        x2 = x1;
+        x1 = x0;
+        y2 = y1;
+        y1 = y0;
+        *sample = y0;
+    }
+}
+
+/// Mute blocks below `threshold_db`, with hold time and short ramps to avoid clicks
+fn noise_gate(samples: &mut [f32], sample_rate: u32, threshold_db: f32) {
+    let block = block_len(sample_rate);
+    let hold_blocks = (GATE_HOLD_MS / BLOCK_MS) as usize;
+
+    let mut held = 0usize;
+    let mut gain = 0.0f32;
+    for chunk in samples.chunks_mut(block) {
+        let target = if rms_db(chunk) >= threshold_db {
+            held = hold_blocks;
+            1.0
+        } else if held > 0 {
+            held -= 1;
+            1.0
+        } else {
+            0.0
+        };
+
+        ramp_gain(chunk, gain, target);
+        gain = target;
+    }
+}
+
+/// Block-wise automatic gain control towards `target_db`
+///
+/// Gain drops quickly on loud passages and rises slowly, and background
+/// blocks below -50dBFS never raise it.
+fn agc(samples: &mut [f32], sample_rate: u32, target_db: f32) {
+    let block = block_len(sample_rate);
+
+    let mut gain_db = 0.0f32;
+    let mut previous = db_to_gain(gain_db);
+    for chunk in samples.chunks_mut(block) {
+        let level = rms_db(chunk);
+        if level > AGC_FLOOR_DB {
+            let wanted = (target_db - level).clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+            let step = if wanted < gain_db {
+                -AGC_ATTACK_DB
+            } else {
+                AGC_RELEASE_DB
+            };
+            gain_db = if step < 0.0 {
+                (gain_db + step).max(wanted)
+            } else {
+                (gain_db + step).min(wanted)
+            };
+        }
+
+        let gain = db_to_gain(gain_db);
+        ramp_gain(chunk, previous, gain);
+        previous = gain;
+    }
+
+    for sample in samples.iter_mut() {
+        *sample = sample.clamp(-1.0, 1.0);
+    }
+}
+
+/// Scale so the loudest sample reaches `target_db`; returns the applied gain in dB
+fn normalize_peak(samples: &mut [f32], target_db: f32) -> f32 {
+    let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+    if peak <= 0.0 {
+        return 0.0;
+    }
+
+    let gain_db = (target_db - 20.0 * peak.log10()).min(MAX_GAIN_DB);
+    apply_gain(samples, db_to_gain(gain_db));
+    gain_db
+}
+
+/// Scale so the RMS level reaches `target_db` without clipping; returns the gain in dB
+fn normalize_rms(samples: &mut [f32], target_db: f32) -> f32 {
+    let level = rms_db(samples);
+    let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+    if peak <= 0.0 {
+        return 0.0;
+    }
+
+    let headroom_db = -20.0 * peak.log10();
+    let gain_db = (target_db - level).min(MAX_GAIN_DB).min(headroom_db);
+    apply_gain(samples, db_to_gain(gain_db));
+    gain_db
+}
+
+fn block_len(sample_rate: u32) -> usize {
+    (sample_rate * BLOCK_MS / 1000).max(1) as usize
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn apply_gain(samples: &mut [f32], gain: f32) {
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+/// Apply a gain that moves linearly from `from` to `to` across the block
+fn ramp_gain(samples: &mut [f32], from: f32, to: f32) {
+    let len = samples.len() as f32;
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample *= from + (to - from) * (i + 1) as f32 / len;
+    }
+}
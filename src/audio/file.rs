@@ -0,0 +1,104 @@
+//! Audio file decoding for offline transcription
+//!
+//! Decodes WAV and FLAC (including the clips the daemon saves), MP3
+//! and Ogg Vorbis through symphonia, then downmixes and resamples to 16kHz mono.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tracing::{info, warn};
+
+use super::{StreamResampler, WHISPER_SAMPLE_RATE, downmix_to_mono};
+
+/// Decode an audio file to 16kHz mono f32 PCM
+pub fn load_audio_file(path: &Path) -> Result<Vec<f32>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .with_context(|| format!("Unsupported or corrupt audio file: {}", path.display()))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .context("No audio track found")?;
+    let track_id = track.id;
+    let mut sample_rate = track
+        .codec_params
+        .sample_rate
+        .unwrap_or(WHISPER_SAMPLE_RATE);
+    let mut channels = track.codec_params.channels.map_or(1, |c| c.count() as u16);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .context("Unsupported audio codec")?;
+
+    let mut samples = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            },
+            Err(e) => return Err(e).context("Failed to read audio packet"),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                warn!("Skipping undecodable packet: {}", e);
+                continue;
+            },
+            Err(e) => return Err(e).context("Failed to decode audio"),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate = spec.rate;
+        channels = spec.channels.count() as u16;
+
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+
+    let frames = samples.len() / channels.max(1) as usize;
+    info!(
+        "Decoded {}: {:.2}s at {}Hz, {} channels",
+        path.display(),
+        frames as f32 / sample_rate as f32,
+        sample_rate,
+        channels
+    );
+
+    let mono = downmix_to_mono(&samples, channels, None);
+    let mut resampled = Vec::new();
+    // Same path as live capture; passes 16kHz audio through unchanged
+    let mut resampler = StreamResampler::new(sample_rate, WHISPER_SAMPLE_RATE)?;
+    resampler.process(&mono, &mut resampled)?;
+    resampler.finish(&mut resampled)?;
+    Ok(resampled)
+}
@@ -0,0 +1,90 @@
+//! Live input level metering for UI feedback
+//!
+//! The recorder feeds every block of audio into a [`LevelMeter`]; readers take
+//! the level accumulated since their last read (RMS and peak over that window).
+
+use std::sync::{Arc, Mutex};
+
+use super::vad::rms_db;
+
+/// Input level over a short window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioLevel {
+    pub rms_db: f32,
+    pub peak_db: f32,
+}
+
+impl AudioLevel {
+    /// Level on a 0-100 scale (-60 dBFS and below is 0), for meters and glyphs
+    pub fn percent(&self) -> u8 {
+        ((self.rms_db + 60.0) / 60.0 * 100.0).clamp(0.0, 100.0) as u8
+    }
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    sum_squares: f64,
+    peak: f32,
+    samples: usize,
+}
+
+/// Shared level accumulator (cheap to clone)
+#[derive(Debug, Clone, Default)]
+pub struct LevelMeter(Arc<Mutex<Window>>);
+
+impl LevelMeter {
+    /// Add a block of mono samples to the current window
+    pub fn add(&self, samples: &[f32]) {
+        let Ok(mut window) = self.0.lock() else {
+            return;
+        };
+        for &sample in samples {
+            window.sum_squares += (sample as f64) * (sample as f64);
+            window.peak = window.peak.max(sample.abs());
+        }
+        window.samples += samples.len();
+    }
+
+    /// Level since the previous call, or None if no audio arrived in between
+    pub fn take(&self) -> Option<AudioLevel> {
+        let mut window = self.0.lock().ok()?;
+        if window.samples == 0 {
+            return None;
+        }
+
+        let window = std::mem::take(&mut *window);
+        let rms = (window.sum_squares / window.samples as f64).sqrt() as f32;
+        Some(AudioLevel {
+            rms_db: rms_db(&[rms]),
+            peak_db: rms_db(&[window.peak]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_resets_window() {
+        let meter = LevelMeter::default();
+        assert_eq!(meter.take(), None);
+
+        meter.add(&[0.5, -0.5, 0.5, -0.5]);
+        let level = meter.take().unwrap();
+        assert!((level.rms_db + 6.02).abs() < 0.01);
+        assert!((level.peak_db + 6.02).abs() < 0.01);
+        assert_eq!(meter.take(), None);
+    }
+
+    #[test]
+    fn test_percent_scale() {
+        let level = |rms_db| AudioLevel {
+            rms_db,
+            peak_db: 0.0,
+        };
+        assert_eq!(level(-100.0).percent(), 0);
+        assert_eq!(level(-30.0).percent(), 50);
+        assert_eq!(level(0.0).percent(), 100);
+    }
+}
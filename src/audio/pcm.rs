@@ -0,0 +1,183 @@
+//! Raw PCM input from a byte stream (stdin or a daemon socket)
+//!
+//! Lets `parecord --raw | hyprvoice transcribe --stdin` and audio piped in over
+//! SSH use the same downmix/resample path as the microphone.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::str::FromStr;
+
+use super::AudioSource;
+
+/// Frames read per chunk (~20ms at 48kHz)
+const CHUNK_FRAMES: usize = 1024;
+
+/// Sample encoding of a raw PCM stream (always little-endian)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PcmFormat {
+    U8,
+    #[default]
+    S16le,
+    S24le,
+    S32le,
+    F32le,
+}
+
+impl PcmFormat {
+    /// Bytes per sample
+    pub fn sample_size(self) -> usize {
+        match self {
+            PcmFormat::U8 => 1,
+            PcmFormat::S16le => 2,
+            PcmFormat::S24le => 3,
+            PcmFormat::S32le | PcmFormat::F32le => 4,
+        }
+    }
+
+    /// Decode one sample to f32 in [-1.0, 1.0]
+    fn decode(self, bytes: &[u8]) -> f32 {
+        match self {
+            PcmFormat::U8 => (bytes[0] as f32 - 128.0) / 128.0,
+            PcmFormat::S16le => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            PcmFormat::S24le => {
+                // Place the 24 bits at the top of an i32 so the sign extends
+                i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2_147_483_648.0
+            },
+            PcmFormat::S32le => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                    / 2_147_483_648.0
+            },
+            PcmFormat::F32le => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl fmt::Display for PcmFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PcmFormat::U8 => "u8",
+            PcmFormat::S16le => "s16le",
+            PcmFormat::S24le => "s24le",
+            PcmFormat::S32le => "s32le",
+            PcmFormat::F32le => "f32le",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for PcmFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "u8" => Ok(PcmFormat::U8),
+            "s16le" | "s16" => Ok(PcmFormat::S16le),
+            "s24le" | "s24" => Ok(PcmFormat::S24le),
+            "s32le" | "s32" => Ok(PcmFormat::S32le),
+            "f32le" | "f32" | "float32le" => Ok(PcmFormat::F32le),
+            other => Err(format!(
+                "unknown PCM format '{}' (expected u8, s16le, s24le, s32le or f32le)",
+                other
+            )),
+        }
+    }
+}
+
+/// Layout of a raw PCM stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcmSpec {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: PcmFormat,
+}
+
+/// Audio source decoding interleaved raw PCM from a reader until EOF
+pub struct PcmSource {
+    name: String,
+    reader: Box<dyn Read + Send>,
+    spec: PcmSpec,
+    /// Bytes of an incomplete frame carried over to the next read
+    pending: Vec<u8>,
+}
+
+impl PcmSource {
+    pub fn new(
+        name: impl Into<String>,
+        reader: Box<dyn Read + Send>,
+        spec: PcmSpec,
+    ) -> Result<Self> {
+        if spec.sample_rate == 0 || spec.channels == 0 {
+            anyhow::bail!(
+                "Invalid PCM layout: {}Hz, {} channels",
+                spec.sample_rate,
+                spec.channels
+            );
+        }
+
+        Ok(Self {
+            name: name.into(),
+            reader,
+            spec,
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl AudioSource for PcmSource {
+    fn name(&self) -> String {
+        format!(
+            "{} ({}, {}Hz, {} channels)",
+            self.name, self.spec.format, self.spec.sample_rate, self.spec.channels
+        )
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels
+    }
+
+    /// Reads block, so `should_stop` is only checked between chunks
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        let sample_size = self.spec.format.sample_size();
+        let frame_size = sample_size * self.spec.channels as usize;
+
+        let mut buffer = vec![0u8; CHUNK_FRAMES * frame_size];
+        let mut samples = Vec::with_capacity(CHUNK_FRAMES * self.spec.channels as usize);
+
+        while !should_stop() {
+            let carried = self.pending.len();
+            buffer[..carried].copy_from_slice(&self.pending);
+
+            let read = match self.reader.read(&mut buffer[carried..]) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e).context("Failed to read PCM input"),
+            };
+
+            // Only decode whole frames; keep the remainder for the next read
+            let available = carried + read;
+            let whole = available - available % frame_size;
+            self.pending.clear();
+            self.pending.extend_from_slice(&buffer[whole..available]);
+
+            samples.clear();
+            samples.extend(
+                buffer[..whole]
+                    .chunks_exact(sample_size)
+                    .map(|bytes| self.spec.format.decode(bytes)),
+            );
+            if !samples.is_empty() {
+                sink(&samples);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+//! Always-open input stream with a pre-roll ring buffer
+//!
+//! Users often start talking while still pressing the hotkey. With pre-roll
+//! enabled the daemon keeps the microphone open and remembers the last few
+//! hundred milliseconds, which are prepended when a recording starts. Nothing
+//! is written anywhere until a recording is requested.
+
+use anyhow::Result;
+use cpal::traits::DeviceTrait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use super::AudioSource;
+use super::capture::{AudioGap, LiveCapture, Reconnect};
+use super::source::{POLL_INTERVAL, default_stream_config};
+
+/// Audio shared between the stream callback and the active recording
+struct Shared {
+    /// Most recent interleaved samples, bounded to `capacity`
+    ring: VecDeque<f32>,
+    capacity: usize,
+    frame_len: usize,
+    /// Receiver of live audio while a recording is running
+    live: Option<mpsc::Sender<Vec<f32>>>,
+    /// Frames sent to the live receiver so far
+    live_frames: usize,
+    /// Gaps during the live recording, with the live frames sent before each
+    gaps: Vec<(usize, AudioGap)>,
+    /// Set while the device is lost and being reopened
+    lost_since: Option<Instant>,
+}
+
+impl Shared {
+    fn new(capacity: usize, frame_len: usize) -> Self {
+        Self {
+            ring: VecDeque::with_capacity(capacity),
+            capacity,
+            frame_len,
+            live: None,
+            live_frames: 0,
+            gaps: Vec::new(),
+            lost_since: None,
+        }
+    }
+
+    fn push(&mut self, data: &[f32]) {
+        if let Some(tx) = &self.live {
+            if tx.send(data.to_vec()).is_ok() {
+                self.live_frames += data.len() / self.frame_len;
+                return;
+            }
+            self.live = None;
+        }
+
+        self.ring.extend(data);
+        if self.ring.len() > self.capacity {
+            let excess = self.ring.len() - self.capacity;
+            self.ring.drain(..excess);
+        }
+    }
+
+    /// Track device loss; gaps only matter while a recording is live
+    fn update_connection(&mut self, gaps: Vec<AudioGap>, lost_since: Option<Instant>) {
+        if self.live.is_some() {
+            let frames = self.live_frames;
+            self.gaps.extend(gaps.into_iter().map(|gap| (frames, gap)));
+        }
+        self.lost_since = lost_since;
+    }
+}
+
+/// Input stream held open in the background, buffering the last `duration` of audio
+pub struct PrerollBuffer {
+    shared: Arc<Mutex<Shared>>,
+    name: String,
+    sample_rate: u32,
+    channels: u16,
+    duration: Duration,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PrerollBuffer {
+    /// Open `device` and start buffering
+    ///
+    /// If the device is lost, the one matching `selector` (or the default) is
+    /// reopened for as long as the buffer lives.
+    pub fn start(
+        device: cpal::Device,
+        selector: Option<String>,
+        duration: Duration,
+    ) -> Result<Self> {
+        let config = default_stream_config(&device)?;
+        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+        let frame_len = config.channels().max(1) as usize;
+        let frames = (duration.as_secs_f64() * config.sample_rate().0 as f64) as usize;
+        let shared = Arc::new(Mutex::new(Shared::new(frames * frame_len, frame_len)));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
+
+        // The CPAL stream is not Send, so it is built and dropped on its own thread
+        let thread = {
+            let shared = Arc::clone(&shared);
+            let stop = Arc::clone(&stop);
+            let config = config.clone();
+            thread::Builder::new()
+                .name("audio-preroll".to_string())
+                .spawn(move || {
+                    let opened = LiveCapture::open(&device, &config, selector, Reconnect::Forever);
+                    let mut capture = match opened {
+                        Ok(capture) => {
+                            let _ = ready_tx.send(Ok(()));
+                            capture
+                        },
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        },
+                    };
+
+                    // Move audio from the lock-free queue into the shared buffer
+                    while !stop.load(Ordering::SeqCst) {
+                        let chunk = capture.read().unwrap_or_default();
+                        if let Ok(mut shared) = shared.lock() {
+                            if !chunk.is_empty() {
+                                shared.push(chunk);
+                            }
+                            shared.update_connection(capture.take_gaps(), capture.lost_since());
+                        }
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                    capture.stop();
+                })?
+        };
+
+        ready_rx
+            .recv()
+            .map_err(|_| anyhow::anyhow!("Pre-roll thread exited during startup"))??;
+
+        info!(
+            "Pre-roll buffer active on {} ({}ms) - microphone is held open",
+            name,
+            duration.as_millis()
+        );
+
+        Ok(Self {
+            shared,
+            name,
+            sample_rate: config.sample_rate().0,
+            channels: config.channels(),
+            duration,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Length of audio kept before a recording starts
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Source for one recording: buffered pre-roll followed by live audio
+    pub fn source(&self) -> PrerollSource {
+        PrerollSource {
+            shared: Arc::clone(&self.shared),
+            name: self.name.clone(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            gaps: Vec::new(),
+        }
+    }
+}
+
+impl Drop for PrerollBuffer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("Pre-roll thread panicked");
+            }
+        }
+        info!("Pre-roll buffer closed");
+    }
+}
+
+/// Recording source reading from a [`PrerollBuffer`]
+pub struct PrerollSource {
+    shared: Arc<Mutex<Shared>>,
+    name: String,
+    sample_rate: u32,
+    channels: u16,
+    gaps: Vec<AudioGap>,
+}
+
+impl AudioSource for PrerollSource {
+    fn name(&self) -> String {
+        format!("{} (pre-roll)", self.name)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        let (tx, rx) = mpsc::channel::<Vec<f32>>();
+
+        // Take the buffered audio and switch to live delivery under one lock,
+        // so no samples are lost or duplicated between the two
+        let preroll: Vec<f32> = {
+            let mut shared = self
+                .shared
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Pre-roll buffer mutex poisoned: {}", e))?;
+            shared.live = Some(tx);
+            shared.live_frames = 0;
+            shared.gaps.clear();
+            shared.ring.drain(..).collect()
+        };
+
+        let frame_len = self.channels.max(1) as usize;
+        info!(
+            "Prepending {:.2}s of pre-roll audio",
+            (preroll.len() / frame_len) as f32 / self.sample_rate as f32
+        );
+        if !preroll.is_empty() {
+            sink(&preroll);
+        }
+        info!("Recording started - speak now!");
+
+        while !should_stop() {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(chunk) => sink(&chunk),
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        // Hand audio back to the ring buffer, then flush what was already queued
+        let preroll_frames = preroll.len() / frame_len;
+        let position = |frames: usize| {
+            Duration::from_secs_f64((preroll_frames + frames) as f64 / self.sample_rate as f64)
+        };
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.live = None;
+
+            self.gaps = shared
+                .gaps
+                .drain(..)
+                .map(|(frames, gap)| AudioGap {
+                    position: position(frames),
+                    ..gap
+                })
+                .collect();
+            // Stopped while the device was still gone
+            if let Some(since) = shared.lost_since {
+                self.gaps.push(AudioGap {
+                    position: position(shared.live_frames),
+                    lost: since.elapsed(),
+                    resumed_on: None,
+                });
+            }
+        }
+        while let Ok(chunk) = rx.try_recv() {
+            sink(&chunk);
+        }
+
+        Ok(())
+    }
+
+    fn take_gaps(&mut self) -> Vec<AudioGap> {
+        std::mem::take(&mut self.gaps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared(capacity: usize) -> Shared {
+        Shared::new(capacity, 1)
+    }
+
+    #[test]
+    fn test_ring_keeps_latest_samples() {
+        let mut shared = shared(4);
+        shared.push(&[1.0, 2.0, 3.0]);
+        shared.push(&[4.0, 5.0, 6.0]);
+        assert_eq!(shared.ring, [3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_live_receiver_bypasses_ring() {
+        let mut shared = shared(4);
+        let (tx, rx) = mpsc::channel();
+        shared.live = Some(tx);
+
+        shared.push(&[1.0, 2.0]);
+        assert!(shared.ring.is_empty());
+        assert_eq!(rx.try_recv().unwrap(), vec![1.0, 2.0]);
+
+        // A dropped receiver falls back to buffering
+        drop(rx);
+        shared.push(&[3.0]);
+        assert!(shared.live.is_none());
+        assert_eq!(shared.ring, [3.0]);
+    }
+
+    #[test]
+    fn test_gaps_only_tracked_while_live() {
+        let gap = AudioGap {
+            position: Duration::from_secs(100),
+            lost: Duration::from_secs(2),
+            resumed_on: Some("mic".to_string()),
+        };
+
+        let mut shared = shared(4);
+        shared.update_connection(vec![gap.clone()], None);
+        assert!(shared.gaps.is_empty());
+
+        let (tx, _rx) = mpsc::channel();
+        shared.live = Some(tx);
+        shared.push(&[1.0, 2.0, 3.0]);
+        let since = Instant::now();
+        shared.update_connection(vec![gap.clone()], Some(since));
+
+        // Positioned by the live audio sent before the gap
+        assert_eq!(shared.gaps, vec![(3, gap)]);
+        assert_eq!(shared.lost_since, Some(since));
+    }
+}
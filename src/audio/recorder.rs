@@ -0,0 +1,523 @@
+//! Recording handle that drives an [`AudioSource`] on a worker thread
+//!
+//! Each recording owns its stop token, so back-to-back recordings cannot
+//! interfere with each other and no process-wide signal handler is needed.
+
+use anyhow::Result;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::dsp::run_stages;
+use super::meter::{AudioLevel, LevelMeter};
+use super::stats::InputStats;
+use super::trim::{TrimOptions, speech_range, trim_silence};
+use super::vad::{VadOptions, VoiceActivityDetector};
+use super::{
+    AudioGap, AudioSource, DspOptions, StreamResampler, WHISPER_SAMPLE_RATE, apply_dsp,
+    downmix_to_mono,
+};
+
+/// Cloneable flag used to ask a recording to stop
+#[derive(Debug, Clone, Default)]
+pub struct StopToken(Arc<AtomicBool>);
+
+impl StopToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the recording to stop
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if a stop was requested
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Options controlling when a recording ends
+#[derive(Debug, Clone, Default)]
+pub struct RecorderOptions {
+    /// Maximum length of captured audio (None = until stopped or source exhausted)
+    pub max_duration: Option<Duration>,
+    /// Keep recording this long after a stop request (buffers trailing words)
+    pub trailing: Duration,
+    /// End automatically after trailing silence, discarding recordings without speech
+    pub vad: Option<VadOptions>,
+    /// Cut leading/trailing silence from the final audio
+    pub trim: Option<TrimOptions>,
+    /// Record only this input channel (0-based) instead of averaging all channels
+    pub channel: Option<u16>,
+    /// Preprocessing applied to the final audio (after trimming)
+    pub dsp: DspOptions,
+    /// Keep the audio readable while recording (live partials and incremental
+    /// transcription); it is then captured straight into the shared buffer
+    pub live_audio: bool,
+}
+
+/// Captured audio plus level statistics of the raw input
+#[derive(Debug, Default)]
+pub struct Recording {
+    /// 16kHz mono f32 PCM (empty if cancelled or discarded)
+    pub samples: Vec<f32>,
+    pub stats: InputStats,
+    /// Audio lost while the input device was unavailable
+    pub gaps: Vec<AudioGap>,
+    /// The source ran out of audio (EOF) rather than being stopped
+    pub source_ended: bool,
+    /// VAD heard no speech, so the audio was discarded
+    pub no_speech: bool,
+}
+
+impl Recording {
+    /// Problems with the input worth telling the user about
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = self.stats.warnings();
+        warnings.extend(self.gaps.iter().map(ToString::to_string));
+        warnings
+    }
+}
+
+#[derive(Debug, Default)]
+struct Captured {
+    /// 16kHz audio so far (only kept with `live_audio`)
+    samples: Vec<f32>,
+    /// Samples captured so far
+    total: usize,
+}
+
+/// Audio of a running recording, readable from other threads (cheap to clone)
+#[derive(Debug, Clone, Default)]
+struct LiveAudio(Arc<Mutex<Captured>>);
+
+impl LiveAudio {
+    fn push(&self, samples: &[f32], keep: bool) {
+        let Ok(mut captured) = self.0.lock() else {
+            return;
+        };
+        captured.total += samples.len();
+        if keep {
+            captured.samples.extend_from_slice(samples);
+        }
+    }
+
+    /// Hand over the kept samples, leaving the buffer empty
+    fn take_samples(&self) -> Vec<f32> {
+        self.0
+            .lock()
+            .map(|mut captured| std::mem::take(&mut captured.samples))
+            .unwrap_or_default()
+    }
+
+    /// The last `len` samples
+    fn tail(&self, len: usize) -> Vec<f32> {
+        self.0
+            .lock()
+            .map(|captured| {
+                let start = captured.samples.len().saturating_sub(len);
+                captured.samples[start..].to_vec()
+            })
+            .unwrap_or_default()
+    }
+
+    fn total(&self) -> usize {
+        self.0
+            .lock()
+            .map(|captured| captured.total)
+            .unwrap_or_default()
+    }
+}
+
+/// Handle to an in-progress recording
+///
+/// Audio is returned as 16kHz mono f32 PCM (Whisper requirement).
+pub struct AudioRecorder {
+    stop: StopToken,
+    cancel: StopToken,
+    meter: LevelMeter,
+    live: LiveAudio,
+    handle: JoinHandle<Result<Recording>>,
+}
+
+impl AudioRecorder {
+    /// Start recording from `source` on a background thread
+    pub fn start(mut source: Box<dyn AudioSource>, options: RecorderOptions) -> Result<Self> {
+        let stop = StopToken::new();
+        let cancel = StopToken::new();
+
+        let meter = LevelMeter::default();
+        let live = LiveAudio::default();
+
+        let worker_stop = stop.clone();
+        let worker_cancel = cancel.clone();
+        let worker_meter = meter.clone();
+        let worker_live = live.clone();
+
+        info!(
+            "Starting recording from {} (max {})",
+            source.name(),
+            options
+                .max_duration
+                .map(|d| format!("{}s", d.as_secs()))
+                .unwrap_or_else(|| "unlimited".to_string())
+        );
+
+        let handle = thread::Builder::new()
+            .name("audio-recorder".to_string())
+            .spawn(move || {
+                record(
+                    source.as_mut(),
+                    &options,
+                    &worker_stop,
+                    &worker_cancel,
+                    &worker_meter,
+                    &worker_live,
+                )
+            })?;
+
+        Ok(Self {
+            stop,
+            cancel,
+            meter,
+            live,
+            handle,
+        })
+    }
+
+    /// Stop recording (after the trailing buffer) and return the captured audio
+    pub fn stop(self) -> Result<Recording> {
+        self.stop.stop();
+        self.wait()
+    }
+
+    /// Wait for the recording to end on its own (max duration or source exhausted)
+    pub fn wait(self) -> Result<Recording> {
+        self.handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Recording thread panicked"))?
+    }
+
+    /// Input level since the previous call (None if no audio arrived)
+    pub fn take_level(&self) -> Option<AudioLevel> {
+        self.meter.take()
+    }
+
+    /// Latest `window` of 16kHz mono audio, before trimming and preprocessing
+    ///
+    /// Empty unless `RecorderOptions::live_audio` is set.
+    pub fn recent_audio(&self, window: Duration) -> Vec<f32> {
+        let len = (window.as_secs_f64() * WHISPER_SAMPLE_RATE as f64) as usize;
+        self.live.tail(len)
+    }
+
+    /// All 16kHz mono audio captured so far (see [`AudioRecorder::recent_audio`])
+    ///
+    /// Copies the whole recording, so check [`AudioRecorder::captured_samples`]
+    /// first. Empty once the recording has ended (the buffer became its samples).
+    pub fn live_audio(&self) -> Vec<f32> {
+        self.live.tail(usize::MAX)
+    }
+
+    /// Length of the audio captured so far
+    pub fn captured(&self) -> Duration {
+        Duration::from_secs_f64(self.captured_samples() as f64 / WHISPER_SAMPLE_RATE as f64)
+    }
+
+    /// Number of 16kHz samples captured so far (no copy of the audio)
+    pub fn captured_samples(&self) -> usize {
+        self.live.total()
+    }
+
+    /// Whether the recording ended on its own (VAD silence, max duration or source exhausted)
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Stop immediately and discard the captured audio
+    pub fn cancel(self) {
+        self.cancel.stop();
+        self.stop.stop();
+        let _ = self.handle.join();
+        info!("Recording cancelled");
+    }
+}
+
+/// Record a finite source on the calling thread until it runs out of audio
+///
+/// Without VAD the whole stream becomes one recording. With `options.vad` set,
+/// each utterance ending in trailing silence is handed to `on_segment` as soon
+/// as it is complete, and stretches without speech are skipped.
+pub fn record_segments(
+    source: &mut dyn AudioSource,
+    options: &RecorderOptions,
+    mut on_segment: impl FnMut(Recording) -> Result<()>,
+) -> Result<()> {
+    let never = StopToken::new();
+    let meter = LevelMeter::default();
+    let live = LiveAudio::default();
+
+    loop {
+        let recording = record(source, options, &never, &never, &meter, &live)?;
+        let ended = recording.source_ended;
+
+        if !recording.samples.is_empty() {
+            on_segment(recording)?;
+        }
+        if ended {
+            return Ok(());
+        }
+    }
+}
+
+/// Trimmed and preprocessed start of a recording still in progress
+///
+/// Takes [`AudioRecorder::live_audio`] and returns audio that the final
+/// recording is guaranteed to begin with, so it can be transcribed ahead of
+/// time. Trimming and preprocessing only look backwards, except for the trim
+/// start before any speech and for normalization: None in those cases.
+pub fn stable_prefix(samples: Vec<f32>, options: &RecorderOptions) -> Option<Vec<f32>> {
+    if !options.dsp.is_causal() {
+        return None;
+    }
+
+    let mut samples = match &options.trim {
+        Some(trim) => {
+            let range = speech_range(&samples, WHISPER_SAMPLE_RATE, trim)?;
+            samples[range].to_vec()
+        },
+        None => samples,
+    };
+    run_stages(&mut samples, WHISPER_SAMPLE_RATE, &options.dsp);
+    Some(samples)
+}
+
+/// Worker thread body: run the source, downmixing and resampling as audio arrives
+fn record(
+    source: &mut dyn AudioSource,
+    options: &RecorderOptions,
+    stop: &StopToken,
+    cancel: &StopToken,
+    meter: &LevelMeter,
+    live: &LiveAudio,
+) -> Result<Recording> {
+    let sample_rate = source.sample_rate();
+    let channels = source.channels();
+    let frame_len = channels.max(1) as usize;
+
+    if let Some(channel) = options.channel {
+        if channel >= channels {
+            anyhow::bail!(
+                "Input channel {} not available ({} has {} channels)",
+                channel + 1,
+                source.name(),
+                channels
+            );
+        }
+        info!("Using input channel {} of {}", channel + 1, channels);
+    }
+
+    let max_frames = options
+        .max_duration
+        .map(|d| (d.as_secs_f64() * sample_rate as f64) as usize);
+
+    // Only 16kHz mono is kept; device audio is downmixed and resampled as it
+    // arrives. Live audio goes straight to the shared buffer, so it isn't kept twice.
+    let expected_samples = if options.live_audio {
+        0
+    } else {
+        options
+            .max_duration
+            .unwrap_or(Duration::from_secs(30))
+            .as_secs() as usize
+            * WHISPER_SAMPLE_RATE as usize
+    };
+    let mut samples: Vec<f32> = Vec::with_capacity(expected_samples);
+    let mut chunk: Vec<f32> = Vec::new();
+    let mut resampler = StreamResampler::new(sample_rate, WHISPER_SAMPLE_RATE)?;
+    let mut resample_error = None;
+
+    let frames = Cell::new(0usize);
+    let stop_requested_at = Cell::new(None::<Instant>);
+    let max_reached = Cell::new(false);
+    let silence_reached = Cell::new(false);
+    let no_speech_reached = Cell::new(false);
+
+    let no_speech_frames = options
+        .vad
+        .as_ref()
+        .and_then(|opts| opts.no_speech)
+        .map(|d| (d.as_secs_f64() * sample_rate as f64) as usize);
+    let mut vad = options
+        .vad
+        .as_ref()
+        .map(|opts| (VoiceActivityDetector::new(sample_rate, opts), opts.silence));
+
+    let mut stats = InputStats::default();
+
+    let mut sink = |data: &[f32]| {
+        frames.set(frames.get() + data.len() / frame_len);
+        stats.add(data);
+        let mono = downmix_to_mono(data, channels, options.channel);
+        meter.add(&mono);
+
+        if resample_error.is_none() {
+            chunk.clear();
+            if let Err(e) = resampler.process(&mono, &mut chunk) {
+                resample_error = Some(e);
+            }
+            live.push(&chunk, options.live_audio);
+            if !options.live_audio {
+                samples.extend_from_slice(&chunk);
+            }
+        }
+
+        if let Some((detector, silence)) = vad.as_mut() {
+            detector.process(&mono);
+
+            if detector.speech_detected()
+                && detector.trailing_silence() >= *silence
+                && !silence_reached.replace(true)
+            {
+                info!(
+                    "Detected {:.1}s of silence after speech - stopping",
+                    silence.as_secs_f32()
+                );
+            }
+
+            if !detector.speech_detected()
+                && no_speech_frames.is_some_and(|max| frames.get() >= max)
+                && !no_speech_reached.replace(true)
+            {
+                info!(
+                    "No speech in the first {:.1}s - stopping",
+                    frames.get() as f64 / sample_rate as f64
+                );
+            }
+        }
+    };
+
+    let should_stop = || {
+        if cancel.is_stopped() || silence_reached.get() || no_speech_reached.get() {
+            return true;
+        }
+
+        if let Some(max) = max_frames {
+            if frames.get() >= max {
+                if !max_reached.replace(true) {
+                    info!(
+                        "Max duration reached ({}s)",
+                        max as f64 / sample_rate as f64
+                    );
+                }
+                return true;
+            }
+        }
+
+        if stop.is_stopped() {
+            let requested = match stop_requested_at.get() {
+                Some(at) => at,
+                None => {
+                    info!("Stop signal received");
+                    if !options.trailing.is_zero() {
+                        info!(
+                            "Buffering trailing audio ({:.1}s)...",
+                            options.trailing.as_secs_f32()
+                        );
+                    }
+                    let now = Instant::now();
+                    stop_requested_at.set(Some(now));
+                    now
+                },
+            };
+            return requested.elapsed() >= options.trailing;
+        }
+
+        false
+    };
+
+    source.run(&mut sink, &should_stop)?;
+    let gaps = source.take_gaps();
+    if options.live_audio {
+        samples = live.take_samples();
+    }
+
+    if cancel.is_stopped() {
+        return Ok(Recording::default());
+    }
+
+    let source_ended = !silence_reached.get()
+        && !no_speech_reached.get()
+        && !max_reached.get()
+        && !stop.is_stopped();
+
+    info!("Input level: {}", stats);
+    for warning in stats.warnings() {
+        warn!("{}", warning);
+    }
+    for gap in &gaps {
+        warn!("{}", gap);
+    }
+
+    if let Some((detector, _)) = &vad {
+        if !detector.speech_detected() {
+            info!("No speech detected - discarding recording");
+            return Ok(Recording {
+                samples: Vec::new(),
+                stats,
+                gaps,
+                source_ended,
+                no_speech: true,
+            });
+        }
+    }
+
+    if let Some(e) = resample_error {
+        return Err(e);
+    }
+    resampler.finish(&mut samples)?;
+
+    info!(
+        "Captured {:.2}s at {}Hz ({} channels)",
+        frames.get() as f32 / sample_rate as f32,
+        sample_rate,
+        channels
+    );
+
+    if samples.is_empty() {
+        warn!("No audio captured - check microphone permissions");
+        return Ok(Recording {
+            samples,
+            stats,
+            gaps,
+            source_ended,
+            no_speech: false,
+        });
+    }
+
+    info!(
+        "Final audio: {} samples ({:.2}s at {}Hz)",
+        samples.len(),
+        samples.len() as f32 / WHISPER_SAMPLE_RATE as f32,
+        WHISPER_SAMPLE_RATE
+    );
+
+    let mut samples = match &options.trim {
+        Some(trim) => trim_silence(samples, WHISPER_SAMPLE_RATE, trim),
+        None => samples,
+    };
+    apply_dsp(&mut samples, WHISPER_SAMPLE_RATE, &options.dsp);
+
+    Ok(Recording {
+        samples,
+        stats,
+        gaps,
+        source_ended,
+        no_speech: false,
+    })
+}
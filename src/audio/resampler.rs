@@ -0,0 +1,118 @@
+//! Incremental resampling to the Whisper sample rate
+//!
+//! Audio is resampled chunk by chunk while recording, so only 16kHz mono is
+//! ever kept in memory and stopping does not have to process the whole
+//! recording at once.
+
+use anyhow::{Context, Result};
+use rubato::{FftFixedIn, Resampler};
+
+/// Input frames per rubato processing block
+const CHUNK_SIZE: usize = 1024;
+
+/// Streaming mono resampler (rubato FFT resampler with delay compensation)
+pub struct StreamResampler {
+    /// None when input and output rates match
+    resampler: Option<FftFixedIn<f32>>,
+    from_rate: u32,
+    to_rate: u32,
+    pending: Vec<f32>,
+    input_frames: usize,
+    output: OutputCounter,
+}
+
+/// Tracks emitted output and drops the resampler's initial delay
+struct OutputCounter {
+    /// Leading output frames still to drop
+    skip: usize,
+    frames: usize,
+}
+
+impl OutputCounter {
+    fn emit(&mut self, output: &[f32], out: &mut Vec<f32>) {
+        let skip = self.skip.min(output.len());
+        self.skip -= skip;
+        out.extend_from_slice(&output[skip..]);
+        self.frames += output.len() - skip;
+    }
+}
+
+impl StreamResampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Result<Self> {
+        let resampler = if from_rate == to_rate {
+            None
+        } else {
+            Some(
+                FftFixedIn::<f32>::new(from_rate as usize, to_rate as usize, CHUNK_SIZE, 2, 1)
+                    .context("Failed to create resampler")?,
+            )
+        };
+        let skip = resampler.as_ref().map_or(0, |r| r.output_delay());
+
+        Ok(Self {
+            resampler,
+            from_rate,
+            to_rate,
+            pending: Vec::with_capacity(CHUNK_SIZE * 2),
+            input_frames: 0,
+            output: OutputCounter { skip, frames: 0 },
+        })
+    }
+
+    /// Feed mono samples, appending any completed output to `out`
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) -> Result<()> {
+        self.input_frames += input.len();
+
+        let Some(resampler) = self.resampler.as_mut() else {
+            self.output.emit(input, out);
+            return Ok(());
+        };
+
+        self.pending.extend_from_slice(input);
+        let mut consumed = 0;
+        while self.pending.len() - consumed >= CHUNK_SIZE {
+            let block = &self.pending[consumed..consumed + CHUNK_SIZE];
+            let output = resampler
+                .process(&[block], None)
+                .context("Resampling failed")?;
+            consumed += CHUNK_SIZE;
+            self.output.emit(&output[0], out);
+        }
+        self.pending.drain(..consumed);
+
+        Ok(())
+    }
+
+    /// Flush buffered input, appending the remaining output to `out`
+    ///
+    /// The total output length matches the input duration at the target rate.
+    pub fn finish(mut self, out: &mut Vec<f32>) -> Result<()> {
+        let expected =
+            (self.input_frames as u64 * self.to_rate as u64 / self.from_rate as u64) as usize;
+
+        let Some(mut resampler) = self.resampler.take() else {
+            return Ok(());
+        };
+
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            let output = resampler
+                .process_partial(Some(&[pending.as_slice()]), None)
+                .context("Resampling failed")?;
+            self.output.emit(&output[0], out);
+        }
+
+        // Push silence through until the delayed tail of the signal is out
+        while self.output.frames < expected {
+            let output = resampler
+                .process_partial(None::<&[&[f32]]>, None)
+                .context("Resampling failed")?;
+            self.output.emit(&output[0], out);
+        }
+
+        let excess = self.output.frames - expected;
+        out.truncate(out.len().saturating_sub(excess));
+
+        Ok(())
+    }
+}
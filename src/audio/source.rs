@@ -0,0 +1,292 @@
+//! Audio sources that feed the recorder
+//!
+//! A source produces interleaved f32 frames at its native rate and channel count.
+//! The recorder takes care of downmixing and resampling to 16kHz mono.
+
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::{HeapCons, HeapRb};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::capture::{LiveCapture, Reconnect};
+use super::{AudioGap, samples_to_f32};
+
+/// How often a running source re-checks its stop condition
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Producer of interleaved f32 audio (microphone, file, stdin, synthetic tones, ...)
+pub trait AudioSource: Send {
+    /// Human-readable name used in logs
+    fn name(&self) -> String;
+
+    /// Native sample rate of the produced audio
+    fn sample_rate(&self) -> u32;
+
+    /// Number of interleaved channels in the produced audio
+    fn channels(&self) -> u16;
+
+    /// Push audio into `sink` until `should_stop` returns true or the source is exhausted
+    ///
+    /// Runs on the recorder's worker thread. Implementations must poll `should_stop`
+    /// regularly even when no audio is arriving.
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()>;
+
+    /// Stretches of audio lost during the last `run` (e.g. the device was unplugged)
+    fn take_gaps(&mut self) -> Vec<AudioGap> {
+        Vec::new()
+    }
+}
+
+/// Microphone input through CPAL
+pub struct CpalSource {
+    device: cpal::Device,
+    config: cpal::SupportedStreamConfig,
+    /// Device selector to reopen a lost device with (None = end the recording instead)
+    reconnect: Option<Option<String>>,
+    gaps: Vec<AudioGap>,
+}
+
+impl CpalSource {
+    /// Open a source on the device's default input config
+    pub fn new(device: cpal::Device) -> Result<Self> {
+        let config = default_stream_config(&device)?;
+        Ok(Self {
+            device,
+            config,
+            reconnect: None,
+            gaps: Vec::new(),
+        })
+    }
+
+    /// Reopen the device found by `selector` (or the default) if it is lost mid-recording
+    pub fn with_reconnect(mut self, selector: Option<String>) -> Self {
+        self.reconnect = Some(selector);
+        self
+    }
+}
+
+/// Stream config used for capture: the device's default input config
+pub(super) fn default_stream_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
+    // Use device's default config (macOS often requires stereo at native sample rate)
+    let default_config = device
+        .default_input_config()
+        .context("Failed to get default input config")?;
+
+    info!(
+        "Device default config: {} channels, {}Hz, {}",
+        default_config.channels(),
+        default_config.sample_rate().0,
+        default_config.sample_format()
+    );
+
+    Ok(default_config)
+}
+
+/// Build and start an input stream delivering interleaved f32 chunks to `on_data`
+///
+/// Samples in any CPAL format are converted to f32 before reaching `on_data`.
+/// Stream errors (e.g. the device disappearing) are passed to `on_error`.
+pub(super) fn build_input_stream(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    on_data: impl FnMut(&[f32]) + Send + 'static,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream> {
+    use cpal::SampleFormat;
+
+    let d = device;
+    let c = config;
+    let stream = match config.sample_format() {
+        SampleFormat::I8 => build_typed_stream::<i8>(d, c, on_data, on_error)?,
+        SampleFormat::I16 => build_typed_stream::<i16>(d, c, on_data, on_error)?,
+        SampleFormat::I24 => build_typed_stream::<cpal::I24>(d, c, on_data, on_error)?,
+        SampleFormat::I32 => build_typed_stream::<i32>(d, c, on_data, on_error)?,
+        SampleFormat::I64 => build_typed_stream::<i64>(d, c, on_data, on_error)?,
+        SampleFormat::U8 => build_typed_stream::<u8>(d, c, on_data, on_error)?,
+        SampleFormat::U16 => build_typed_stream::<u16>(d, c, on_data, on_error)?,
+        SampleFormat::U32 => build_typed_stream::<u32>(d, c, on_data, on_error)?,
+        SampleFormat::U64 => build_typed_stream::<u64>(d, c, on_data, on_error)?,
+        SampleFormat::F32 => build_typed_stream::<f32>(d, c, on_data, on_error)?,
+        SampleFormat::F64 => build_typed_stream::<f64>(d, c, on_data, on_error)?,
+        other => anyhow::bail!("Unsupported input sample format: {}", other),
+    };
+
+    stream.play()?;
+    Ok(stream)
+}
+
+/// Input stream for one concrete sample type, converting each callback buffer to f32
+fn build_typed_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    mut on_data: impl FnMut(&[f32]) + Send + 'static,
+    on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    let mut buffer = Vec::new();
+    let stream = device.build_input_stream(
+        &config.config(),
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            samples_to_f32(data, &mut buffer);
+            on_data(&buffer);
+        },
+        on_error,
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+impl AudioSource for CpalSource {
+    fn name(&self) -> String {
+        self.device.name().unwrap_or_else(|_| "Unknown".to_string())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.config.sample_rate().0
+    }
+
+    fn channels(&self) -> u16 {
+        self.config.channels()
+    }
+
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        // The CPAL stream is not Send, so it lives on this (worker) thread only
+        let mut capture = match &self.reconnect {
+            Some(selector) => LiveCapture::open(
+                &self.device,
+                &self.config,
+                selector.clone(),
+                Reconnect::Limited,
+            )?,
+            None => LiveCapture::open(&self.device, &self.config, None, Reconnect::Never)?,
+        };
+
+        let mut started = false;
+        while !should_stop() {
+            // None: the device is gone and could not be reopened
+            let Some(chunk) = capture.read() else {
+                break;
+            };
+            if chunk.is_empty() {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            if !started {
+                started = true;
+                info!("Recording started - speak now!");
+            }
+            sink(chunk);
+        }
+
+        // Stop stream, then flush anything the callback queued before it stopped
+        let rest = capture.stop();
+        if !rest.is_empty() {
+            sink(rest);
+        }
+        self.gaps = capture.take_gaps();
+
+        Ok(())
+    }
+
+    fn take_gaps(&mut self) -> Vec<AudioGap> {
+        std::mem::take(&mut self.gaps)
+    }
+}
+
+/// Audio the callback queue holds before whole buffers are dropped
+const QUEUE_CAPACITY: Duration = Duration::from_secs(2);
+
+/// Consumer side of the queue between the realtime callback and a worker thread
+pub(super) struct QueueReader {
+    consumer: HeapCons<f32>,
+    buffer: Vec<f32>,
+    dropped: Arc<AtomicUsize>,
+    errors: mpsc::Receiver<cpal::StreamError>,
+    frame_len: usize,
+    sample_rate: u32,
+}
+
+impl QueueReader {
+    /// Take everything queued so far (always whole frames)
+    pub(super) fn read(&mut self) -> &[f32] {
+        let n = self.consumer.pop_slice(&mut self.buffer);
+        &self.buffer[..n]
+    }
+
+    /// Whether nothing is queued
+    pub(super) fn is_empty(&self) -> bool {
+        self.consumer.is_empty()
+    }
+
+    /// Oldest stream error reported by CPAL since the last call
+    pub(super) fn take_error(&mut self) -> Option<cpal::StreamError> {
+        self.errors.try_recv().ok()
+    }
+
+    /// Warn if the worker fell behind and the callback had to drop audio
+    pub(super) fn report_overflow(&self) {
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        if dropped > 0 {
+            warn!(
+                "Audio queue overflowed: dropped {:.2}s of audio",
+                (dropped / self.frame_len) as f32 / self.sample_rate as f32
+            );
+        }
+    }
+}
+
+/// Start a stream whose callback pushes into a lock-free SPSC queue
+///
+/// The callback never locks or allocates; if the queue is full the whole
+/// buffer is dropped so frames stay aligned.
+pub(super) fn open_queued_stream(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+) -> Result<(cpal::Stream, QueueReader)> {
+    let frame_len = config.channels().max(1) as usize;
+    let sample_rate = config.sample_rate().0;
+    let capacity = (QUEUE_CAPACITY.as_secs_f64() * sample_rate as f64) as usize * frame_len;
+
+    let (mut producer, consumer) = HeapRb::<f32>::new(capacity).split();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let (error_tx, errors) = mpsc::channel();
+
+    let callback_dropped = Arc::clone(&dropped);
+    let stream = build_input_stream(
+        device,
+        config,
+        move |data| {
+            if producer.vacant_len() >= data.len() {
+                producer.push_slice(data);
+            } else {
+                callback_dropped.fetch_add(data.len(), Ordering::Relaxed);
+            }
+        },
+        move |err| {
+            let _ = error_tx.send(err);
+        },
+    )?;
+
+    Ok((
+        stream,
+        QueueReader {
+            consumer,
+            buffer: vec![0.0; capacity],
+            dropped,
+            errors,
+            frame_len,
+            sample_rate,
+        },
+    ))
+}
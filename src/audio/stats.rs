@@ -0,0 +1,167 @@
+//! Input level statistics and microphone warnings
+//!
+//! Collected on the raw device audio while recording, so a muted, quiet or
+//! clipping microphone can be reported before (or instead of) transcribing.
+
+use super::vad::rms_db;
+
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Peak below this level means the input is muted or disconnected
+const SILENT_PEAK_DB: f32 = -60.0;
+
+/// Average level below this is too quiet for reliable recognition
+const QUIET_RMS_DB: f32 = -50.0;
+
+/// Clipping above this fraction of samples is worth warning about
+const CLIPPING_WARN_FRACTION: f32 = 0.01;
+
+/// Peak, RMS and clipping statistics over all captured samples
+#[derive(Debug, Clone, Default)]
+pub struct InputStats {
+    peak: f32,
+    sum_squares: f64,
+    clipped: usize,
+    samples: usize,
+}
+
+impl InputStats {
+    /// Accumulate a block of (interleaved) samples
+    pub fn add(&mut self, data: &[f32]) {
+        for &sample in data {
+            let magnitude = sample.abs();
+            self.peak = self.peak.max(magnitude);
+            self.sum_squares += (sample as f64) * (sample as f64);
+            if magnitude >= CLIP_LEVEL {
+                self.clipped += 1;
+            }
+        }
+        self.samples += data.len();
+    }
+
+    /// Loudest sample in dBFS
+    pub fn peak_db(&self) -> f32 {
+        rms_db(&[self.peak])
+    }
+
+    /// Average level in dBFS
+    pub fn rms_db(&self) -> f32 {
+        if self.samples == 0 {
+            return rms_db(&[]);
+        }
+        let rms = (self.sum_squares / self.samples as f64).sqrt() as f32;
+        rms_db(&[rms])
+    }
+
+    /// Fraction of samples at full scale
+    pub fn clipped_fraction(&self) -> f32 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.clipped as f32 / self.samples as f32
+    }
+
+    /// Input never rose above the noise of a muted/disconnected device
+    ///
+    /// Also true if no audio arrived at all (see [`InputStats::warnings`]).
+    pub fn is_silent(&self) -> bool {
+        self.peak_db() < SILENT_PEAK_DB
+    }
+
+    /// Actionable warnings about the input level (empty if the input looks healthy)
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.samples == 0 {
+            warnings.push(
+                "No audio captured - check that the input device is connected and microphone access is allowed"
+                    .to_string(),
+            );
+            return warnings;
+        }
+
+        if self.is_silent() {
+            warnings.push(format!(
+                "Input is muted or silent (peak {:.0} dBFS) - check the mic mute switch and input volume",
+                self.peak_db()
+            ));
+        } else if self.rms_db() < QUIET_RMS_DB {
+            warnings.push(format!(
+                "Input is very quiet (average {:.0} dBFS) - raise the microphone gain",
+                self.rms_db()
+            ));
+        }
+
+        if self.clipped_fraction() > CLIPPING_WARN_FRACTION {
+            warnings.push(format!(
+                "Input is clipping {:.0}% of the time - lower the microphone gain",
+                self.clipped_fraction() * 100.0
+            ));
+        }
+
+        warnings
+    }
+}
+
+impl std::fmt::Display for InputStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {:.1} dBFS, average {:.1} dBFS, clipped {:.1}%",
+            self.peak_db(),
+            self.rms_db(),
+            self.clipped_fraction() * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(data: &[f32]) -> InputStats {
+        let mut stats = InputStats::default();
+        stats.add(data);
+        stats
+    }
+
+    #[test]
+    fn test_silent_input() {
+        let stats = stats(&[0.0; 1000]);
+        assert!(stats.is_silent());
+        assert!(stats.warnings()[0].contains("muted or silent"));
+    }
+
+    #[test]
+    fn test_no_audio_captured() {
+        let stats = InputStats::default();
+        assert!(stats.is_silent());
+        let warnings = stats.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("No audio captured"));
+    }
+
+    #[test]
+    fn test_healthy_input_has_no_warnings() {
+        let data: Vec<f32> = (0..1600).map(|i| 0.3 * (i as f32 * 0.1).sin()).collect();
+        assert!(stats(&data).warnings().is_empty());
+    }
+
+    #[test]
+    fn test_quiet_input() {
+        let data: Vec<f32> = (0..1600).map(|i| 0.002 * (i as f32 * 0.1).sin()).collect();
+        let warnings = stats(&data).warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("very quiet"));
+    }
+
+    #[test]
+    fn test_clipping_percentage() {
+        let mut data = vec![0.3; 88];
+        data.extend([1.0; 12]);
+        let stats = stats(&data);
+        assert!((stats.clipped_fraction() - 0.12).abs() < 1e-6);
+        assert!(stats.warnings()[0].contains("clipping 12% of the time"));
+    }
+}
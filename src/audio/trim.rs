@@ -0,0 +1,109 @@
+//! Leading/trailing silence trimming
+//!
+//! Dead air before and after speech wastes decoder time and invites Whisper's
+//! "Thank you." hallucinations, so recordings are cut to the detected speech
+//! plus a little padding.
+
+use std::ops::Range;
+use std::time::Duration;
+use tracing::info;
+
+use super::vad::{VadOptions, speech_bounds};
+
+/// Silence trimming settings
+#[derive(Debug, Clone)]
+pub struct TrimOptions {
+    /// Speech detection settings used to find the speech boundaries
+    pub vad: VadOptions,
+    /// Audio kept on each side of the detected speech
+    pub padding: Duration,
+}
+
+impl Default for TrimOptions {
+    fn default() -> Self {
+        Self {
+            vad: VadOptions::default(),
+            padding: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Trim leading and trailing silence from mono audio
+///
+/// Audio without detectable speech is returned unchanged.
+pub fn trim_silence(samples: Vec<f32>, sample_rate: u32, options: &TrimOptions) -> Vec<f32> {
+    let Some(range) = speech_range(&samples, sample_rate, options) else {
+        info!("No speech boundaries found - keeping full recording");
+        return samples;
+    };
+
+    let secs = |n: usize| n as f32 / sample_rate as f32;
+    info!(
+        "Trimmed silence: {:.2}s leading, {:.2}s trailing ({:.2}s -> {:.2}s)",
+        secs(range.start),
+        secs(samples.len() - range.end),
+        secs(samples.len()),
+        secs(range.len())
+    );
+
+    if range.start == 0 && range.end == samples.len() {
+        return samples;
+    }
+    samples[range].to_vec()
+}
+
+/// Part of the audio `trim_silence` keeps, or None if it contains no speech
+pub(super) fn speech_range(
+    samples: &[f32],
+    sample_rate: u32,
+    options: &TrimOptions,
+) -> Option<Range<usize>> {
+    let bounds = speech_bounds(samples, sample_rate, &options.vad)?;
+
+    let padding = (options.padding.as_secs_f64() * sample_rate as f64) as usize;
+    Some(bounds.start.saturating_sub(padding)..(bounds.end + padding).min(samples.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn clip(lead_secs: f32, speech_secs: f32, tail_secs: f32) -> Vec<f32> {
+        let mut samples = vec![0.0; (lead_secs * 16000.0) as usize];
+        samples.extend(
+            (0..(speech_secs * 16000.0) as usize)
+                .map(|i| 0.3 * (2.0 * PI * 300.0 * i as f32 / 16000.0).sin()),
+        );
+        samples.extend(vec![0.0; (tail_secs * 16000.0) as usize]);
+        samples
+    }
+
+    #[test]
+    fn test_trim_keeps_padding() {
+        let trimmed = trim_silence(clip(2.0, 1.0, 1.5), 16000, &TrimOptions::default());
+
+        // 1s of speech plus 300ms padding on each side (30ms frame tolerance)
+        let expected = 16000 + 2 * 4800;
+        assert!(
+            trimmed.len().abs_diff(expected) < 960,
+            "Expected ~{} samples, got {}",
+            expected,
+            trimmed.len()
+        );
+    }
+
+    #[test]
+    fn test_trim_padding_clamped_to_clip() {
+        let samples = clip(0.1, 1.0, 0.1);
+        let len = samples.len();
+        let trimmed = trim_silence(samples, 16000, &TrimOptions::default());
+        assert_eq!(trimmed.len(), len);
+    }
+
+    #[test]
+    fn test_trim_without_speech_is_noop() {
+        let trimmed = trim_silence(vec![0.0; 16000], 16000, &TrimOptions::default());
+        assert_eq!(trimmed.len(), 16000);
+    }
+}
@@ -0,0 +1,296 @@
+//! Energy-based voice activity detection
+//!
+//! Classifies 30ms frames as speech when their RMS level is above both an
+//! absolute threshold and an adaptive noise floor. Cheap enough to run inside
+//! the recording loop.
+
+use std::ops::Range;
+use std::time::Duration;
+
+/// Length of one analysis frame
+const FRAME_MS: u32 = 30;
+
+/// Speech must be this far above the tracked noise floor
+const NOISE_FLOOR_MARGIN_DB: f32 = 10.0;
+
+/// Maximum upward drift of the noise floor per frame (falls immediately)
+const NOISE_FLOOR_RISE_DB: f32 = 0.05;
+
+/// Level reported for digital silence
+const SILENCE_DB: f32 = -100.0;
+
+/// Voice activity detection settings
+#[derive(Debug, Clone)]
+pub struct VadOptions {
+    /// Minimum frame level (dBFS) that can count as speech
+    pub threshold_db: f32,
+    /// Trailing silence after speech that ends an auto-stop recording
+    pub silence: Duration,
+    /// Time without any speech that ends (and discards) an auto-stop recording
+    pub no_speech: Option<Duration>,
+    /// Consecutive speech needed before a recording counts as containing speech
+    pub min_speech: Duration,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        Self {
+            threshold_db: -40.0,
+            silence: Duration::from_millis(1200),
+            no_speech: Some(Duration::from_secs(10)),
+            min_speech: Duration::from_millis(150),
+        }
+    }
+}
+
+/// Streaming voice activity detector over mono audio
+pub struct VoiceActivityDetector {
+    frame_len: usize,
+    threshold_db: f32,
+    min_speech_frames: usize,
+    pending: Vec<f32>,
+    /// Tracked background level, seeded from the first frame (capped at the threshold)
+    noise_floor_db: Option<f32>,
+    speech_run: usize,
+    silence_run: usize,
+    speech_detected: bool,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(sample_rate: u32, options: &VadOptions) -> Self {
+        let frame_len = (sample_rate * FRAME_MS / 1000).max(1) as usize;
+        let min_speech_frames =
+            (options.min_speech.as_millis() as usize).div_ceil(FRAME_MS as usize);
+
+        Self {
+            frame_len,
+            threshold_db: options.threshold_db,
+            min_speech_frames: min_speech_frames.max(1),
+            pending: Vec::with_capacity(frame_len),
+            noise_floor_db: None,
+            speech_run: 0,
+            silence_run: 0,
+            speech_detected: false,
+        }
+    }
+
+    /// Feed mono samples (any chunk size)
+    pub fn process(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.pending.push(sample);
+            if self.pending.len() == self.frame_len {
+                let level = rms_db(&self.pending);
+                self.pending.clear();
+                self.push_frame(level);
+            }
+        }
+    }
+
+    /// Classify a single frame (by level) and update the run counters
+    fn push_frame(&mut self, level_db: f32) -> bool {
+        // Capping the seed keeps speech at the very start of a recording detectable
+        let floor = *self
+            .noise_floor_db
+            .get_or_insert(level_db.min(self.threshold_db));
+        let is_speech = self.is_speech_level(level_db);
+
+        self.noise_floor_db = Some(if level_db < floor {
+            level_db
+        } else {
+            floor + (level_db - floor).min(NOISE_FLOOR_RISE_DB)
+        });
+
+        if is_speech {
+            self.speech_run += 1;
+            if self.speech_run >= self.min_speech_frames {
+                self.speech_detected = true;
+                self.silence_run = 0;
+            }
+        } else {
+            self.speech_run = 0;
+            self.silence_run += 1;
+        }
+
+        is_speech
+    }
+
+    /// Whether a frame level counts as speech at the current noise floor
+    pub fn is_speech_level(&self, level_db: f32) -> bool {
+        let floor = self.noise_floor_db.unwrap_or(SILENCE_DB);
+        level_db > self.threshold_db && level_db > floor + NOISE_FLOOR_MARGIN_DB
+    }
+
+    /// Whether any speech has been seen so far
+    pub fn speech_detected(&self) -> bool {
+        self.speech_detected
+    }
+
+    /// Silence since the last speech frame
+    pub fn trailing_silence(&self) -> Duration {
+        Duration::from_millis((self.silence_run as u32 * FRAME_MS) as u64)
+    }
+}
+
+/// Sample range spanning the first to the last run of speech in a mono clip
+///
+/// Runs shorter than `min_speech` (clicks, key presses) are ignored.
+/// Returns None if the clip contains no speech.
+pub fn speech_bounds(
+    samples: &[f32],
+    sample_rate: u32,
+    options: &VadOptions,
+) -> Option<Range<usize>> {
+    let mut detector = VoiceActivityDetector::new(sample_rate, options);
+    let frame_len = detector.frame_len;
+    let min_frames = detector.min_speech_frames;
+
+    let mut bounds: Option<Range<usize>> = None;
+    let mut run_start = None;
+
+    let close_run = |start: usize, end: usize, bounds: &mut Option<Range<usize>>| {
+        if end - start >= min_frames {
+            let range = start * frame_len..end * frame_len;
+            *bounds = Some(match bounds.take() {
+                Some(b) => b.start..range.end,
+                None => range,
+            });
+        }
+    };
+
+    let frames = samples.chunks_exact(frame_len);
+    let frame_count = frames.len();
+    for (index, frame) in frames.enumerate() {
+        let is_speech = detector.push_frame(rms_db(frame));
+        match (is_speech, run_start) {
+            (true, None) => run_start = Some(index),
+            (false, Some(start)) => {
+                close_run(start, index, &mut bounds);
+                run_start = None;
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(start) = run_start {
+        close_run(start, frame_count, &mut bounds);
+    }
+
+    bounds
+}
+
+/// RMS level of a block of samples in dBFS
+pub fn rms_db(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return SILENCE_DB;
+    }
+
+    let mean_square = samples.iter().map(|&s| s * s).sum::<f32>() / samples.len() as f32;
+    if mean_square <= 0.0 {
+        return SILENCE_DB;
+    }
+
+    (10.0 * mean_square.log10()).max(SILENCE_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn tone(amplitude: f32, secs: f32) -> Vec<f32> {
+        (0..(secs * 16000.0) as usize)
+            .map(|i| amplitude * (2.0 * PI * 300.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_rms_db() {
+        assert_eq!(rms_db(&[]), SILENCE_DB);
+        assert_eq!(rms_db(&[0.0; 100]), SILENCE_DB);
+        // Full-scale square wave is 0 dBFS
+        assert!(rms_db(&[1.0, -1.0, 1.0, -1.0]).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_silence_is_not_speech() {
+        let mut vad = VoiceActivityDetector::new(16000, &VadOptions::default());
+        vad.process(&vec![0.0; 32000]);
+        assert!(!vad.speech_detected());
+        assert!(vad.trailing_silence() >= Duration::from_millis(1900));
+    }
+
+    #[test]
+    fn test_speech_then_silence() {
+        let mut vad = VoiceActivityDetector::new(16000, &VadOptions::default());
+        vad.process(&vec![0.0; 4800]);
+        vad.process(&tone(0.3, 1.0));
+        assert!(vad.speech_detected());
+        assert_eq!(vad.trailing_silence(), Duration::ZERO);
+
+        vad.process(&vec![0.0; 16000]);
+        let silence = vad.trailing_silence();
+        assert!(
+            silence >= Duration::from_millis(950) && silence <= Duration::from_millis(1000),
+            "Expected ~1s trailing silence, got {:?}",
+            silence
+        );
+    }
+
+    #[test]
+    fn test_speech_from_first_frame() {
+        let mut vad = VoiceActivityDetector::new(16000, &VadOptions::default());
+        vad.process(&tone(0.3, 0.5));
+        assert!(vad.speech_detected());
+    }
+
+    #[test]
+    fn test_speech_bounds() {
+        let mut clip = vec![0.0; 16000];
+        clip.extend(tone(0.3, 1.0));
+        clip.extend(vec![0.0; 8000]);
+
+        let bounds = speech_bounds(&clip, 16000, &VadOptions::default()).unwrap();
+        // Frame-aligned to 30ms (480 samples)
+        assert!(
+            bounds.start.abs_diff(16000) < 480,
+            "start: {}",
+            bounds.start
+        );
+        assert!(bounds.end.abs_diff(32000) < 480, "end: {}", bounds.end);
+    }
+
+    #[test]
+    fn test_speech_bounds_ignores_clicks() {
+        let mut clip = vec![0.0; 8000];
+        clip.extend(tone(0.3, 1.0));
+        clip.extend(vec![0.0; 8000]);
+        // Hotkey click at the end of the recording
+        clip.extend(tone(0.8, 0.03));
+        clip.extend(vec![0.0; 1600]);
+
+        let bounds = speech_bounds(&clip, 16000, &VadOptions::default()).unwrap();
+        assert!(bounds.end.abs_diff(24000) < 480, "end: {}", bounds.end);
+
+        assert!(speech_bounds(&vec![0.0; 16000], 16000, &VadOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_short_click_is_not_speech() {
+        let mut vad = VoiceActivityDetector::new(16000, &VadOptions::default());
+        vad.process(&tone(0.8, 0.03));
+        vad.process(&vec![0.0; 8000]);
+        assert!(!vad.speech_detected());
+    }
+
+    #[test]
+    fn test_steady_noise_is_not_speech() {
+        // Constant hum above the absolute threshold sets the noise floor instead
+        let mut vad = VoiceActivityDetector::new(16000, &VadOptions::default());
+        vad.process(&tone(0.02, 5.0));
+        assert!(!vad.speech_detected());
+
+        // Speech well above the hum is still detected
+        vad.process(&tone(0.5, 0.5));
+        assert!(vad.speech_detected());
+    }
+}
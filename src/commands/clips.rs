@@ -0,0 +1,45 @@
+//! Saved audio clip maintenance
+//!
+//! Applies the `[audio]` retention settings to the clips directory on demand,
+//! e.g. after tightening the limits or when the daemon has not run in a while.
+
+use anyhow::Result;
+
+use crate::{audio, config};
+
+/// Delete saved clips outside the configured retention limits
+pub fn prune(dry_run: bool) -> Result<()> {
+    let cfg = config::load()?;
+    let dir = &cfg.audio.audio_clips_path;
+    let retention = cfg.audio.clip_retention();
+
+    if !retention.is_enabled() {
+        println!("No clip retention limits configured.");
+        println!(
+            "Set clips_max_age_days, clips_max_total_mb or clips_max_count under [audio] in {}",
+            config::config_path()?.display()
+        );
+        return Ok(());
+    }
+
+    let report = audio::prune_clips(dir, &retention, dry_run)?;
+
+    println!(
+        "{} {} clips ({}) in {}",
+        if dry_run { "Would remove" } else { "Removed" },
+        report.removed,
+        format_size(report.removed_bytes),
+        dir.display()
+    );
+    println!(
+        "Kept {} clips ({})",
+        report.kept,
+        format_size(report.kept_bytes)
+    );
+
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
@@ -0,0 +1,58 @@
+//! Audio input device listing command
+//!
+//! Prints every input device with its supported stream configs so users can
+//! pick a value for the `[audio] device` config key.
+
+use anyhow::Result;
+
+use crate::{audio, config};
+
+/// List input devices, marking the default and the configured device
+pub fn run() -> Result<()> {
+    let devices = audio::list_input_devices()?;
+    let configured = config::load().ok().and_then(|cfg| cfg.audio.device);
+
+    let names: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+    let selected = configured
+        .as_deref()
+        .and_then(|sel| audio::match_device(&names, sel.trim()));
+
+    if devices.is_empty() {
+        println!("No input devices found. Check microphone permissions.");
+        return Ok(());
+    }
+
+    println!("Input devices:\n");
+
+    for device in &devices {
+        let mut tags = Vec::new();
+        if device.is_default {
+            tags.push("default");
+        }
+        if selected == Some(device.index) {
+            tags.push("configured");
+        }
+
+        let tags = if tags.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", tags.join(", "))
+        };
+        println!("  [{}] {}{}", device.index, device.name, tags);
+
+        for config in &device.configs {
+            println!("      {}", config);
+        }
+    }
+
+    println!();
+    match configured {
+        Some(sel) if selected.is_none() => {
+            println!("Configured: [audio] device = \"{}\" (NOT FOUND)", sel)
+        },
+        Some(sel) => println!("Configured: [audio] device = \"{}\"", sel),
+        None => println!("Configured: system default (set [audio] device to an index or name)"),
+    }
+
+    Ok(())
+}
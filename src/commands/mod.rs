@@ -2,6 +2,13 @@
 //!
 //! This module contains all subcommand implementations organized by functionality.
 
+pub mod clips;
+pub mod devices;
 pub mod enigo_test;
+pub mod status;
+pub mod transcribe;
 
+pub use devices::run as devices;
 pub use enigo_test::run as enigo_test;
+pub use status::run as status;
+pub use transcribe::run as transcribe;
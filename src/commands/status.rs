@@ -0,0 +1,115 @@
+//! Daemon status command
+//!
+//! Reports whether the daemon is running, recording, and holding the
+//! microphone open for pre-roll. With `--levels` it shows a live input meter,
+//! with `--partials` the live transcript of each recording.
+
+use anyhow::Result;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::daemon::{self, DaemonRequest, DaemonResponse};
+
+/// Width of the live level meter in characters
+const METER_WIDTH: usize = 30;
+
+/// Print the daemon's recording and microphone state
+pub fn run(levels: bool, partials: bool) -> Result<()> {
+    if !daemon::is_daemon_running() {
+        println!("Daemon: not running");
+        return Ok(());
+    }
+
+    if levels {
+        return follow_levels();
+    }
+    if partials {
+        return follow_partials();
+    }
+
+    match daemon::send_request(&DaemonRequest::Status)? {
+        DaemonResponse::Status {
+            recording,
+            mic_open,
+            preroll_ms,
+        } => {
+            println!("Daemon: running");
+            println!("Recording: {}", if recording { "yes" } else { "no" });
+
+            let mic = match (mic_open, preroll_ms) {
+                (false, _) => "closed".to_string(),
+                (true, 0) => "open (recording)".to_string(),
+                (true, ms) => format!("held open (pre-roll {}ms)", ms),
+            };
+            println!("Microphone: {}", mic);
+        },
+        DaemonResponse::Error { message } => anyhow::bail!("Status failed: {}", message),
+        other => anyhow::bail!("Unexpected response: {:?}", other),
+    }
+
+    Ok(())
+}
+
+/// Draw a live input meter until interrupted
+fn follow_levels() -> Result<()> {
+    println!("Input level (Ctrl+C to quit):");
+
+    daemon::send_request_streaming(
+        &DaemonRequest::SubscribeLevels,
+        Duration::from_secs(5),
+        |response| {
+            if let DaemonResponse::Level {
+                recording,
+                rms_db,
+                percent,
+                ..
+            } = response
+            {
+                let filled = percent as usize * METER_WIDTH / 100;
+                let line = if recording {
+                    format!(
+                        "[{}{}] {:6.1} dBFS",
+                        "#".repeat(filled),
+                        "-".repeat(METER_WIDTH - filled),
+                        rms_db
+                    )
+                } else {
+                    format!("[{}] not recording", " ".repeat(METER_WIDTH))
+                };
+                print!("\r{}", line);
+                std::io::stdout().flush()?;
+            }
+            Ok(true)
+        },
+    )
+}
+
+/// Print each recording's live transcript, finishing the line when it ends
+fn follow_partials() -> Result<()> {
+    println!("Live transcript (Ctrl+C to quit):");
+
+    let mut shown = None::<String>;
+    daemon::send_request_streaming(
+        &DaemonRequest::SubscribePartials,
+        Duration::from_secs(5),
+        |response| {
+            if let DaemonResponse::Partial { recording, text } = response {
+                match (recording, &shown) {
+                    (true, Some(previous)) if *previous == text => {},
+                    (true, _) => {
+                        // Rewrite the current line with the newer hypothesis
+                        print!("\r\x1b[2K{}", text);
+                        std::io::stdout().flush()?;
+                        shown = Some(text);
+                    },
+                    (false, Some(_)) => {
+                        println!();
+                        shown = None;
+                    },
+                    (false, None) => {},
+                }
+            }
+            Ok(true)
+        },
+    )
+}
@@ -0,0 +1,203 @@
+//! Offline audio file and stdin transcription command
+//!
+//! Decodes a file (or raw PCM piped to stdin) and prints its transcription to
+//! stdout, as plain text or (files only) as SRT/WebVTT captions or JSON. Uses
+//! the daemon's resident model when a daemon is running, otherwise loads the
+//! model locally.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::Duration;
+use tracing::info;
+
+use crate::audio::{PcmSource, PcmSpec, RecorderOptions};
+use crate::daemon::{self, DaemonRequest, DaemonResponse};
+use crate::transcribe::candle_engine::CandleEngine;
+use crate::transcribe::segments::format_transcript;
+use crate::transcribe::{Task, Transcriber, TranscriptFormat};
+use crate::{audio, config};
+
+/// How long to wait for the daemon to transcribe a file
+const DAEMON_TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Transcribe `file` and print it in `format` (or write it to `output`)
+///
+/// `words` adds word timings and confidences (JSON, and WebVTT karaoke cues).
+/// `task` overrides `[model] task`.
+pub fn run(
+    file: &Path,
+    format: TranscriptFormat,
+    words: bool,
+    output: Option<&Path>,
+    task: Option<Task>,
+    model_override: Option<String>,
+    local: bool,
+) -> Result<()> {
+    if words && !format.is_timed() {
+        anyhow::bail!("--words needs a timed output format (srt, vtt or json)");
+    }
+
+    let path = file
+        .canonicalize()
+        .with_context(|| format!("Audio file not found: {}", file.display()))?;
+
+    // A model override only makes sense for a locally loaded model
+    let transcript = if !local && model_override.is_none() && daemon::is_daemon_running() {
+        transcribe_via_daemon(&path, format, words, task)?
+    } else {
+        transcribe_locally(&path, format, words, task, model_override)?
+    };
+
+    match output {
+        Some(output) => std::fs::write(output, transcript)
+            .with_context(|| format!("Failed to write {}", output.display()))?,
+        None => print!("{}", transcript),
+    }
+    Ok(())
+}
+
+fn transcribe_via_daemon(
+    path: &Path,
+    format: TranscriptFormat,
+    words: bool,
+    task: Option<Task>,
+) -> Result<String> {
+    info!("Transcribing {} via daemon", path.display());
+
+    let request = DaemonRequest::TranscribeFile {
+        path: path.to_path_buf(),
+        timestamps: format.is_timed(),
+        words,
+        task,
+    };
+    match daemon::send_request_with_timeout(&request, DAEMON_TRANSCRIBE_TIMEOUT)? {
+        DaemonResponse::Success { text, .. } if !format.is_timed() => Ok(text + "\n"),
+        DaemonResponse::Transcript(transcription) if format.is_timed() => {
+            Ok(format_transcript(&transcription, format))
+        },
+        DaemonResponse::Error { message } => anyhow::bail!("Daemon error: {}", message),
+        other => anyhow::bail!("Unexpected response from daemon: {:?}", other),
+    }
+}
+
+/// Transcribe raw PCM from stdin until EOF, printing text per segment if `chunked`
+pub fn run_stdin(
+    spec: PcmSpec,
+    chunked: bool,
+    task: Option<Task>,
+    model_override: Option<String>,
+    local: bool,
+) -> Result<()> {
+    if !local && model_override.is_none() && daemon::is_daemon_running() {
+        stdin_via_daemon(spec, chunked, task)
+    } else {
+        stdin_locally(spec, chunked, task, model_override)
+    }
+}
+
+fn stdin_via_daemon(spec: PcmSpec, chunked: bool, task: Option<Task>) -> Result<()> {
+    info!("Streaming stdin to daemon");
+
+    let request = DaemonRequest::TranscribeStream {
+        spec,
+        chunked,
+        task,
+    };
+    daemon::send_request_with_body(&request, std::io::stdin(), |response| match response {
+        DaemonResponse::Success { text, .. } => {
+            println!("{}", text);
+            Ok(true)
+        },
+        DaemonResponse::Ok { .. } => Ok(false),
+        DaemonResponse::Error { message } => anyhow::bail!("Daemon error: {}", message),
+        other => anyhow::bail!("Unexpected response from daemon: {:?}", other),
+    })
+}
+
+fn stdin_locally(
+    spec: PcmSpec,
+    chunked: bool,
+    task: Option<Task>,
+    model_override: Option<String>,
+) -> Result<()> {
+    let cfg = config::load()?;
+    // Load the model first so chunked text starts flowing with the audio
+    let mut transcriber = load_model(task, model_override)?;
+
+    let options = RecorderOptions {
+        vad: chunked.then(|| cfg.audio.vad_options()),
+        trim: cfg.audio.trim_options(),
+        dsp: cfg.audio.dsp_options(),
+        ..Default::default()
+    };
+    let mut source = PcmSource::new("stdin", Box::new(std::io::stdin()), spec)?;
+
+    let mut segments = 0usize;
+    audio::record_segments(&mut source, &options, |recording| {
+        segments += 1;
+        println!("{}", transcriber.transcribe(&recording.samples)?.text);
+        Ok(())
+    })?;
+
+    if segments == 0 && !chunked {
+        anyhow::bail!("No audio received on stdin");
+    }
+    Ok(())
+}
+
+fn transcribe_locally(
+    path: &Path,
+    format: TranscriptFormat,
+    words: bool,
+    task: Option<Task>,
+    model_override: Option<String>,
+) -> Result<String> {
+    let samples = audio::load_audio_file(path)?;
+    if samples.is_empty() {
+        anyhow::bail!("File contains no audio: {}", path.display());
+    }
+
+    let mut transcriber = load_model(task, model_override)?;
+    let transcript = if format.is_timed() {
+        format_transcript(&transcriber.transcribe_timed(&samples, words)?, format)
+    } else {
+        transcriber.transcribe(&samples)?.text + "\n"
+    };
+    info!("Decoding: {}", transcriber.decode_stats());
+    Ok(transcript)
+}
+
+/// Load the configured (or overridden) model in this process
+fn load_model(task: Option<Task>, model_override: Option<String>) -> Result<CandleEngine> {
+    let mut cfg = config::load()?;
+    if let Some(model_path) = model_override {
+        cfg.model.path = model_path.into();
+    }
+
+    if !cfg.model.path.exists() {
+        anyhow::bail!(
+            "Model not found: {}\nRun: hyprvoice download {}",
+            cfg.model.path.display(),
+            cfg.model
+                .path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+        );
+    }
+
+    info!("Loading whisper model...");
+    CandleEngine::with_options(
+        cfg.model
+            .path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid model path"))?,
+        &cfg.model.language,
+        cfg.model.prompt.clone(),
+        cfg.model.offline,
+    )?
+    .with_allowed_languages(&cfg.model.languages)?
+    .with_beam_search(cfg.model.beam_size, cfg.model.length_penalty)?
+    .with_draft_model(cfg.model.draft_model_path.as_deref())
+    .with_task(task.unwrap_or(cfg.model.task))
+}
@@ -4,8 +4,6 @@ use std::path::PathBuf;
 
 const APP_NAME: &str = "hyprvoice";
 
-const DEFAULT_PROMPT: &str = "async, await, impl, struct, enum, pub, static, btreemap, hashmap, kubernetes, k8s, docker, container, pod, lifecycle, workflow, ci/cd, yaml, json, rustlang, python, javascript, typescript, bash, git, repo, branch, commit, push, pull, merge, rebase, upstream, downstream, middleware, database, sql, postgres, redis, api, endpoint, graphql, rest, grpc, protobuf, systemd, journalctl, flatpak, wayland, nix, cargo.";
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub model: ModelConfig,
@@ -19,12 +17,27 @@ pub struct ModelConfig {
     pub path: PathBuf,
     /// HuggingFace model ID for Candle engine (e.g., "openai/whisper-large-v3-turbo")
     pub model_id: String,
-    /// Path to optional draft model file for speculative decoding
+    /// Path to optional draft model file for speculative decoding (None = off)
     pub draft_model_path: Option<PathBuf>,
-    /// Language code (e.g., "en")
+    /// Language code (e.g., "en"), or "auto" to detect it
     pub language: String,
+    /// Languages "auto" may pick from (empty = any the model knows)
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// "transcribe", or "translate" for English text from any spoken language
+    #[serde(default)]
+    pub task: crate::transcribe::Task,
+    /// Beam search width for the first decoding attempt (1 = greedy, fastest)
+    #[serde(default = "default_beam_size")]
+    pub beam_size: usize,
+    /// Beam ranking length penalty exponent (None = plain per-token average)
+    #[serde(default)]
+    pub length_penalty: Option<f32>,
     /// Optional prompt to bias the model vocabulary (technical terms)
     pub prompt: Option<String>,
+    /// Never download model files; everything must be on disk already
+    #[serde(default)]
+    pub offline: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,12 +46,148 @@ pub struct AudioConfig {
     pub sample_rate: u32,
     /// Recording timeout in seconds (0 = no timeout)
     pub timeout_secs: u32,
+    /// Input device: index or name substring from `hyprvoice devices` (None = system default)
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Record only this input channel (1-based, so 0 is invalid) instead of averaging all channels
+    #[serde(default)]
+    pub channel: Option<u16>,
     /// Save audio recordings to disk
     #[serde(default)]
     pub save_audio_clips: bool,
-    /// Directory to save audio clips (WAV format with timestamps)
+    /// Directory to save audio clips (one file per recording, named by timestamp)
     #[serde(default = "default_audio_clips_path")]
     pub audio_clips_path: PathBuf,
+    /// Saved clip format: "flac", "wav16" or "wav32"
+    #[serde(default)]
+    pub clip_format: crate::audio::ClipFormat,
+    /// Delete saved clips older than this many days (None = keep forever)
+    #[serde(default)]
+    pub clips_max_age_days: Option<u32>,
+    /// Delete the oldest clips once they take up more than this many MB
+    #[serde(default)]
+    pub clips_max_total_mb: Option<u64>,
+    /// Keep at most this many saved clips
+    #[serde(default)]
+    pub clips_max_count: Option<usize>,
+    /// End toggle recordings automatically once speech is followed by silence
+    #[serde(default)]
+    pub vad_auto_stop: bool,
+    /// Trailing silence in milliseconds that ends an auto-stop recording
+    #[serde(default = "default_vad_silence_ms")]
+    pub vad_silence_ms: u32,
+    /// Minimum level in dBFS for audio to count as speech
+    #[serde(default = "default_vad_threshold_db")]
+    pub vad_threshold_db: f32,
+    /// Auto-stop recordings without any speech end after this many milliseconds (0 = run to the time limit)
+    #[serde(default = "default_vad_no_speech_ms")]
+    pub vad_no_speech_ms: u32,
+    /// Cut leading and trailing silence before transcription
+    #[serde(default = "default_true")]
+    pub trim_silence: bool,
+    /// Audio in milliseconds kept around the detected speech when trimming
+    #[serde(default = "default_trim_padding_ms")]
+    pub trim_padding_ms: u32,
+    /// High-pass filter cutoff in Hz, removes DC offset and rumble (None = off)
+    #[serde(default)]
+    pub high_pass_hz: Option<f32>,
+    /// Mute audio quieter than this level in dBFS (None = off)
+    #[serde(default)]
+    pub noise_gate_db: Option<f32>,
+    /// Automatic gain control for quiet or inconsistent microphones
+    #[serde(default)]
+    pub agc: bool,
+    /// Level normalization: "off", "peak" or "rms"
+    #[serde(default)]
+    pub normalize: crate::audio::Normalization,
+    /// Normalization target in dBFS (None = -1 for peak, -20 for rms)
+    #[serde(default)]
+    pub normalize_target_db: Option<f32>,
+    /// Daemon keeps the microphone open and prepends this much audio to recordings (0 = off)
+    #[serde(default)]
+    pub preroll_ms: u32,
+    /// Daemon transcribes the latest audio this often while recording (0 = off)
+    #[serde(default)]
+    pub partial_interval_ms: u32,
+}
+
+fn default_beam_size() -> usize {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_trim_padding_ms() -> u32 {
+    300
+}
+
+fn default_vad_silence_ms() -> u32 {
+    1200
+}
+
+fn default_vad_no_speech_ms() -> u32 {
+    10000
+}
+
+fn default_vad_threshold_db() -> f32 {
+    -40.0
+}
+
+impl AudioConfig {
+    /// Voice activity detection settings derived from config
+    pub fn vad_options(&self) -> crate::audio::VadOptions {
+        crate::audio::VadOptions {
+            threshold_db: self.vad_threshold_db,
+            silence: std::time::Duration::from_millis(self.vad_silence_ms as u64),
+            no_speech: (self.vad_no_speech_ms > 0)
+                .then(|| std::time::Duration::from_millis(self.vad_no_speech_ms as u64)),
+            ..Default::default()
+        }
+    }
+
+    /// 0-based input channel to record, or None to average all channels
+    ///
+    /// Fails for `channel = 0`, since channels are numbered from 1.
+    pub fn channel_index(&self) -> Result<Option<u16>> {
+        match self.channel {
+            Some(0) => anyhow::bail!(
+                "Invalid audio.channel = 0: channels are numbered from 1 (leave it unset to average all)"
+            ),
+            channel => Ok(channel.map(|c| c - 1)),
+        }
+    }
+
+    /// Preprocessing chain settings derived from config
+    pub fn dsp_options(&self) -> crate::audio::DspOptions {
+        crate::audio::DspOptions {
+            high_pass_hz: self.high_pass_hz.filter(|hz| *hz > 0.0),
+            noise_gate_db: self.noise_gate_db,
+            agc: self.agc,
+            normalize: self.normalize,
+            normalize_target_db: self.normalize_target_db,
+        }
+    }
+
+    /// Retention limits for saved clips
+    pub fn clip_retention(&self) -> crate::audio::ClipRetention {
+        crate::audio::ClipRetention {
+            max_age: self
+                .clips_max_age_days
+                .map(|days| std::time::Duration::from_secs(days as u64 * 24 * 60 * 60)),
+            max_total_bytes: self.clips_max_total_mb.map(|mb| mb * 1024 * 1024),
+            max_count: self.clips_max_count,
+        }
+    }
+
+    /// Silence trimming settings, or None if trimming is disabled
+    pub fn trim_options(&self) -> Option<crate::audio::TrimOptions> {
+        self.trim_silence.then(|| crate::audio::TrimOptions {
+            vad: self.vad_options(),
+            padding: std::time::Duration::from_millis(self.trim_padding_ms as u64),
+        })
+    }
 }
 
 fn default_audio_clips_path() -> PathBuf {
@@ -67,15 +216,39 @@ impl Default for Config {
             model: ModelConfig {
                 path: data_dir.join("models/whisper-large-v3-turbo-safetensors"),
                 model_id: "openai/whisper-large-v3-turbo".to_string(),
-                draft_model_path: Some(data_dir.join("models/ggml-tiny.en.bin")),
+                draft_model_path: None, // Must share the main model's vocabulary
                 language: "en".to_string(),
+                languages: Vec::new(),
+                task: crate::transcribe::Task::Transcribe,
+                beam_size: default_beam_size(),
+                length_penalty: None,
                 prompt: None, // Disabled by default - causes decoder issues when enabled
+                offline: false,
             },
             audio: AudioConfig {
                 sample_rate: 16000,
                 timeout_secs: 30,
+                device: None,
+                channel: None,
                 save_audio_clips: false,
                 audio_clips_path: default_audio_clips_path(),
+                clip_format: crate::audio::ClipFormat::Flac,
+                clips_max_age_days: None,
+                clips_max_total_mb: None,
+                clips_max_count: None,
+                vad_auto_stop: false,
+                vad_silence_ms: default_vad_silence_ms(),
+                vad_threshold_db: default_vad_threshold_db(),
+                vad_no_speech_ms: default_vad_no_speech_ms(),
+                trim_silence: true,
+                trim_padding_ms: default_trim_padding_ms(),
+                high_pass_hz: None,
+                noise_gate_db: None,
+                agc: false,
+                normalize: crate::audio::Normalization::Off,
+                normalize_target_db: None,
+                preroll_ms: 0,
+                partial_interval_ms: 0,
             },
             output: OutputConfig {
                 display_server: None,
@@ -113,5 +286,19 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.audio.sample_rate, 16000);
         assert_eq!(config.model.language, "en");
+        // Preprocessing is opt-in, stage by stage
+        assert_eq!(config.audio.dsp_options().high_pass_hz, None);
+        // Live partials cost a decode each, so they're opt-in too
+        assert_eq!(config.audio.partial_interval_ms, 0);
+    }
+
+    #[test]
+    fn test_channel_is_one_based() {
+        let mut config = Config::default();
+        assert_eq!(config.audio.channel_index().unwrap(), None);
+        config.audio.channel = Some(2);
+        assert_eq!(config.audio.channel_index().unwrap(), Some(1));
+        config.audio.channel = Some(0);
+        assert!(config.audio.channel_index().is_err());
     }
 }
@@ -1,6 +1,8 @@
 use anyhow::{Context, Result};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
+use std::thread;
 use std::time::Duration;
 use tracing::info;
 
@@ -10,8 +12,8 @@ use super::server::{get_socket_path, is_daemon_running};
 /// Timeout for daemon communication (30 seconds)
 const DAEMON_TIMEOUT: Duration = Duration::from_secs(30);
 
-/// Send request to daemon and get response
-pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
+/// Connect to the daemon and write a single request line
+fn connect_and_send(request: &DaemonRequest, read_timeout: Duration) -> Result<UnixStream> {
     let socket_path = get_socket_path()?;
 
     let mut stream =
@@ -19,7 +21,7 @@ pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
 
     // Set timeout for both read and write operations
     stream
-        .set_read_timeout(Some(DAEMON_TIMEOUT))
+        .set_read_timeout(Some(read_timeout))
         .context("Failed to set read timeout")?;
     stream
         .set_write_timeout(Some(DAEMON_TIMEOUT))
@@ -32,17 +34,82 @@ pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
     stream.write_all(b"\n")?;
     stream.flush()?;
 
-    // Read response
-    let mut reader = BufReader::new(stream);
+    Ok(stream)
+}
+
+/// Read one response line from the daemon
+fn read_response(reader: &mut BufReader<UnixStream>) -> Result<DaemonResponse> {
     let mut line = String::new();
-    reader
+    let read = reader
         .read_line(&mut line)
         .context("Failed to read daemon response (timeout or connection closed)")?;
+    if read == 0 {
+        anyhow::bail!("Daemon closed the connection without responding");
+    }
+
+    serde_json::from_str(line.trim()).context("Failed to parse daemon response")
+}
+
+/// Send request to daemon and get response
+pub fn send_request(request: &DaemonRequest) -> Result<DaemonResponse> {
+    send_request_with_timeout(request, DAEMON_TIMEOUT)
+}
 
-    let response: DaemonResponse =
-        serde_json::from_str(line.trim()).context("Failed to parse daemon response")?;
+/// Send request and wait up to `read_timeout` for the response (long transcriptions)
+pub fn send_request_with_timeout(
+    request: &DaemonRequest,
+    read_timeout: Duration,
+) -> Result<DaemonResponse> {
+    let stream = connect_and_send(request, read_timeout)?;
+    let mut reader = BufReader::new(stream);
+    read_response(&mut reader)
+}
+
+/// Send request and hand each response to `handler` until it returns false
+///
+/// Used for requests the daemon answers more than once (e.g. auto-stop recordings).
+/// `read_timeout` bounds the wait between two responses.
+pub fn send_request_streaming(
+    request: &DaemonRequest,
+    read_timeout: Duration,
+    mut handler: impl FnMut(DaemonResponse) -> Result<bool>,
+) -> Result<()> {
+    let stream = connect_and_send(request, read_timeout)?;
+    let mut reader = BufReader::new(stream);
+
+    while handler(read_response(&mut reader)?)? {}
+
+    Ok(())
+}
+
+/// Send request followed by a raw `body`, handing each response to `handler`
+///
+/// The body is copied until EOF on a separate thread so responses can arrive
+/// while it is still streaming. There is no timeout between responses, since
+/// a live stream may be silent for a long time.
+pub fn send_request_with_body(
+    request: &DaemonRequest,
+    mut body: impl Read + Send + 'static,
+    mut handler: impl FnMut(DaemonResponse) -> Result<bool>,
+) -> Result<()> {
+    let stream = connect_and_send(request, DAEMON_TIMEOUT)?;
+    stream.set_read_timeout(None)?;
+    // Writes block while the daemon is busy transcribing a segment
+    stream.set_write_timeout(None)?;
+
+    let mut writer = stream.try_clone()?;
+    let upload = thread::spawn(move || -> Result<()> {
+        std::io::copy(&mut body, &mut writer).context("Failed to send audio to daemon")?;
+        writer.shutdown(Shutdown::Write)?;
+        Ok(())
+    });
+
+    let mut reader = BufReader::new(stream);
+    while handler(read_response(&mut reader)?)? {}
 
-    Ok(response)
+    upload
+        .join()
+        .map_err(|_| anyhow::anyhow!("Upload thread panicked"))?
 }
 
 /// Stop recording via daemon
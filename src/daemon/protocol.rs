@@ -1,17 +1,66 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::audio::PcmSpec;
+use crate::transcribe::{DetectedLanguage, Task, Transcription};
 
 /// Request from client to daemon
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum DaemonRequest {
     #[serde(rename = "start_recording")]
-    StartRecording { max_duration: u32 },
+    StartRecording {
+        max_duration: u32,
+        /// End on trailing silence and send the result on the same connection
+        #[serde(default)]
+        auto_stop: bool,
+        /// Overrides `[model] task` for this recording
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<Task>,
+    },
     #[serde(rename = "stop_recording")]
     StopRecording,
     #[serde(rename = "shutdown")]
     Shutdown,
     #[serde(rename = "ping")]
     Ping,
+    #[serde(rename = "status")]
+    Status,
+    /// Stream `Level` responses (~20Hz) until the client disconnects
+    #[serde(rename = "subscribe_levels")]
+    SubscribeLevels,
+    /// Stream `Partial` responses while recordings run, until the client disconnects
+    #[serde(rename = "subscribe_partials")]
+    SubscribePartials,
+    /// Transcribe an audio file with the resident model (absolute path)
+    #[serde(rename = "transcribe_file")]
+    TranscribeFile {
+        path: PathBuf,
+        /// Answer with a timed `Transcript` instead of `Success`
+        #[serde(default)]
+        timestamps: bool,
+        /// Include word timings and confidences in the `Transcript`
+        #[serde(default)]
+        words: bool,
+        /// Overrides `[model] task` for this file
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<Task>,
+    },
+    /// Transcribe raw PCM sent after the request line until the client half-closes
+    ///
+    /// Answered with one `Success` per segment (a single one unless `chunked`),
+    /// then `Ok` once the stream is done.
+    #[serde(rename = "transcribe_stream")]
+    TranscribeStream {
+        #[serde(flatten)]
+        spec: PcmSpec,
+        /// Split on pauses (VAD) and send text per segment as it is spoken
+        #[serde(default)]
+        chunked: bool,
+        /// Overrides `[model] task` for this stream
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        task: Option<Task>,
+    },
 }
 
 /// Response from daemon to client
@@ -23,7 +72,41 @@ pub enum DaemonResponse {
     #[serde(rename = "recording")]
     Recording,
     #[serde(rename = "success")]
-    Success { text: String },
+    Success {
+        text: String,
+        /// Input level problems noticed while recording (muted, quiet, clipping)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+        /// Spoken language, when detected (`language = "auto"`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<DetectedLanguage>,
+    },
     #[serde(rename = "error")]
     Error { message: String },
+    /// Timed transcript (`TranscribeFile` with timestamps)
+    #[serde(rename = "transcript")]
+    Transcript(Transcription),
+    /// Live input level; -100 dBFS when not recording
+    #[serde(rename = "level")]
+    Level {
+        recording: bool,
+        rms_db: f32,
+        peak_db: f32,
+        /// RMS level on a 0-100 scale for simple meters
+        percent: u8,
+    },
+    /// Live hypothesis of the running recording; replaced by each newer one
+    ///
+    /// Sent whenever it changes and repeated periodically. `recording: false`
+    /// (empty text) once the recording has ended.
+    #[serde(rename = "partial")]
+    Partial { recording: bool, text: String },
+    #[serde(rename = "daemon_status")]
+    Status {
+        recording: bool,
+        /// Whether the input device is currently open (recording or pre-roll)
+        mic_open: bool,
+        /// Pre-roll buffer length, 0 when disabled
+        preroll_ms: u32,
+    },
 }
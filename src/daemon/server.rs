@@ -5,12 +5,17 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use crate::audio::capture_toggle;
+use crate::audio::{
+    self, AudioLevel, AudioRecorder, AudioSource, CpalSource, PcmSource, PcmSpec, PrerollBuffer,
+    RecorderOptions, Recording, WHISPER_SAMPLE_RATE,
+};
 use crate::daemon::protocol::{DaemonRequest, DaemonResponse};
 use crate::state;
+use crate::transcribe::longform::complete_windows;
+use crate::transcribe::{Task, WindowCache};
 // Transcriber trait is now used via Box<dyn ...>
 
 /// Get the path to the daemon socket
@@ -64,16 +69,80 @@ pub fn is_daemon_running() -> bool {
     serde_json::from_str::<DaemonResponse>(line.trim()).is_ok()
 }
 
+/// Extra audio captured after a stop request so trailing words are not cut off
+const TRAILING_AUDIO: Duration = Duration::from_secs(1);
+
+/// How often an auto-stop client checks whether its recording has ended
+const AUTO_STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often live input levels are sampled and sent to subscribers
+const LEVEL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Level reported while no recording is running
+const IDLE_LEVEL_DB: f32 = -100.0;
+
+/// Latest audio transcribed for each live partial (one Whisper window)
+const PARTIAL_WINDOW: Duration = Duration::from_secs(30);
+
+/// Audio needed before the first partial is attempted
+const MIN_PARTIAL_AUDIO: Duration = Duration::from_secs(1);
+
+/// How often partial subscribers check for a new hypothesis
+const PARTIAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Partial subscribers get the current hypothesis at least this often
+const PARTIAL_KEEPALIVE: Duration = Duration::from_secs(1);
+
+/// How often the running recording is checked for complete long-form windows
+const WINDOW_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Shared state for async recording
 struct RecordingState {
-    handle: Option<JoinHandle<Result<Vec<f32>>>>,
-    audio: Option<Vec<f32>>,
+    recorder: Option<AudioRecorder>,
+    /// Incremented on every start so waiters can tell recordings apart
+    session: u64,
+    /// Task of the running recording
+    task: Task,
+}
+
+/// Live hypothesis of the running recording
+#[derive(Default)]
+struct PartialState {
+    /// None while no recording is running
+    text: Option<String>,
+    /// Incremented on every change so subscribers can tell updates apart
+    version: u64,
+}
+
+/// Long-form windows of the running recording decoded ahead of the final pass
+#[derive(Default)]
+struct EarlyWindows {
+    /// Recording the windows belong to (0 = none)
+    session: u64,
+    /// Trim and preprocessing settings of that recording
+    options: RecorderOptions,
+    cache: WindowCache,
+    /// Complete raw windows when the audio was last copied and found nothing
+    /// new to decode (the trimmed audio can hold fewer windows than the raw)
+    checked: usize,
 }
 
 /// Daemon server state
 struct DaemonServer {
     transcriber: Arc<Mutex<Box<dyn crate::transcribe::Transcriber>>>,
     recording_state: Arc<Mutex<RecordingState>>,
+    /// `[model] task`, used unless a request overrides it
+    task: Task,
+    /// Why the model can't translate, if it can't
+    translate_error: Option<String>,
+    /// Always-open input stream, when pre-roll is enabled
+    preroll: Option<PrerollBuffer>,
+    /// Latest input level of the running recording
+    level: Mutex<Option<AudioLevel>>,
+    /// How often live partials are transcribed (None = disabled)
+    partial_interval: Option<Duration>,
+    partial: Mutex<PartialState>,
+    early_windows: Mutex<EarlyWindows>,
     shutdown: Arc<AtomicBool>,
 }
 
@@ -92,51 +161,80 @@ impl DaemonServer {
                 .ok_or_else(|| anyhow::anyhow!("Invalid model path"))?,
             &config.model.language,
             config.model.prompt.clone(),
-        )?;
+            config.model.offline,
+        )?
+        .with_allowed_languages(&config.model.languages)?
+        .with_beam_search(config.model.beam_size, config.model.length_penalty)?
+        .with_draft_model(config.model.draft_model_path.as_deref())
+        .with_task(config.model.task)?;
+        let translate_error = transcriber
+            .check_task(Task::Translate)
+            .err()
+            .map(|e| format!("{:#}", e));
 
         info!("Model loaded and resident in GPU VRAM");
 
+        // Reported at startup rather than on the first recording
+        config.audio.channel_index()?;
+
+        let preroll = Self::start_preroll(&config);
+
         Ok(Self {
             transcriber: Arc::new(Mutex::new(Box::new(transcriber))),
             recording_state: Arc::new(Mutex::new(RecordingState {
-                handle: None,
-                audio: None,
+                recorder: None,
+                session: 0,
+                task: config.model.task,
             })),
+            task: config.model.task,
+            translate_error,
+            preroll,
+            level: Mutex::new(None),
+            partial_interval: (config.audio.partial_interval_ms > 0)
+                .then(|| Duration::from_millis(config.audio.partial_interval_ms as u64)),
+            partial: Mutex::new(PartialState::default()),
+            early_windows: Mutex::new(EarlyWindows::default()),
             shutdown: Arc::new(AtomicBool::new(false)),
         })
     }
 
-    /// Save audio recording as WAV file with timestamp
-    fn save_audio_recording(samples: &[f32], output_dir: &Path, sample_rate: u32) -> Result<()> {
-        // Create output directory if it doesn't exist
-        std::fs::create_dir_all(output_dir).context("Failed to create audio clips directory")?;
-
-        // Generate filename with timestamp
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let filename = format!("recording_{}.wav", timestamp);
-        let filepath = output_dir.join(filename);
-
-        // Write WAV file
-        let spec = hound::WavSpec {
-            channels: 1,
-            sample_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
+    /// Open the pre-roll buffer if enabled (failures fall back to on-demand capture)
+    fn start_preroll(config: &crate::config::Config) -> Option<PrerollBuffer> {
+        if config.audio.preroll_ms == 0 {
+            return None;
+        }
 
-        let mut writer =
-            hound::WavWriter::create(&filepath, spec).context("Failed to create WAV file")?;
+        let duration = Duration::from_millis(config.audio.preroll_ms as u64);
+        let preroll = crate::audio::select_input_device(config.audio.device.as_deref())
+            .and_then(|device| PrerollBuffer::start(device, config.audio.device.clone(), duration));
 
-        for &sample in samples {
-            writer
-                .write_sample(sample)
-                .context("Failed to write sample")?;
+        match preroll {
+            Ok(preroll) => Some(preroll),
+            Err(e) => {
+                warn!(
+                    "Failed to start pre-roll buffer, opening mic on demand: {:#}",
+                    e
+                );
+                None
+            },
         }
+    }
 
-        writer.finalize().context("Failed to finalize WAV file")?;
+    /// Save a timestamped clip in the configured format (FLAC by default) and
+    /// apply the retention policy (failures only log a warning)
+    fn save_audio_recording(samples: &[f32], audio: &crate::config::AudioConfig) {
+        let dir = &audio.audio_clips_path;
+        if let Err(e) = audio::save_clip(samples, dir, WHISPER_SAMPLE_RATE, audio.clip_format) {
+            warn!("Failed to save audio recording: {}", e);
+            return;
+        }
 
-        info!("Audio saved to: {}", filepath.display());
-        Ok(())
+        let retention = audio.clip_retention();
+        if retention.is_enabled() {
+            if let Err(e) = audio::prune_clips(dir, &retention, false) {
+                warn!("Failed to prune audio clips: {}", e);
+            }
+        }
     }
 
     fn handle_client(&self, mut stream: UnixStream) -> Result<()> {
@@ -155,12 +253,48 @@ impl DaemonServer {
             DaemonRequest::Ping => DaemonResponse::Ok {
                 message: "pong".to_string(),
             },
-            DaemonRequest::StartRecording { max_duration } => {
-                self.handle_start_recording(max_duration)?
+            DaemonRequest::StartRecording {
+                max_duration,
+                auto_stop,
+                task,
+            } => {
+                let task = task.unwrap_or(self.task);
+                let (response, session) =
+                    self.handle_start_recording(max_duration, auto_stop, task)?;
+
+                // Auto-stop clients stay connected and get the transcription once VAD ends it
+                if let (DaemonResponse::Recording, true) = (&response, auto_stop) {
+                    Self::write_response(&mut stream, &response)?;
+                    self.wait_for_auto_stop(session)?
+                } else {
+                    response
+                }
             },
             DaemonRequest::StopRecording => self.handle_stop_recording()?,
+            DaemonRequest::Status => self.handle_status()?,
+            DaemonRequest::TranscribeFile {
+                path,
+                timestamps,
+                words,
+                task,
+            } => {
+                self.handle_transcribe_file(&path, timestamps, words, task.unwrap_or(self.task))?
+            },
+            // Long-lived: streams its own responses until the client disconnects
+            DaemonRequest::SubscribeLevels => return self.stream_levels(&mut stream),
+            DaemonRequest::SubscribePartials => return self.stream_partials(&mut stream),
+            // The request line is followed by raw audio on the same connection
+            DaemonRequest::TranscribeStream {
+                spec,
+                chunked,
+                task,
+            } => {
+                let task = task.unwrap_or(self.task);
+                return self.handle_transcribe_stream(reader, &mut stream, spec, chunked, task);
+            },
             DaemonRequest::Shutdown => {
                 info!("Shutdown requested");
+                self.cancel_recording();
                 self.shutdown.store(true, Ordering::SeqCst);
                 DaemonResponse::Ok {
                     message: "shutting down".to_string(),
@@ -168,15 +302,25 @@ impl DaemonServer {
             },
         };
 
-        let response_json = serde_json::to_string(&response)?;
+        Self::write_response(&mut stream, &response)
+    }
+
+    /// Write a single newline-delimited response
+    fn write_response(stream: &mut UnixStream, response: &DaemonResponse) -> Result<()> {
+        let response_json = serde_json::to_string(response)?;
         stream.write_all(response_json.as_bytes())?;
         stream.write_all(b"\n")?;
         stream.flush()?;
-
         Ok(())
     }
 
-    fn handle_start_recording(&self, max_duration: u32) -> Result<DaemonResponse> {
+    /// Start a recording, returning the response and the new session id
+    fn handle_start_recording(
+        &self,
+        max_duration: u32,
+        auto_stop: bool,
+        task: Task,
+    ) -> Result<(DaemonResponse, u64)> {
         // Atomic check-and-set: mutex ensures no race between check and state update
         let mut state = self
             .recording_state
@@ -184,27 +328,507 @@ impl DaemonServer {
             .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
 
         // Check if already recording
-        if state.handle.is_some() {
-            return Ok(DaemonResponse::Error {
-                message: "Already recording".to_string(),
-            });
+        if state.recorder.is_some() {
+            return Ok((
+                DaemonResponse::Error {
+                    message: "Already recording".to_string(),
+                },
+                state.session,
+            ));
+        }
+
+        // Checked up front rather than failing after the user has spoken
+        if let (Task::Translate, Some(message)) = (task, &self.translate_error) {
+            return Ok((
+                DaemonResponse::Error {
+                    message: message.clone(),
+                },
+                state.session,
+            ));
         }
 
-        info!("Starting background recording (max {}s)", max_duration);
+        info!(
+            "Starting background recording (max {}s, auto-stop: {}, task: {})",
+            max_duration, auto_stop, task
+        );
+
+        // Resolve the input up front so a missing device is reported to the client
+        let config = crate::config::load()?;
+        let channel = match config.audio.channel_index() {
+            Ok(channel) => channel,
+            Err(e) => {
+                return Ok((
+                    DaemonResponse::Error {
+                        message: format!("{:#}", e),
+                    },
+                    state.session,
+                ));
+            },
+        };
+        let source = match self.open_source(&config) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to open input device: {:#}", e);
+                return Ok((
+                    DaemonResponse::Error {
+                        message: format!("{:#}", e),
+                    },
+                    state.session,
+                ));
+            },
+        };
+
+        // Start recording on its own worker thread
+        let options = RecorderOptions {
+            max_duration: Some(Duration::from_secs(max_duration as u64)),
+            trailing: TRAILING_AUDIO,
+            vad: auto_stop.then(|| config.audio.vad_options()),
+            trim: config.audio.trim_options(),
+            channel,
+            dsp: config.audio.dsp_options(),
+            // Read by live partials and early window decoding
+            live_audio: true,
+        };
+        let recorder = AudioRecorder::start(source, options.clone())?;
 
         // Create PID file for UI state (Waybar uses this)
         state::toggle::start_recording()?;
 
-        // Set up signal handler for this recording session
-        state::toggle::setup_signal_handler()?;
+        state.recorder = Some(recorder);
+        state.session += 1;
+        state.task = task;
+        if let Ok(mut early) = self.early_windows.lock() {
+            *early = EarlyWindows {
+                session: state.session,
+                options,
+                cache: WindowCache::default(),
+                checked: 0,
+            };
+        }
+        if self.partial_interval.is_some() {
+            self.set_partial(Some(String::new()));
+        }
+
+        Ok((DaemonResponse::Recording, state.session))
+    }
+
+    /// Recording source: the pre-roll buffer if active, otherwise a fresh capture stream
+    fn open_source(&self, config: &crate::config::Config) -> Result<Box<dyn AudioSource>> {
+        if let Some(preroll) = &self.preroll {
+            return Ok(Box::new(preroll.source()));
+        }
+
+        let device = crate::audio::select_input_device(config.audio.device.as_deref())?;
+        let source = CpalSource::new(device)?.with_reconnect(config.audio.device.clone());
+        Ok(Box::new(source))
+    }
+
+    /// Sample the recorder's input level and publish it to the level file
+    fn update_level(&self) {
+        let (recording, new_level) = match self.recording_state.lock() {
+            Ok(state) => (
+                state.recorder.is_some(),
+                state.recorder.as_ref().and_then(|r| r.take_level()),
+            ),
+            Err(_) => return,
+        };
+
+        let Ok(mut level) = self.level.lock() else {
+            return;
+        };
+
+        if !recording {
+            if level.take().is_some() {
+                let _ = state::toggle::cleanup_level();
+            }
+            return;
+        }
+
+        // Keep the previous level if no audio arrived since the last tick
+        if let Some(new_level) = new_level {
+            *level = Some(new_level);
+            if let Err(e) =
+                state::toggle::write_level(new_level.percent(), new_level.rms_db, new_level.peak_db)
+            {
+                debug!("Failed to write level file: {}", e);
+            }
+        }
+    }
+
+    /// Send the current input level every `LEVEL_INTERVAL` until the client disconnects
+    fn stream_levels(&self, stream: &mut UnixStream) -> Result<()> {
+        info!("Client subscribed to input levels");
+
+        while !self.shutdown.load(Ordering::SeqCst) {
+            let level = *self
+                .level
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Level mutex poisoned: {}", e))?;
+
+            let response = match level {
+                Some(level) => DaemonResponse::Level {
+                    recording: true,
+                    rms_db: level.rms_db,
+                    peak_db: level.peak_db,
+                    percent: level.percent(),
+                },
+                None => DaemonResponse::Level {
+                    recording: false,
+                    rms_db: IDLE_LEVEL_DB,
+                    peak_db: IDLE_LEVEL_DB,
+                    percent: 0,
+                },
+            };
+
+            if Self::write_response(stream, &response).is_err() {
+                info!("Level subscriber disconnected");
+                break;
+            }
+            std::thread::sleep(LEVEL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    /// Transcribe the latest window of the running recording and publish the hypothesis
+    ///
+    /// `heard` remembers the last audio transcribed, so nothing is decoded twice.
+    /// Skipped while the model is busy (e.g. with the final pass or a file).
+    fn update_partial(&self, heard: &mut Option<(u64, Duration)>) {
+        let (session, task, captured, audio) = match self.recording_state.lock() {
+            Ok(state) => match &state.recorder {
+                Some(recorder) => (
+                    state.session,
+                    state.task,
+                    recorder.captured(),
+                    recorder.recent_audio(PARTIAL_WINDOW),
+                ),
+                None => return,
+            },
+            Err(_) => return,
+        };
+
+        if captured < MIN_PARTIAL_AUDIO || *heard == Some((session, captured)) {
+            return;
+        }
+
+        let Ok(mut transcriber) = self.transcriber.try_lock() else {
+            return;
+        };
+        let result = transcriber
+            .set_task(task)
+            .and_then(|()| transcriber.transcribe_partial(&audio));
+        drop(transcriber);
+        *heard = Some((session, captured));
+
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Partial transcription failed: {}", e);
+                return;
+            },
+        };
+
+        // Hold the state lock so a stop can't clear the partial before this lands
+        let Ok(state) = self.recording_state.lock() else {
+            return;
+        };
+        if state.recorder.is_some() && state.session == session {
+            debug!("Partial ({:.1}s): {}", captured.as_secs_f32(), text);
+            self.set_partial(Some(text));
+        }
+    }
+
+    /// Decode the long-form windows of the running recording that are complete
+    ///
+    /// Each window is decoded on the same trimmed and preprocessed audio the
+    /// final pass will see, so `finish_recording` only has to decode the rest.
+    fn decode_early_windows(&self) {
+        let (session, task, captured) = match self.recording_state.lock() {
+            Ok(state) => match &state.recorder {
+                Some(recorder) => (state.session, state.task, recorder.captured_samples()),
+                None => return,
+            },
+            Err(_) => return,
+        };
+
+        let options = match self.early_windows.lock() {
+            // Cheap check before copying the audio: a new window must fit in
+            // the raw audio, and it must have grown since the last look
+            Ok(early) if early.session == session => {
+                let windows = complete_windows(captured).count();
+                if windows <= early.cache.decoded().max(early.checked) {
+                    return;
+                }
+                early.options.clone()
+            },
+            _ => return,
+        };
+        let windows = complete_windows(captured).count();
+
+        // Copied only now that a window is complete, not on every check
+        let audio = match self.recording_state.lock() {
+            Ok(state) if state.session == session => match &state.recorder {
+                Some(recorder) => recorder.live_audio(),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let mark_checked = || {
+            if let Ok(mut early) = self.early_windows.lock() {
+                if early.session == session {
+                    early.checked = windows;
+                }
+            }
+        };
+        let Some(prefix) = audio::stable_prefix(audio, &options) else {
+            mark_checked();
+            return;
+        };
+
+        loop {
+            let range = match self.early_windows.lock() {
+                Ok(early) if early.session == session => early.cache.next_missing(&prefix),
+                _ => None,
+            };
+            let Some(range) = range else {
+                mark_checked();
+                return;
+            };
+
+            // Never hold up a final pass or file request; retried on the next check
+            let Ok(mut transcriber) = self.transcriber.try_lock() else {
+                return;
+            };
+            let started = Instant::now();
+            let transcription = match transcriber
+                .set_task(task)
+                .and_then(|()| transcriber.transcribe_window(&prefix[range.clone()]))
+            {
+                Ok(transcription) => transcription,
+                Err(e) => {
+                    warn!("Early window decoding failed: {}", e);
+                    return;
+                },
+            };
+            info!(
+                "Decoded window {:.0}s-{:.0}s during recording in {:.1}s",
+                range.start as f32 / WHISPER_SAMPLE_RATE as f32,
+                range.end as f32 / WHISPER_SAMPLE_RATE as f32,
+                started.elapsed().as_secs_f32()
+            );
+
+            // Stored while the model is still locked, so a waiting final pass sees it
+            match self.early_windows.lock() {
+                Ok(mut early) if early.session == session => {
+                    early.cache.insert(&prefix, range, transcription);
+                },
+                _ => return,
+            }
+        }
+    }
+
+    /// Replace the live hypothesis (None once the recording has ended)
+    fn set_partial(&self, text: Option<String>) {
+        let Ok(mut partial) = self.partial.lock() else {
+            return;
+        };
+        if partial.text == text {
+            return;
+        }
+
+        let written = match &text {
+            Some(text) => state::toggle::write_partial(text),
+            None => state::toggle::cleanup_partial(),
+        };
+        if let Err(e) = written {
+            debug!("Failed to update partial file: {}", e);
+        }
+
+        partial.text = text;
+        partial.version += 1;
+    }
+
+    /// Send the live hypothesis whenever it changes until the client disconnects
+    fn stream_partials(&self, stream: &mut UnixStream) -> Result<()> {
+        info!("Client subscribed to partial transcripts");
+
+        let mut sent: Option<(u64, Instant)> = None;
+        while !self.shutdown.load(Ordering::SeqCst) {
+            let (version, text) = {
+                let partial = self
+                    .partial
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("Partial mutex poisoned: {}", e))?;
+                (partial.version, partial.text.clone())
+            };
+
+            let due = match sent {
+                Some((sent_version, at)) => {
+                    sent_version != version || at.elapsed() >= PARTIAL_KEEPALIVE
+                },
+                None => true,
+            };
+
+            if due {
+                let response = DaemonResponse::Partial {
+                    recording: text.is_some(),
+                    text: text.unwrap_or_default(),
+                };
+                if Self::write_response(stream, &response).is_err() {
+                    info!("Partial subscriber disconnected");
+                    break;
+                }
+                sent = Some((version, Instant::now()));
+            }
+            std::thread::sleep(PARTIAL_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    fn handle_status(&self) -> Result<DaemonResponse> {
+        let state = self
+            .recording_state
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
+
+        let recording = state.recorder.is_some();
+        Ok(DaemonResponse::Status {
+            recording,
+            mic_open: recording || self.preroll.is_some(),
+            preroll_ms: self
+                .preroll
+                .as_ref()
+                .map_or(0, |p| p.duration().as_millis() as u32),
+        })
+    }
+
+    fn handle_transcribe_file(
+        &self,
+        path: &Path,
+        timestamps: bool,
+        words: bool,
+        task: Task,
+    ) -> Result<DaemonResponse> {
+        info!("Transcribing file: {}", path.display());
+
+        let samples = match crate::audio::load_audio_file(path) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to decode {}: {:#}", path.display(), e);
+                return Ok(DaemonResponse::Error {
+                    message: format!("{:#}", e),
+                });
+            },
+        };
+
+        if samples.is_empty() {
+            return Ok(DaemonResponse::Error {
+                message: "File contains no audio".to_string(),
+            });
+        }
+
+        if timestamps {
+            self.transcribe_timed(&samples, words, task)
+        } else {
+            self.transcribe_samples(&samples, task)
+        }
+    }
+
+    fn handle_transcribe_stream(
+        &self,
+        reader: BufReader<UnixStream>,
+        stream: &mut UnixStream,
+        spec: PcmSpec,
+        chunked: bool,
+        task: Task,
+    ) -> Result<()> {
+        info!(
+            "Transcribing PCM stream: {}Hz, {} channels, {} (chunked: {})",
+            spec.sample_rate, spec.channels, spec.format, chunked
+        );
+
+        // Same cleanup as microphone audio; channel selection only applies to the mic
+        let config = crate::config::load()?;
+        let options = RecorderOptions {
+            vad: chunked.then(|| config.audio.vad_options()),
+            trim: config.audio.trim_options(),
+            dsp: config.audio.dsp_options(),
+            ..Default::default()
+        };
+
+        let mut segments = 0usize;
+        let result =
+            PcmSource::new("client stream", Box::new(reader), spec).and_then(|mut source| {
+                audio::record_segments(&mut source, &options, |recording| {
+                    segments += 1;
+                    let response = self.transcribe_samples(&recording.samples, task)?;
+                    Self::write_response(stream, &response)
+                })
+            });
 
-        // Spawn recording thread
-        let handle = thread::spawn(move || capture_toggle(max_duration, 16000));
+        let response = match result {
+            Err(e) => {
+                error!("PCM stream failed: {:#}", e);
+                DaemonResponse::Error {
+                    message: format!("{:#}", e),
+                }
+            },
+            Ok(()) if segments == 0 && !chunked => DaemonResponse::Error {
+                message: "No audio received".to_string(),
+            },
+            Ok(()) => DaemonResponse::Ok {
+                message: format!("Stream finished ({} segments)", segments),
+            },
+        };
+        Self::write_response(stream, &response)
+    }
 
-        state.handle = Some(handle);
-        state.audio = None;
+    /// Run 16kHz mono audio through the resident model
+    fn transcribe_samples(&self, samples: &[f32], task: Task) -> Result<DaemonResponse> {
+        let mut transcriber = self
+            .transcriber
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Transcriber mutex poisoned: {}", e))?;
 
-        Ok(DaemonResponse::Recording)
+        let result = transcriber
+            .set_task(task)
+            .and_then(|()| transcriber.transcribe(samples));
+        Ok(match result {
+            Ok(transcription) => DaemonResponse::Success {
+                text: transcription.text,
+                warnings: Vec::new(),
+                language: transcription.language,
+            },
+            Err(e) => {
+                error!("Transcription failed with error: {}", e);
+                DaemonResponse::Error {
+                    message: format!("Transcription error: {}", e),
+                }
+            },
+        })
+    }
+
+    fn transcribe_timed(&self, samples: &[f32], words: bool, task: Task) -> Result<DaemonResponse> {
+        let mut transcriber = self
+            .transcriber
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Transcriber mutex poisoned: {}", e))?;
+
+        let result = transcriber
+            .set_task(task)
+            .and_then(|()| transcriber.transcribe_timed(samples, words));
+        Ok(match result {
+            Ok(transcription) => DaemonResponse::Transcript(transcription),
+            Err(e) => {
+                error!("Transcription failed with error: {}", e);
+                DaemonResponse::Error {
+                    message: format!("Transcription error: {}", e),
+                }
+            },
+        })
     }
 
     fn handle_stop_recording(&self) -> Result<DaemonResponse> {
@@ -214,8 +838,8 @@ impl DaemonServer {
             .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
 
         // Check if recording
-        let handle = match state.handle.take() {
-            Some(h) => h,
+        let recorder = match state.recorder.take() {
+            Some(r) => r,
             None => {
                 return Ok(DaemonResponse::Error {
                     message: "Not recording".to_string(),
@@ -224,45 +848,90 @@ impl DaemonServer {
         };
 
         info!("Stop requested - signaling recording thread");
-
-        // Send stop signal
-        state::toggle::STOP_RECORDING.store(true, Ordering::SeqCst);
+        let task = state.task;
 
         // Wait for recording thread to finish
         drop(state); // Release lock while waiting
-        let samples = handle
-            .join()
-            .map_err(|_| anyhow::anyhow!("Recording thread panicked"))??;
+        self.finish_recording(recorder.stop(), task)
+    }
 
-        // Reset stop flag for next recording
-        state::toggle::STOP_RECORDING.store(false, Ordering::SeqCst);
+    /// Block until an auto-stop recording ends on its own, then transcribe it
+    ///
+    /// Returns `Ok` without text if another client stopped the recording first.
+    fn wait_for_auto_stop(&self, session: u64) -> Result<DaemonResponse> {
+        loop {
+            std::thread::sleep(AUTO_STOP_POLL_INTERVAL);
 
-        info!("Captured {} samples", samples.len());
+            let mut state = self
+                .recording_state
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Recording state mutex poisoned: {}", e))?;
 
-        if samples.is_empty() {
-            return Ok(DaemonResponse::Error {
-                message: "No audio captured".to_string(),
-            });
+            if state.session != session {
+                return Ok(Self::stopped_elsewhere());
+            }
+            match state.recorder.take_if(|recorder| recorder.is_finished()) {
+                Some(recorder) => {
+                    let task = state.task;
+                    drop(state);
+                    info!("Recording ended automatically");
+                    return self.finish_recording(recorder.wait(), task);
+                },
+                None if state.recorder.is_none() => return Ok(Self::stopped_elsewhere()),
+                None => {},
+            }
         }
+    }
 
-        // Save audio if enabled in config
-        let config = crate::config::load()?;
-        if config.audio.save_audio_clips {
-            if let Err(e) = Self::save_audio_recording(
-                &samples,
-                &config.audio.audio_clips_path,
-                config.audio.sample_rate,
-            ) {
-                warn!("Failed to save audio recording: {}", e);
-            }
+    /// Reply to an auto-stop client whose recording another client ended
+    fn stopped_elsewhere() -> DaemonResponse {
+        DaemonResponse::Ok {
+            message: "Recording stopped by another client".to_string(),
         }
+    }
 
+    /// Save and transcribe a finished recording, updating UI state files
+    fn finish_recording(&self, recording: Result<Recording>, task: Task) -> Result<DaemonResponse> {
         // CRITICAL: Remove recording.pid BEFORE creating processing file
         // Otherwise Waybar keeps showing "recording" (checks recording.pid first)
         state::toggle::cleanup_recording()?;
+        self.set_partial(None);
 
         // Create processing state file for Waybar (now recording.pid is gone)
         state::toggle::start_processing()?;
+        let _processing_cleanup = scopeguard::guard((), |_| {
+            let _ = state::toggle::cleanup_processing();
+        });
+
+        let recording = recording?;
+        let samples = &recording.samples;
+        info!("Captured {} samples", samples.len());
+
+        // Don't run a transcription cycle on a muted microphone
+        let warnings = recording.warnings();
+        if recording.stats.is_silent() {
+            return Ok(DaemonResponse::Error {
+                message: warnings.join("; "),
+            });
+        }
+
+        if recording.no_speech {
+            return Ok(DaemonResponse::Error {
+                message: with_warnings("No speech detected", &warnings),
+            });
+        }
+
+        if samples.is_empty() {
+            return Ok(DaemonResponse::Error {
+                message: with_warnings("No audio captured", &warnings),
+            });
+        }
+
+        // Save audio if enabled in config
+        let config = crate::config::load()?;
+        if config.audio.save_audio_clips {
+            Self::save_audio_recording(samples, &config.audio);
+        }
 
         // Transcribe with the persistent model
         info!("Transcribing {} samples...", samples.len());
@@ -271,7 +940,17 @@ impl DaemonServer {
             .lock()
             .map_err(|e| anyhow::anyhow!("Transcriber mutex poisoned: {}", e))?;
 
-        let text = match transcriber.transcribe(&samples) {
+        // Windows decoded during recording are reused if the audio matches
+        let early = self
+            .early_windows
+            .lock()
+            .map(|mut early| std::mem::take(&mut *early))
+            .unwrap_or_default();
+
+        let result = transcriber
+            .set_task(task)
+            .and_then(|()| transcriber.transcribe_cached(samples, &early.cache));
+        let transcription = match result {
             Ok(t) => {
                 info!("Transcription completed successfully");
                 t
@@ -285,18 +964,43 @@ impl DaemonServer {
             },
         };
 
-        if text.is_empty() {
+        if transcription.text.is_empty() {
             return Ok(DaemonResponse::Error {
-                message: "No speech detected".to_string(),
+                message: with_warnings("No speech detected", &warnings),
             });
         }
 
-        info!("Transcribed: {}", text);
+        info!("Transcribed: {}", transcription.text);
+
+        Ok(DaemonResponse::Success {
+            text: transcription.text,
+            warnings,
+            language: transcription.language,
+        })
+    }
+
+    /// Discard any in-progress recording (used on shutdown)
+    fn cancel_recording(&self) {
+        let recorder = match self.recording_state.lock() {
+            Ok(mut state) => state.recorder.take(),
+            Err(_) => return,
+        };
 
-        // Clean up processing state file (recording.pid already removed above)
-        state::toggle::cleanup_processing()?;
+        if let Some(recorder) = recorder {
+            recorder.cancel();
+            self.set_partial(None);
+            let _ = state::toggle::cleanup_recording();
+            let _ = state::toggle::cleanup_processing();
+        }
+    }
+}
 
-        Ok(DaemonResponse::Success { text })
+/// Append input level warnings to an error message
+fn with_warnings(message: &str, warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        message.to_string()
+    } else {
+        format!("{} ({})", message, warnings.join("; "))
     }
 }
 
@@ -318,7 +1022,47 @@ pub fn run_daemon(model_path: &Path) -> Result<()> {
 
     info!("Daemon listening on {}", socket_path.display());
 
-    let server = DaemonServer::new(model_path)?;
+    let server = Arc::new(DaemonServer::new(model_path)?);
+
+    // Sample recording levels for subscribers and the status bar level file
+    {
+        let server = Arc::clone(&server);
+        std::thread::Builder::new()
+            .name("level-monitor".to_string())
+            .spawn(move || {
+                while !server.shutdown.load(Ordering::SeqCst) {
+                    server.update_level();
+                    std::thread::sleep(LEVEL_INTERVAL);
+                }
+            })?;
+    }
+
+    // Transcribe the running recording periodically for live partials
+    if let Some(interval) = server.partial_interval {
+        let server = Arc::clone(&server);
+        std::thread::Builder::new()
+            .name("partial-transcriber".to_string())
+            .spawn(move || {
+                let mut heard = None;
+                while !server.shutdown.load(Ordering::SeqCst) {
+                    std::thread::sleep(interval);
+                    server.update_partial(&mut heard);
+                }
+            })?;
+    }
+
+    // Decode complete long-form windows while recording so stopping is quick
+    {
+        let server = Arc::clone(&server);
+        std::thread::Builder::new()
+            .name("window-decoder".to_string())
+            .spawn(move || {
+                while !server.shutdown.load(Ordering::SeqCst) {
+                    std::thread::sleep(WINDOW_CHECK_INTERVAL);
+                    server.decode_early_windows();
+                }
+            })?;
+    }
 
     for stream in listener.incoming() {
         if server.shutdown.load(Ordering::SeqCst) {
@@ -328,9 +1072,13 @@ pub fn run_daemon(model_path: &Path) -> Result<()> {
 
         match stream {
             Ok(stream) => {
-                if let Err(e) = server.handle_client(stream) {
-                    error!("Error handling client: {}", e);
-                }
+                // One thread per client so long-lived requests (auto-stop) don't block stops
+                let server = Arc::clone(&server);
+                std::thread::spawn(move || {
+                    if let Err(e) = server.handle_client(stream) {
+                        error!("Error handling client: {}", e);
+                    }
+                });
             },
             Err(e) => {
                 error!("Error accepting connection: {}", e);
@@ -15,11 +15,17 @@ mod output;
 mod state;
 mod transcribe;
 
-use transcribe::Transcriber;
+use transcribe::{Task, Transcriber, TranscriptFormat};
 
 /// Maximum recording duration in toggle mode (5 minutes)
 const TOGGLE_MODE_TIMEOUT_SECS: u32 = 300;
 
+/// Length of the test recording made by `hyprvoice doctor`
+const DOCTOR_SAMPLE_SECS: u64 = 2;
+
+/// Extra time an auto-stop client waits beyond the recording limit (covers transcription)
+const AUTO_STOP_GRACE_SECS: u64 = 120;
+
 #[derive(Parser)]
 #[command(name = "hyprvoice")]
 #[command(about = "Voice dictation for Linux developers")]
@@ -48,6 +54,10 @@ enum Commands {
         /// Copy to clipboard instead of typing
         #[arg(short, long)]
         clipboard: bool,
+
+        /// transcribe, or translate to English (overrides [model] task)
+        #[arg(long)]
+        task: Option<Task>,
     },
 
     /// Stop a running recording
@@ -82,6 +92,76 @@ enum Commands {
     /// Check system dependencies
     Doctor,
 
+    /// List audio input devices and their supported configs
+    Devices,
+
+    /// Show daemon recording and microphone state
+    Status {
+        /// Show a live input level meter
+        #[arg(long, conflicts_with = "partials")]
+        levels: bool,
+        /// Follow live partial transcripts of daemon recordings
+        #[arg(long)]
+        partials: bool,
+    },
+
+    /// Transcribe an audio file (WAV, FLAC, MP3, Ogg) or raw PCM on stdin and print the text or captions
+    Transcribe {
+        /// Audio file to transcribe
+        #[arg(required_unless_present = "stdin")]
+        file: Option<std::path::PathBuf>,
+
+        /// Read raw interleaved PCM from stdin until EOF (e.g. `parecord --raw | ...`)
+        #[arg(long, conflicts_with = "file")]
+        stdin: bool,
+
+        /// Sample rate of the stdin PCM in Hz
+        #[arg(long, default_value_t = 16000, requires = "stdin")]
+        rate: u32,
+
+        /// Channel count of the stdin PCM
+        #[arg(long, default_value_t = 1, requires = "stdin")]
+        channels: u16,
+
+        /// Sample format of the stdin PCM: u8, s16le, s24le, s32le or f32le
+        #[arg(long, default_value_t = audio::PcmFormat::S16le, requires = "stdin")]
+        format: audio::PcmFormat,
+
+        /// Print text for each pause-separated segment as it is spoken
+        #[arg(long, requires = "stdin")]
+        chunked: bool,
+
+        /// Output format: text, srt, vtt or json (timed formats need a file)
+        #[arg(long, default_value_t = TranscriptFormat::Text, conflicts_with = "stdin")]
+        output_format: TranscriptFormat,
+
+        /// Add word timings and confidences (JSON; karaoke cues in WebVTT)
+        #[arg(long, conflicts_with = "stdin")]
+        words: bool,
+
+        /// Write the transcript to this file instead of stdout
+        #[arg(short, long, conflicts_with = "stdin")]
+        output: Option<std::path::PathBuf>,
+
+        /// transcribe, or translate to English (overrides [model] task)
+        #[arg(long)]
+        task: Option<Task>,
+
+        /// Override model path (always loads the model locally)
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Load the model in this process even if the daemon is running
+        #[arg(long)]
+        local: bool,
+    },
+
+    /// Manage saved audio clips
+    Clips {
+        #[command(subcommand)]
+        action: ClipsAction,
+    },
+
     /// Run daemon server (keeps model loaded in GPU memory)
     Daemon {
         /// Override model path
@@ -101,6 +181,16 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum ClipsAction {
+    /// Delete clips outside the [audio] retention limits
+    Prune {
+        /// Only report what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -112,8 +202,9 @@ fn main() -> Result<()> {
             model,
             duration,
             clipboard,
+            task,
         } => {
-            cmd_start(model, duration, clipboard)?;
+            cmd_start(model, duration, clipboard, task)?;
         },
         Commands::Stop => {
             cmd_stop()?;
@@ -132,6 +223,49 @@ fn main() -> Result<()> {
         Commands::Doctor => {
             cmd_doctor()?;
         },
+        Commands::Devices => {
+            commands::devices()?;
+        },
+        Commands::Status { levels, partials } => {
+            commands::status(levels, partials)?;
+        },
+        Commands::Transcribe {
+            file,
+            stdin,
+            rate,
+            channels,
+            format,
+            chunked,
+            output_format,
+            words,
+            output,
+            task,
+            model,
+            local,
+        } => match file {
+            Some(file) if !stdin => commands::transcribe(
+                &file,
+                output_format,
+                words,
+                output.as_deref(),
+                task,
+                model,
+                local,
+            )?,
+            _ => {
+                let spec = audio::PcmSpec {
+                    sample_rate: rate,
+                    channels,
+                    format,
+                };
+                commands::transcribe::run_stdin(spec, chunked, task, model, local)?;
+            },
+        },
+        Commands::Clips {
+            action: ClipsAction::Prune { dry_run },
+        } => {
+            commands::clips::prune(dry_run)?;
+        },
         Commands::Daemon { model } => {
             cmd_daemon(model)?;
         },
@@ -157,7 +291,10 @@ fn init_logging(verbose: bool) -> Result<()> {
         .with_ansi(false)
         .with_target(false);
 
-    let console_layer = tracing_subscriber::fmt::layer().with_target(false);
+    // Logs go to stderr so command output on stdout stays scriptable
+    let console_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false);
 
     // Respect RUST_LOG env var, fallback to default filter
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -173,18 +310,29 @@ fn init_logging(verbose: bool) -> Result<()> {
     Ok(())
 }
 
-fn cmd_start(model_override: Option<String>, duration: u32, clipboard: bool) -> Result<()> {
+fn cmd_start(
+    model_override: Option<String>,
+    duration: u32,
+    clipboard: bool,
+    task: Option<Task>,
+) -> Result<()> {
     // Check if toggle mode (duration = 0)
     if duration == 0 {
-        return cmd_start_toggle(model_override, clipboard);
+        return cmd_start_toggle(model_override, clipboard, task);
     }
 
     // Fixed duration mode
-    cmd_start_fixed(model_override, duration, clipboard)
+    cmd_start_fixed(model_override, duration, clipboard, task)
 }
 
 /// Toggle mode: first call starts, second call stops
-fn cmd_start_toggle(model_override: Option<String>, clipboard: bool) -> Result<()> {
+///
+/// `task` only applies when this call starts the recording.
+fn cmd_start_toggle(
+    model_override: Option<String>,
+    clipboard: bool,
+    task: Option<Task>,
+) -> Result<()> {
     // Load config
     let mut cfg = config::load()?;
     if let Some(model_path) = model_override {
@@ -228,34 +376,12 @@ fn cmd_start_toggle(model_override: Option<String>, clipboard: bool) -> Result<(
         let _ = state::cleanup_processing();
 
         match response {
-            daemon::DaemonResponse::Success { text } => {
-                if text.is_empty() {
-                    info!("No speech detected");
-                    return Ok(());
-                }
-
-                // Output the transcribed text
-                let output_mode = if clipboard {
-                    output::OutputMode::Clipboard
-                } else {
-                    output::OutputMode::Type
-                };
-
-                info!("Transcribed: {}", text);
-                output::inject_text(&text, output_mode)?;
-                info!("Text output via {:?}", output_mode);
-
-                // Send notification
-                let preview = if text.len() > 80 {
-                    format!("{}...", text.chars().take(77).collect::<String>())
-                } else {
-                    text.clone()
-                };
-                send_notification("Transcription Complete", &preview, "normal");
-
-                Ok(())
+            daemon::DaemonResponse::Success { text, warnings, .. } => {
+                notify_input_warnings(&warnings);
+                output_transcription(&text, clipboard)
             },
             daemon::DaemonResponse::Error { message } => {
+                send_notification("Transcription Failed", &message, "critical");
                 anyhow::bail!("Daemon error: {}", message)
             },
             _ => anyhow::bail!("Unexpected response from daemon"),
@@ -273,29 +399,103 @@ fn cmd_start_toggle(model_override: Option<String>, clipboard: bool) -> Result<(
             anyhow::bail!("Daemon is not running. Start it first with: hyprvoice daemon &");
         }
 
-        // Send start request
-        let response = daemon::send_request(&daemon::DaemonRequest::StartRecording {
+        let request = daemon::DaemonRequest::StartRecording {
             max_duration: TOGGLE_MODE_TIMEOUT_SECS,
-        })?;
+            auto_stop: cfg.audio.vad_auto_stop,
+            task,
+        };
+
+        if !cfg.audio.vad_auto_stop {
+            // Send start request
+            let response = daemon::send_request(&request)?;
+
+            return match response {
+                daemon::DaemonResponse::Recording => {
+                    info!("Daemon started recording");
+                    println!("Recording... Press Super+V again to stop and transcribe.");
+                    Ok(())
+                },
+                daemon::DaemonResponse::Error { message } => {
+                    anyhow::bail!("Failed to start recording: {}", message)
+                },
+                _ => {
+                    anyhow::bail!("Unexpected response from daemon")
+                },
+            };
+        }
 
-        match response {
+        // Auto-stop: stay connected until the daemon detects the end of speech
+        let timeout =
+            std::time::Duration::from_secs(TOGGLE_MODE_TIMEOUT_SECS as u64 + AUTO_STOP_GRACE_SECS);
+        daemon::send_request_streaming(&request, timeout, |response| match response {
             daemon::DaemonResponse::Recording => {
-                info!("Daemon started recording");
-                println!("Recording... Press Super+V again to stop and transcribe.");
-                Ok(())
+                info!("Daemon started recording (auto-stop enabled)");
+                println!("Recording... stops automatically when you stop speaking.");
+                Ok(true)
             },
-            daemon::DaemonResponse::Error { message } => {
-                anyhow::bail!("Failed to start recording: {}", message)
+            daemon::DaemonResponse::Success { text, warnings, .. } => {
+                notify_input_warnings(&warnings);
+                output_transcription(&text, clipboard)?;
+                Ok(false)
             },
-            _ => {
-                anyhow::bail!("Unexpected response from daemon")
+            // Stopped manually; the stopping client outputs the text
+            daemon::DaemonResponse::Ok { message } => {
+                info!("{}", message);
+                Ok(false)
             },
-        }
+            daemon::DaemonResponse::Error { message } => {
+                send_notification("Transcription Failed", &message, "critical");
+                anyhow::bail!("Daemon error: {}", message)
+            },
+            other => anyhow::bail!("Unexpected response from daemon: {:?}", other),
+        })
     }
 }
 
+/// Show microphone level warnings reported for a recording
+fn notify_input_warnings(warnings: &[String]) {
+    for warning in warnings {
+        tracing::warn!("{}", warning);
+        send_notification("Microphone Warning", warning, "normal");
+    }
+}
+
+/// Type or copy transcribed text and show a notification
+fn output_transcription(text: &str, clipboard: bool) -> Result<()> {
+    if text.is_empty() {
+        info!("No speech detected");
+        return Ok(());
+    }
+
+    // Output the transcribed text
+    let output_mode = if clipboard {
+        output::OutputMode::Clipboard
+    } else {
+        output::OutputMode::Type
+    };
+
+    info!("Transcribed: {}", text);
+    output::inject_text(text, output_mode)?;
+    info!("Text output via {:?}", output_mode);
+
+    // Send notification
+    let preview = if text.len() > 80 {
+        format!("{}...", text.chars().take(77).collect::<String>())
+    } else {
+        text.to_string()
+    };
+    send_notification("Transcription Complete", &preview, "normal");
+
+    Ok(())
+}
+
 /// Fixed duration recording mode
-fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: bool) -> Result<()> {
+fn cmd_start_fixed(
+    model_override: Option<String>,
+    duration: u32,
+    clipboard: bool,
+    task: Option<Task>,
+) -> Result<()> {
     info!("Loading configuration...");
     let mut cfg = config::load()?;
 
@@ -333,12 +533,38 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
             .ok_or_else(|| anyhow::anyhow!("Invalid model path"))?,
         &cfg.model.language,
         cfg.model.prompt.clone(),
-    )?;
+        cfg.model.offline,
+    )?
+    .with_allowed_languages(&cfg.model.languages)?
+    .with_beam_search(cfg.model.beam_size, cfg.model.length_penalty)?
+    .with_draft_model(cfg.model.draft_model_path.as_deref())
+    .with_task(task.unwrap_or(cfg.model.task))?;
     info!("Model loaded successfully");
 
+    let device = audio::select_input_device(cfg.audio.device.as_deref())?;
+    let source = audio::CpalSource::new(device)?.with_reconnect(cfg.audio.device.clone());
+
     info!("Recording for {} seconds...", duration);
-    let audio_data = audio::capture(duration, cfg.audio.sample_rate)?;
-    info!("Captured {} samples", audio_data.len());
+    let recorder = audio::AudioRecorder::start(
+        Box::new(source),
+        audio::RecorderOptions {
+            max_duration: Some(std::time::Duration::from_secs(duration as u64)),
+            trim: cfg.audio.trim_options(),
+            channel: cfg.audio.channel_index()?,
+            dsp: cfg.audio.dsp_options(),
+            ..Default::default()
+        },
+    )?;
+    let recording = recorder.wait()?;
+    info!("Captured {} samples", recording.samples.len());
+
+    let warnings = recording.warnings();
+    if recording.stats.is_silent() {
+        let message = warnings.join("; ");
+        send_notification("Transcription Failed", &message, "critical");
+        anyhow::bail!("{}", message);
+    }
+    notify_input_warnings(&warnings);
 
     // Create processing state file
     let processing_file = state::get_state_dir()?.join("processing");
@@ -348,7 +574,7 @@ fn cmd_start_fixed(model_override: Option<String>, duration: u32, clipboard: boo
     });
 
     info!("Transcribing...");
-    let text = transcriber.transcribe(&audio_data)?;
+    let text = transcriber.transcribe(&recording.samples)?.text;
 
     if text.is_empty() {
         info!("No speech detected");
@@ -472,10 +698,14 @@ fn cmd_config_check() -> Result<()> {
                 has_warnings = true;
             }
         },
-        None => {
-            println!("⚠ model.draft_model_path = (missing, speculative decoding disabled)");
-            has_warnings = true;
-        },
+        // Off by default: a draft must share the main model's vocabulary
+        None => println!("✓ model.draft_model_path = (not set, speculative decoding off)"),
+    }
+
+    // Check input channel (1-based)
+    if let Err(e) = current.audio.channel_index() {
+        println!("✗ {}", e);
+        has_warnings = true;
     }
 
     // Check prompt
@@ -537,11 +767,6 @@ fn cmd_config_migrate() -> Result<()> {
         println!("✓ Added model.model_id");
     }
 
-    if current.model.draft_model_path.is_none() {
-        current.model.draft_model_path = defaults.model.draft_model_path;
-        println!("✓ Added model.draft_model_path");
-    }
-
     if current.model.prompt.is_none() {
         current.model.prompt = defaults.model.prompt;
         println!("✓ Added model.prompt (technical vocabulary)");
@@ -604,6 +829,43 @@ fn cmd_daemon(model_override: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Record a short sample from the configured input and report its levels
+fn doctor_check_microphone(cfg: &config::Config) {
+    println!(
+        "\nTesting microphone for {}s - speak normally...",
+        DOCTOR_SAMPLE_SECS
+    );
+
+    let recording = audio::select_input_device(cfg.audio.device.as_deref())
+        .and_then(audio::CpalSource::new)
+        .and_then(|source| {
+            audio::AudioRecorder::start(
+                Box::new(source),
+                audio::RecorderOptions {
+                    max_duration: Some(std::time::Duration::from_secs(DOCTOR_SAMPLE_SECS)),
+                    channel: cfg.audio.channel_index()?,
+                    ..Default::default()
+                },
+            )
+        })
+        .and_then(|recorder| recorder.wait());
+
+    match recording {
+        Ok(recording) => {
+            let warnings = recording.stats.warnings();
+            println!(
+                "[{}] Microphone: {}",
+                if warnings.is_empty() { "OK" } else { "WARN" },
+                recording.stats
+            );
+            for warning in warnings {
+                println!("      {}", warning);
+            }
+        },
+        Err(e) => println!("[FAIL] Microphone: {:#}", e),
+    }
+}
+
 fn cmd_doctor() -> Result<()> {
     println!("Checking system dependencies...\n");
 
@@ -629,6 +891,8 @@ fn cmd_doctor() -> Result<()> {
         .unwrap_or(false);
     println!("\n[{}] PipeWire", if pw_ok { "OK" } else { "MISSING" });
 
+    doctor_check_microphone(&cfg);
+
     // Show log location
     if let Ok(log_dir) = state::get_log_dir() {
         println!("\nLogs: {}", log_dir.display());
@@ -4,10 +4,14 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 use tracing::info;
 
+use super::files::{CONFIG_FILE, GENERATION_CONFIG_FILE, TOKENIZER_FILE, companion_path};
 use super::registry::ModelInfo;
 use super::verify::verify_checksum;
 
 /// Download a model with progress reporting and checksum verification
+///
+/// Its config and tokenizer are stored next to the weights, so loading it
+/// later needs no network.
 pub fn download_model(model: &ModelInfo, dest_dir: &Path) -> Result<std::path::PathBuf> {
     let dest_path = dest_dir.join(model.filename);
 
@@ -16,6 +20,7 @@ pub fn download_model(model: &ModelInfo, dest_dir: &Path) -> Result<std::path::P
         info!("Model already exists, verifying checksum...");
         if verify_checksum(&dest_path, model.sha256)? {
             info!("Existing model verified, skipping download");
+            download_companions(model, &dest_path)?;
             return Ok(dest_path);
         }
         info!("Existing model failed checksum, re-downloading...");
@@ -90,9 +95,41 @@ pub fn download_model(model: &ModelInfo, dest_dir: &Path) -> Result<std::path::P
     fs::rename(&temp_path, &dest_path).context("Failed to move downloaded file")?;
 
     info!("Model saved to {}", dest_path.display());
+    download_companions(model, &dest_path)?;
     Ok(dest_path)
 }
 
+/// Fetch the config and tokenizer of `model` next to `weights` (see
+/// [`companion_path`]), skipping those already there
+pub fn download_companions(model: &ModelInfo, weights: &Path) -> Result<()> {
+    for name in [CONFIG_FILE, TOKENIZER_FILE, GENERATION_CONFIG_FILE] {
+        let dest = companion_path(weights, name);
+        if dest.exists() {
+            continue;
+        }
+
+        let url = model.companion_url(name);
+        info!("Downloading {}", url);
+        let response = match ureq::get(&url).call() {
+            Ok(response) => response,
+            // Not every model has a generation config
+            Err(ureq::Error::Status(404, _)) if name == GENERATION_CONFIG_FILE => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to download {}", url)),
+        };
+
+        let temp_path = dest.with_extension("download");
+        let mut file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+        if let Err(e) = std::io::copy(&mut response.into_reader(), &mut file) {
+            drop(file);
+            fs::remove_file(&temp_path)?;
+            return Err(e).with_context(|| format!("Failed to download {}", url));
+        }
+        fs::rename(&temp_path, &dest).context("Failed to move downloaded file")?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
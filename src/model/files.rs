@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+/// Model config, required to load weights
+pub const CONFIG_FILE: &str = "config.json";
+
+/// Tokenizer, required to load weights
+pub const TOKENIZER_FILE: &str = "tokenizer.json";
+
+/// Decoding defaults (alignment heads), optional
+pub const GENERATION_CONFIG_FILE: &str = "generation_config.json";
+
+/// Where `name` is stored for the weights file `weights`
+///
+/// Several models share the models directory, so the file is prefixed with
+/// the weights' name: `ggml-tiny.en.bin` → `ggml-tiny.en.config.json`.
+pub fn companion_path(weights: &Path, name: &str) -> PathBuf {
+    let stem = weights.file_stem().unwrap_or_default().to_string_lossy();
+    weights.with_file_name(format!("{}.{}", stem, name))
+}
+
+/// Existing copy of `name` for `weights`: the prefixed one, or a plain
+/// `name` in the same directory (a directory holding a single model)
+pub fn find_companion(weights: &Path, name: &str) -> Option<PathBuf> {
+    [companion_path(weights, name), weights.with_file_name(name)]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_companion_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let weights = dir.path().join("ggml-tiny.en.bin");
+        assert_eq!(
+            companion_path(&weights, CONFIG_FILE),
+            dir.path().join("ggml-tiny.en.config.json")
+        );
+        assert_eq!(find_companion(&weights, CONFIG_FILE), None);
+
+        // A directory with one model may use the plain name
+        std::fs::write(dir.path().join(CONFIG_FILE), "{}").unwrap();
+        assert_eq!(
+            find_companion(&weights, CONFIG_FILE),
+            Some(dir.path().join(CONFIG_FILE))
+        );
+
+        // The model's own copy wins
+        std::fs::write(companion_path(&weights, CONFIG_FILE), "{}").unwrap();
+        assert_eq!(
+            find_companion(&weights, CONFIG_FILE),
+            Some(companion_path(&weights, CONFIG_FILE))
+        );
+    }
+}
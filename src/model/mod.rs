@@ -1,6 +1,8 @@
 mod download;
+mod files;
 mod registry;
 mod verify;
 
-pub use download::download_model;
+pub use download::{download_companions, download_model};
+pub use files::{CONFIG_FILE, GENERATION_CONFIG_FILE, TOKENIZER_FILE, find_companion};
 pub use registry::ModelInfo;
@@ -1,9 +1,13 @@
+use std::path::Path;
+
 /// Information about a Whisper model
 #[derive(Debug, Clone)]
 pub struct ModelInfo {
     pub name: &'static str,
     pub filename: &'static str,
     pub url: &'static str,
+    /// Hugging Face repo with the config and tokenizer matching the weights
+    pub config_repo: &'static str,
     pub sha256: &'static str,
     pub size_mb: u32,
 }
@@ -14,6 +18,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
         name: "large-v3-turbo",
         filename: "ggml-large-v3-turbo.bin",
         url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin",
+        config_repo: "openai/whisper-large-v3-turbo",
         sha256: "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69",
         size_mb: 1625,
     },
@@ -21,6 +26,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
         name: "distil-large-v3",
         filename: "ggml-distil-large-v3.bin",
         url: "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/main/ggml-distil-large-v3.bin",
+        config_repo: "distil-whisper/distil-large-v3",
         sha256: "2883a11b90fb10ed592d826edeaee7d2929bf1ab985109fe9e1e7b4d2b69a298",
         size_mb: 1520,
     },
@@ -31,6 +37,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-tiny.en.bin"
         ),
+        config_repo: "openai/whisper-tiny.en",
         sha256: "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
         size_mb: 78,
     },
@@ -41,6 +48,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-base.en.bin"
         ),
+        config_repo: "openai/whisper-base.en",
         sha256: "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
         size_mb: 148,
     },
@@ -51,6 +59,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-small.en.bin"
         ),
+        config_repo: "openai/whisper-small.en",
         sha256: "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
         size_mb: 488,
     },
@@ -61,6 +70,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-medium.en.bin"
         ),
+        config_repo: "openai/whisper-medium.en",
         sha256: "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356",
         size_mb: 1530,
     },
@@ -71,6 +81,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-large-v3.bin"
         ),
+        config_repo: "openai/whisper-large-v3",
         sha256: "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
         size_mb: 3100,
     },
@@ -82,6 +93,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-tiny.bin"
         ),
+        config_repo: "openai/whisper-tiny",
         sha256: "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21",
         size_mb: 78,
     },
@@ -92,6 +104,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-base.bin"
         ),
+        config_repo: "openai/whisper-base",
         sha256: "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe",
         size_mb: 148,
     },
@@ -102,6 +115,7 @@ pub const MODEL_REGISTRY: &[ModelInfo] = &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/",
             "ggml-small.bin"
         ),
+        config_repo: "openai/whisper-small",
         sha256: "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1571299571",
         size_mb: 488,
     },
@@ -120,6 +134,19 @@ impl ModelInfo {
         MODEL_REGISTRY.iter().find(|m| m.name == normalized)
     }
 
+    /// Model whose weights are stored as `weights` (by file name)
+    pub fn for_weights(weights: &Path) -> Option<&'static ModelInfo> {
+        Self::find(&weights.file_name()?.to_string_lossy())
+    }
+
+    /// Download URL of a file from the model's config repo
+    pub fn companion_url(&self, name: &str) -> String {
+        format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            self.config_repo, name
+        )
+    }
+
     /// List all available model names
     pub fn available_models() -> Vec<&'static str> {
         MODEL_REGISTRY.iter().map(|m| m.name).collect()
@@ -143,4 +170,15 @@ mod tests {
         assert!(info.is_some());
         assert_eq!(info.unwrap().name, "base.en");
     }
+
+    #[test]
+    fn test_config_repo_matches_weights() {
+        let info = ModelInfo::for_weights(Path::new("/models/ggml-tiny.en.bin")).unwrap();
+        assert_eq!(info.config_repo, "openai/whisper-tiny.en");
+        assert_eq!(
+            info.companion_url("config.json"),
+            "https://huggingface.co/openai/whisper-tiny.en/resolve/main/config.json"
+        );
+        assert!(ModelInfo::for_weights(Path::new("/models/custom.gguf")).is_none());
+    }
 }
@@ -28,6 +28,16 @@ pub fn get_pid_file() -> Result<PathBuf> {
     Ok(get_state_dir()?.join("recording.pid"))
 }
 
+/// Get the live input level file path (written by the daemon while recording)
+pub fn get_level_file() -> Result<PathBuf> {
+    Ok(get_state_dir()?.join("level"))
+}
+
+/// Get the live partial transcript file path (written by the daemon while recording)
+pub fn get_partial_file() -> Result<PathBuf> {
+    Ok(get_state_dir()?.join("partial"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,15 +1,11 @@
 use anyhow::{Context, Result};
-use nix::sys::signal::{self, Signal};
+use nix::sys::signal;
 use nix::unistd::Pid;
 use std::fs;
 use std::io::Write;
-use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::info;
 
-use super::paths::get_pid_file;
-
-/// Global flag to signal recording should stop
-pub static STOP_RECORDING: AtomicBool = AtomicBool::new(false);
+use super::paths::{get_level_file, get_partial_file, get_pid_file};
 
 /// Recording state information
 #[derive(Debug)]
@@ -127,22 +123,54 @@ pub fn cleanup_processing() -> Result<()> {
     Ok(())
 }
 
-/// Stop a running recording by sending SIGUSR1
-#[allow(dead_code)]
-pub fn stop_recording(state: &RecordingState) -> Result<()> {
-    info!(
-        "Sending stop signal to recording process (PID: {})",
-        state.pid
-    );
+/// Publish the live input level for status bars
+///
+/// Single line: `<percent 0-100> <rms dBFS> <peak dBFS>`. Written via rename so
+/// readers never see a partial line.
+pub fn write_level(percent: u8, rms_db: f32, peak_db: f32) -> Result<()> {
+    let level_file = get_level_file()?;
+    let tmp_file = level_file.with_extension("tmp");
+    fs::write(
+        &tmp_file,
+        format!("{} {:.1} {:.1}\n", percent, rms_db, peak_db),
+    )?;
+    fs::rename(&tmp_file, &level_file)?;
+    Ok(())
+}
 
-    signal::kill(Pid::from_raw(state.pid as i32), Signal::SIGUSR1)
-        .context("Failed to send stop signal to recording process")?;
+/// Remove the live input level file
+pub fn cleanup_level() -> Result<()> {
+    let level_file = get_level_file()?;
+    if level_file.exists() {
+        fs::remove_file(&level_file)?;
+    }
+    Ok(())
+}
+
+/// Publish the live partial transcript for status bars
+///
+/// Single line (newlines replaced by spaces), written via rename like the level file.
+pub fn write_partial(text: &str) -> Result<()> {
+    let partial_file = get_partial_file()?;
+    let tmp_file = partial_file.with_extension("tmp");
+    fs::write(&tmp_file, format!("{}\n", text.replace('\n', " ")))?;
+    fs::rename(&tmp_file, &partial_file)?;
+    Ok(())
+}
 
+/// Remove the live partial transcript file
+pub fn cleanup_partial() -> Result<()> {
+    let partial_file = get_partial_file()?;
+    if partial_file.exists() {
+        fs::remove_file(&partial_file)?;
+    }
     Ok(())
 }
 
 /// Clean up PID file (called when recording ends)
 pub fn cleanup_recording() -> Result<()> {
+    let _ = cleanup_level();
+    let _ = cleanup_partial();
     let pid_file = get_pid_file()?;
     if pid_file.exists() {
         fs::remove_file(&pid_file)?;
@@ -153,29 +181,6 @@ pub fn cleanup_recording() -> Result<()> {
     Ok(())
 }
 
-/// Set up signal handler for SIGUSR1
-pub fn setup_signal_handler() -> Result<()> {
-    // Register handler for SIGUSR1
-    unsafe {
-        signal::signal(
-            Signal::SIGUSR1,
-            signal::SigHandler::Handler(handle_stop_signal),
-        )
-        .context("Failed to set up signal handler")?;
-    }
-    Ok(())
-}
-
-/// Signal handler function
-extern "C" fn handle_stop_signal(_: i32) {
-    STOP_RECORDING.store(true, Ordering::SeqCst);
-}
-
-/// Check if stop was requested
-pub fn should_stop() -> bool {
-    STOP_RECORDING.load(Ordering::SeqCst)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,319 @@
+//! Cross-attention weights of the decoder's alignment heads
+//!
+//! candle's Whisper decoder does not expose its attention weights, so word
+//! alignment runs a separate teacher-forced pass through a copy of the decoder
+//! layers, loaded from the same weights file. Only the layers up to the last
+//! alignment head are loaded.
+
+use anyhow::Result;
+use candle_core::{Device, IndexOp, Tensor};
+use candle_nn::{LayerNorm, Module};
+use candle_transformers::models::whisper::{self, Config};
+use candle_transformers::quantized_nn;
+use candle_transformers::quantized_var_builder::VarBuilder as QuantizedVarBuilder;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Weights file a model was loaded from
+#[derive(Debug, Clone)]
+pub enum WeightsFile {
+    Safetensors(PathBuf),
+    Gguf(PathBuf),
+}
+
+/// `(layer, head)` pairs whose cross-attention follows the audio
+///
+/// Read from `alignment_heads` in the model's `generation_config.json`.
+pub fn read_alignment_heads(generation_config: &Path) -> Result<Vec<(usize, usize)>> {
+    #[derive(serde::Deserialize)]
+    struct GenerationConfig {
+        alignment_heads: Vec<(usize, usize)>,
+    }
+
+    let config: GenerationConfig =
+        serde_json::from_str(&std::fs::read_to_string(generation_config)?)?;
+    Ok(config.alignment_heads)
+}
+
+type Layer = Box<dyn Module + Send + Sync>;
+
+/// The parts of a var builder the decoder layers need, for both weight formats
+trait Weights: Sized {
+    fn push(&self, name: &str) -> Self;
+    fn linear(&self, in_dim: usize, out_dim: usize, bias: bool) -> Result<Layer>;
+    fn layer_norm(&self, size: usize) -> Result<LayerNorm>;
+    fn matrix(&self, rows: usize, cols: usize, name: &str) -> Result<Tensor>;
+}
+
+impl Weights for candle_nn::VarBuilder<'_> {
+    fn push(&self, name: &str) -> Self {
+        self.pp(name)
+    }
+
+    fn linear(&self, in_dim: usize, out_dim: usize, bias: bool) -> Result<Layer> {
+        Ok(if bias {
+            Box::new(candle_nn::linear(in_dim, out_dim, self.clone())?)
+        } else {
+            Box::new(candle_nn::linear_no_bias(in_dim, out_dim, self.clone())?)
+        })
+    }
+
+    fn layer_norm(&self, size: usize) -> Result<LayerNorm> {
+        Ok(candle_nn::layer_norm(size, 1e-5, self.clone())?)
+    }
+
+    fn matrix(&self, rows: usize, cols: usize, name: &str) -> Result<Tensor> {
+        Ok(self.get((rows, cols), name)?)
+    }
+}
+
+impl Weights for QuantizedVarBuilder {
+    fn push(&self, name: &str) -> Self {
+        self.pp(name)
+    }
+
+    fn linear(&self, in_dim: usize, out_dim: usize, bias: bool) -> Result<Layer> {
+        Ok(if bias {
+            Box::new(quantized_nn::linear(in_dim, out_dim, self.clone())?)
+        } else {
+            Box::new(quantized_nn::linear_no_bias(in_dim, out_dim, self.clone())?)
+        })
+    }
+
+    fn layer_norm(&self, size: usize) -> Result<LayerNorm> {
+        Ok(quantized_nn::layer_norm(size, 1e-5, self.clone())?)
+    }
+
+    fn matrix(&self, rows: usize, cols: usize, name: &str) -> Result<Tensor> {
+        Ok(self.get((rows, cols), name)?.dequantize(self.device())?)
+    }
+}
+
+struct Attention {
+    query: Layer,
+    key: Layer,
+    value: Layer,
+    out: Layer,
+    n_head: usize,
+}
+
+impl Attention {
+    fn load(weights: &impl Weights, n_state: usize, n_head: usize) -> Result<Self> {
+        Ok(Self {
+            query: weights.push("q_proj").linear(n_state, n_state, true)?,
+            key: weights.push("k_proj").linear(n_state, n_state, false)?,
+            value: weights.push("v_proj").linear(n_state, n_state, true)?,
+            out: weights.push("out_proj").linear(n_state, n_state, true)?,
+            n_head,
+        })
+    }
+
+    fn heads(&self, x: &Tensor) -> Result<Tensor> {
+        let (batch, ctx, state) = x.dims3()?;
+        Ok(x.reshape((batch, ctx, self.n_head, state / self.n_head))?
+            .transpose(1, 2)?)
+    }
+
+    /// Attention output plus the scores before softmax `[batch, head, query, key]`
+    fn forward(
+        &self,
+        x: &Tensor,
+        xa: Option<&Tensor>,
+        mask: Option<&Tensor>,
+    ) -> Result<(Tensor, Tensor)> {
+        let source = xa.unwrap_or(x);
+        let q = self.query.forward(x)?;
+        let k = self.key.forward(source)?;
+        let v = self.value.forward(source)?;
+
+        let state = q.dim(2)?;
+        let scale = ((state / self.n_head) as f64).powf(-0.25);
+        let q = (self.heads(&q)? * scale)?;
+        let k = (self.heads(&k)?.transpose(2, 3)? * scale)?;
+        let v = self.heads(&v)?.contiguous()?;
+
+        let mut qk = q.matmul(&k)?;
+        if let Some(mask) = mask {
+            qk = qk.broadcast_add(mask)?;
+        }
+        let weights = candle_nn::ops::softmax_last_dim(&qk)?;
+        let wv = weights.matmul(&v)?.transpose(1, 2)?.flatten_from(2)?;
+        Ok((self.out.forward(&wv)?, qk))
+    }
+}
+
+struct Block {
+    attn: Attention,
+    attn_ln: LayerNorm,
+    cross_attn: Attention,
+    cross_attn_ln: LayerNorm,
+    mlp_linear1: Layer,
+    mlp_linear2: Layer,
+    mlp_ln: LayerNorm,
+}
+
+impl Block {
+    fn load(weights: &impl Weights, n_state: usize, n_head: usize) -> Result<Self> {
+        Ok(Self {
+            attn: Attention::load(&weights.push("self_attn"), n_state, n_head)?,
+            attn_ln: weights.push("self_attn_layer_norm").layer_norm(n_state)?,
+            cross_attn: Attention::load(&weights.push("encoder_attn"), n_state, n_head)?,
+            cross_attn_ln: weights
+                .push("encoder_attn_layer_norm")
+                .layer_norm(n_state)?,
+            mlp_linear1: weights.push("fc1").linear(n_state, n_state * 4, true)?,
+            mlp_linear2: weights.push("fc2").linear(n_state * 4, n_state, true)?,
+            mlp_ln: weights.push("final_layer_norm").layer_norm(n_state)?,
+        })
+    }
+
+    /// Block output plus its cross-attention scores
+    fn forward(&self, x: &Tensor, xa: &Tensor, mask: &Tensor) -> Result<(Tensor, Tensor)> {
+        let (attn, _) = self
+            .attn
+            .forward(&self.attn_ln.forward(x)?, None, Some(mask))?;
+        let x = (x + attn)?;
+        let (cross, scores) =
+            self.cross_attn
+                .forward(&self.cross_attn_ln.forward(&x)?, Some(xa), None)?;
+        let x = (x + cross)?;
+        let mlp = self.mlp_linear2.forward(
+            &self
+                .mlp_linear1
+                .forward(&self.mlp_ln.forward(&x)?)?
+                .gelu()?,
+        )?;
+        Ok(((x + mlp)?, scores))
+    }
+}
+
+/// Decoder layers that report where the alignment heads attend
+pub struct AlignmentDecoder {
+    token_embedding: Tensor,
+    positional_embedding: Tensor,
+    blocks: Vec<Block>,
+    heads: Vec<(usize, usize)>,
+    device: Device,
+}
+
+impl AlignmentDecoder {
+    /// Load the decoder layers from `file`
+    ///
+    /// Without known `heads`, all heads of the upper half of the decoder are
+    /// used, as in the reference implementation.
+    pub fn load(
+        file: &WeightsFile,
+        config: &Config,
+        heads: Option<Vec<(usize, usize)>>,
+        device: &Device,
+    ) -> Result<Self> {
+        let layers = config.decoder_layers;
+        let heads = heads.unwrap_or_else(|| {
+            (layers / 2..layers)
+                .flat_map(|layer| (0..config.decoder_attention_heads).map(move |h| (layer, h)))
+                .collect()
+        });
+        let Some(last_layer) = heads.iter().map(|&(layer, _)| layer).max() else {
+            anyhow::bail!("No alignment heads");
+        };
+        if last_layer >= layers
+            || heads
+                .iter()
+                .any(|&(_, h)| h >= config.decoder_attention_heads)
+        {
+            anyhow::bail!("Alignment heads {:?} don't fit the model", heads);
+        }
+
+        info!(
+            "Loading {} decoder layers for word alignment ({} heads)",
+            last_layer + 1,
+            heads.len()
+        );
+        let (token_embedding, positional_embedding, blocks) = match file {
+            WeightsFile::Safetensors(path) => {
+                let vb = unsafe {
+                    candle_nn::VarBuilder::from_mmaped_safetensors(
+                        std::slice::from_ref(path),
+                        whisper::DTYPE,
+                        device,
+                    )?
+                };
+                Self::load_layers(&vb.push("model.decoder"), config, last_layer)?
+            },
+            WeightsFile::Gguf(path) => {
+                let vb = QuantizedVarBuilder::from_gguf(path, device)?;
+                Self::load_layers(&vb.push("model.decoder"), config, last_layer)?
+            },
+        };
+
+        Ok(Self {
+            token_embedding,
+            positional_embedding,
+            blocks,
+            heads,
+            device: device.clone(),
+        })
+    }
+
+    fn load_layers(
+        weights: &impl Weights,
+        config: &Config,
+        last_layer: usize,
+    ) -> Result<(Tensor, Tensor, Vec<Block>)> {
+        let n_state = config.d_model;
+        let token_embedding = weights.matrix(config.vocab_size, n_state, "embed_tokens.weight")?;
+        let positional_embedding = weights.matrix(
+            config.max_target_positions,
+            n_state,
+            "embed_positions.weight",
+        )?;
+        let blocks = (0..=last_layer)
+            .map(|i| {
+                Block::load(
+                    &weights.push(&format!("layers.{}", i)),
+                    n_state,
+                    config.decoder_attention_heads,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((token_embedding, positional_embedding, blocks))
+    }
+
+    /// Cross-attention of each alignment head for the full token sequence
+    ///
+    /// Returns one row-major `tokens × frames` matrix per head, softmaxed over
+    /// the first `frames` encoder frames (the rest is padding).
+    pub fn attention(
+        &self,
+        audio_features: &Tensor,
+        tokens: &[u32],
+        frames: usize,
+    ) -> Result<Vec<Vec<f32>>> {
+        let len = tokens.len();
+        let input = Tensor::new(tokens, &self.device)?.unsqueeze(0)?;
+        let mut x = self
+            .token_embedding
+            .index_select(&input.flatten_all()?, 0)?
+            .unsqueeze(0)?
+            .broadcast_add(&self.positional_embedding.narrow(0, 0, len)?)?;
+
+        let mask: Vec<f32> = (0..len)
+            .flat_map(|i| (0..len).map(move |j| if j > i { f32::NEG_INFINITY } else { 0.0 }))
+            .collect();
+        let mask = Tensor::from_vec(mask, (len, len), &self.device)?;
+
+        let frames = frames.min(audio_features.dim(1)?);
+
+        let mut heads = Vec::with_capacity(self.heads.len());
+        for (layer, block) in self.blocks.iter().enumerate() {
+            let (next, scores) = block.forward(&x, audio_features, &mask)?;
+            for &(_, head) in self.heads.iter().filter(|(l, _)| *l == layer) {
+                let weights =
+                    candle_nn::ops::softmax_last_dim(&scores.i((0, head))?.narrow(1, 0, frames)?)?;
+                heads.push(weights.flatten_all()?.to_vec1::<f32>()?);
+            }
+            x = next;
+        }
+        Ok(heads)
+    }
+}
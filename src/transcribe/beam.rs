@@ -0,0 +1,248 @@
+//! Beam search over decoder hypotheses
+//!
+//! Follows the reference implementation: at every step each live hypothesis
+//! proposes its `beam_size + 1` most likely next tokens, the best `beam_size`
+//! candidates that don't end the text live on, and those ending in
+//! `<|endoftext|>` are set aside as finished. Once `beam_size` hypotheses have
+//! finished, the one with the best length-penalized score wins.
+
+/// A token may not repeat more than this many times in a row (as in greedy decoding)
+const MAX_REPEATS: usize = 3;
+
+/// One candidate token sequence
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hypothesis {
+    /// Sampled tokens, without the end of text
+    pub tokens: Vec<u32>,
+    /// Probability of each token when it was picked
+    pub probs: Vec<f32>,
+    /// Log probability of the whole sequence, end of text included
+    pub sum_logprob: f64,
+    /// Whether the sequence ended with `<|endoftext|>`
+    pub finished: bool,
+}
+
+impl Hypothesis {
+    /// Mean log probability per sampled token (the fallback threshold metric)
+    pub fn avg_logprob(&self) -> f64 {
+        let steps = self.tokens.len() + self.finished as usize;
+        if steps == 0 {
+            0.0
+        } else {
+            self.sum_logprob / steps as f64
+        }
+    }
+
+    /// Ranking score: log probability divided by a length penalty
+    ///
+    /// Without `length_penalty` the penalty is the length itself, otherwise
+    /// the Google NMT penalty `((5 + length) / 6) ^ length_penalty`.
+    pub fn score(&self, length_penalty: Option<f32>) -> f64 {
+        let length = self.tokens.len().max(1) as f64;
+        let penalty = match length_penalty {
+            Some(alpha) => ((5.0 + length) / 6.0).powf(alpha as f64),
+            None => length,
+        };
+        self.sum_logprob / penalty
+    }
+
+    fn repeats(&self, token: u32) -> bool {
+        self.tokens.len() >= MAX_REPEATS
+            && self.tokens[self.tokens.len() - MAX_REPEATS..]
+                .iter()
+                .all(|&t| t == token)
+    }
+}
+
+/// State of a beam search over one window
+pub struct BeamSearch {
+    beam_size: usize,
+    eot: u32,
+    length_penalty: Option<f32>,
+    live: Vec<Hypothesis>,
+    finished: Vec<Hypothesis>,
+}
+
+impl BeamSearch {
+    pub fn new(beam_size: usize, eot: u32, length_penalty: Option<f32>) -> Self {
+        Self {
+            beam_size: beam_size.max(1),
+            eot,
+            length_penalty,
+            live: vec![Hypothesis::default()],
+            finished: Vec::new(),
+        }
+    }
+
+    /// Hypotheses to extend in the next step (all of the same length)
+    pub fn live(&self) -> &[Hypothesis] {
+        &self.live
+    }
+
+    /// Whether enough hypotheses have finished (or none is left to extend)
+    pub fn is_done(&self) -> bool {
+        self.finished.len() >= self.beam_size || self.live.is_empty()
+    }
+
+    /// Extend the live hypotheses; `logprobs[i]` are the next-token log
+    /// probabilities of `live()[i]` over the whole vocabulary
+    pub fn step(&mut self, logprobs: &[Vec<f32>]) {
+        let mut candidates: Vec<(f64, usize, u32, f32)> = Vec::new();
+        for (index, (hypothesis, logprobs)) in self.live.iter().zip(logprobs).enumerate() {
+            for token in top_tokens(logprobs, self.beam_size + 1) {
+                let logprob = logprobs[token as usize];
+                if !logprob.is_finite() || hypothesis.repeats(token) {
+                    continue;
+                }
+                candidates.push((
+                    hypothesis.sum_logprob + logprob as f64,
+                    index,
+                    token,
+                    logprob,
+                ));
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut next = Vec::with_capacity(self.beam_size);
+        for (sum_logprob, index, token, logprob) in candidates {
+            if next.len() >= self.beam_size {
+                break;
+            }
+
+            let mut hypothesis = self.live[index].clone();
+            hypothesis.sum_logprob = sum_logprob;
+            if token == self.eot {
+                if self.finished.len() < self.beam_size {
+                    hypothesis.finished = true;
+                    self.finished.push(hypothesis);
+                }
+            } else {
+                hypothesis.tokens.push(token);
+                hypothesis.probs.push(logprob.exp());
+                next.push(hypothesis);
+            }
+        }
+        self.live = next;
+    }
+
+    /// Best hypothesis, preferring finished ones
+    pub fn best(self) -> Hypothesis {
+        let length_penalty = self.length_penalty;
+        let pool = if self.finished.is_empty() {
+            self.live
+        } else {
+            self.finished
+        };
+        pool.into_iter()
+            .max_by(|a, b| a.score(length_penalty).total_cmp(&b.score(length_penalty)))
+            .unwrap_or_default()
+    }
+}
+
+/// Ids of the `count` largest values, best first
+fn top_tokens(logprobs: &[f32], count: usize) -> Vec<u32> {
+    let mut ids: Vec<u32> = (0..logprobs.len() as u32).collect();
+    let count = count.min(ids.len());
+    if count == 0 {
+        return Vec::new();
+    }
+    let by_logprob = |a: &u32, b: &u32| logprobs[*b as usize].total_cmp(&logprobs[*a as usize]);
+    ids.select_nth_unstable_by(count - 1, by_logprob);
+    ids.truncate(count);
+    ids.sort_by(by_logprob);
+    ids
+}
+
+/// Log-softmax of one row of logits
+pub fn log_softmax(logits: &[f32]) -> Vec<f32> {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if !max.is_finite() {
+        return vec![f32::NEG_INFINITY; logits.len()];
+    }
+    let log_total = logits.iter().map(|&l| (l - max).exp()).sum::<f32>().ln();
+    logits.iter().map(|&l| l - max - log_total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tiny vocabulary: 0-3 text, 4 end of text
+    const EOT: u32 = 4;
+
+    fn row(probs: [f32; 5]) -> Vec<f32> {
+        probs.iter().map(|p| p.ln()).collect()
+    }
+
+    #[test]
+    fn test_beam_finds_sequence_greedy_misses() {
+        // Greedy takes token 0 (0.5), whose best continuation is weak;
+        // token 1 (0.4) leads to a near-certain end
+        let mut search = BeamSearch::new(2, EOT, None);
+        search.step(&[row([0.5, 0.4, 0.05, 0.05, 0.0])]);
+        assert_eq!(search.live().len(), 2);
+
+        let after = |token: u32| match token {
+            0 => row([0.3, 0.3, 0.2, 0.1, 0.1]),
+            _ => row([0.01, 0.01, 0.01, 0.01, 0.96]),
+        };
+        let rows: Vec<Vec<f32>> = search.live().iter().map(|h| after(h.tokens[0])).collect();
+        search.step(&rows);
+
+        let best = search.best();
+        assert!(best.finished);
+        assert_eq!(best.tokens, vec![1]);
+        assert!((best.probs[0] - 0.4).abs() < 1e-6);
+        assert!((best.avg_logprob() - (0.4f64.ln() + 0.96f64.ln()) / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_beam_done_once_enough_finished() {
+        let mut search = BeamSearch::new(2, EOT, None);
+        search.step(&[row([0.05, 0.05, 0.05, 0.05, 0.8])]);
+        assert!(!search.is_done());
+
+        let rows = vec![row([0.01, 0.01, 0.01, 0.01, 0.96]); search.live().len()];
+        search.step(&rows);
+        assert!(search.is_done());
+    }
+
+    #[test]
+    fn test_length_penalty_favours_longer_text() {
+        let short = Hypothesis {
+            tokens: vec![1],
+            sum_logprob: -1.0,
+            ..Default::default()
+        };
+        let long = Hypothesis {
+            tokens: vec![1, 2, 3, 4],
+            sum_logprob: -2.0,
+            ..Default::default()
+        };
+
+        // Per-token average: -1.0 vs -0.5
+        assert!(long.score(None) > short.score(None));
+        // Without normalization (alpha = 0) the raw log probability decides
+        assert!(short.score(Some(0.0)) > long.score(Some(0.0)));
+    }
+
+    #[test]
+    fn test_repeated_tokens_are_cut_off() {
+        let mut search = BeamSearch::new(1, EOT, None);
+        for _ in 0..3 {
+            search.step(&[row([0.9, 0.05, 0.02, 0.02, 0.01])]);
+        }
+        assert_eq!(search.live()[0].tokens, vec![0, 0, 0]);
+
+        search.step(&[row([0.9, 0.05, 0.02, 0.02, 0.01])]);
+        assert_eq!(search.live()[0].tokens, vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_log_softmax() {
+        let logprobs = log_softmax(&[1.0, 1.0, f32::NEG_INFINITY]);
+        assert!((logprobs[0] - 0.5f32.ln()).abs() < 1e-6);
+        assert_eq!(logprobs[2], f32::NEG_INFINITY);
+    }
+}
@@ -2,22 +2,42 @@ use anyhow::Result;
 use candle_core::{Device, IndexOp, Tensor};
 use candle_nn::VarBuilder;
 use candle_transformers::models::whisper::{self, Config};
-use hf_hub::{Repo, api::sync::Api};
-use std::path::Path;
+use hf_hub::{Cache, api::sync::Api};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokenizers::Tokenizer;
-use tracing::{debug, error, info, warn};
-
-use crate::transcribe::Transcriber;
+use tracing::{debug, info, warn};
+
+use crate::model::{
+    CONFIG_FILE, GENERATION_CONFIG_FILE, ModelInfo, TOKENIZER_FILE, download_companions,
+    find_companion,
+};
+use crate::transcribe::alignment::{AlignmentDecoder, WeightsFile, read_alignment_heads};
+use crate::transcribe::beam::{BeamSearch, Hypothesis, log_softmax};
+use crate::transcribe::language::{AUTO_LANGUAGE, DetectedLanguage, LANGUAGES, pick_language};
+use crate::transcribe::longform::{
+    CHUNK_LENGTH_SECS, CHUNK_OVERLAP_SECS, CHUNK_SAMPLES, SAMPLE_RATE, WindowCache, stitch_texts,
+    window_ranges,
+};
+use crate::transcribe::segments::stitch_windows;
+use crate::transcribe::speculative::{self, GreedyOutput};
+use crate::transcribe::timestamps::{TimestampRules, parse_segments};
+use crate::transcribe::words::{
+    FRAMES_PER_SECOND, alignment_matrix, split_words, uses_spaces, word_times,
+};
+use crate::transcribe::{Segment, Task, Transcriber, Transcription, Word};
 
 // Temperature fallback constants (from official Candle Whisper example)
 const TEMPERATURES: [f64; 6] = [0.0, 0.2, 0.4, 0.6, 0.8, 1.0];
 const COMPRESSION_RATIO_THRESHOLD: f64 = 2.4;
 const LOGPROB_THRESHOLD: f64 = -1.0;
 
-// Audio chunking constants for long-form transcription
-const CHUNK_LENGTH_SECS: f32 = 30.0; // Maximum 30 seconds per chunk (Whisper limit)
-const CHUNK_OVERLAP_SECS: f32 = 5.0; // 5-second overlap between chunks
-const SAMPLE_RATE: usize = 16000; // Whisper requires 16kHz audio
+/// Vocabulary size from which a model is multilingual (English-only: 51864)
+const MULTILINGUAL_VOCAB_SIZE: usize = 51865;
+
+/// Config and tokenizer for local weights the model registry doesn't know
+const FALLBACK_CONFIG_MODEL: &str = "large-v3-turbo";
 
 /// Model wrapper supporting both normal (safetensors) and quantized (GGUF) models
 enum Model {
@@ -56,16 +76,193 @@ impl Model {
     }
 }
 
+/// Small Whisper model proposing tokens for the main model to verify
+struct Draft {
+    model: Model,
+    config: Config,
+    tokenizer: Tokenizer,
+    mel_filters: Vec<f32>,
+    /// Blank and all special tokens but the end of text, so only text is proposed
+    suppress_tokens: Tensor,
+}
+
+impl Draft {
+    /// Load a draft that shares `main`'s text vocabulary
+    fn load(path: &Path, main: &Tokenizer, device: &Device, offline: bool) -> Result<Self> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid draft model path"))?;
+        let (config, tokenizer, model, _, _) = CandleEngine::load_model(path, device, offline)?;
+
+        // Text tokens are shared as-is, special tokens are mapped by name
+        let eot = |tokenizer: &Tokenizer| tokenizer.token_to_id("<|endoftext|>");
+        let Some(eot_token) = eot(&tokenizer).filter(|&token| Some(token) == eot(main)) else {
+            anyhow::bail!(
+                "vocabulary differs from the main model (English-only .en drafts only fit .en models)"
+            );
+        };
+
+        let mut mask = vec![0f32; tokenizer.get_vocab_size(true)];
+        mask[220] = f32::NEG_INFINITY;
+        for value in &mut mask[eot_token as usize + 1..] {
+            *value = f32::NEG_INFINITY;
+        }
+
+        Ok(Self {
+            mel_filters: CandleEngine::load_mel_filters(config.num_mel_bins)?,
+            suppress_tokens: Tensor::new(&mask[..], device)?,
+            model,
+            config,
+            tokenizer,
+        })
+    }
+
+    /// Encoder output for a padded 30s window, reusing `mel` if the mel bins match
+    fn encode(&mut self, audio: &[f32], mel: &Tensor) -> Result<Tensor> {
+        let n_mels = self.config.num_mel_bins;
+        let mel = if mel.dim(1)? == n_mels {
+            mel.clone()
+        } else {
+            let data = whisper::audio::pcm_to_mel(&self.config, audio, &self.mel_filters);
+            let frames = data.len() / n_mels;
+            Tensor::from_vec(data, (n_mels, frames), mel.device())?
+                .narrow(1, 0, frames.min(self.config.max_source_positions * 2))?
+                .unsqueeze(0)?
+        };
+        self.model.encoder_forward(&mel, true)
+    }
+
+    /// The main model's initial tokens in draft token ids
+    ///
+    /// None if the draft lacks one of the special tokens (e.g. a newer language).
+    fn map_prefix(&self, main: &Tokenizer, prefix: &[u32], eot_token: u32) -> Option<Vec<u32>> {
+        prefix
+            .iter()
+            .map(|&token| {
+                if token < eot_token {
+                    Some(token)
+                } else {
+                    self.tokenizer.token_to_id(&main.id_to_token(token)?)
+                }
+            })
+            .collect()
+    }
+
+    /// Greedy next token after `tokens`
+    fn next_token(&mut self, tokens: &[u32], audio_features: &Tensor, flush: bool) -> Result<u32> {
+        let input = Tensor::new(tokens, audio_features.device())?.unsqueeze(0)?;
+        let hidden = self.model.decoder_forward(&input, audio_features, flush)?;
+        let logits = self
+            .model
+            .decoder_final_linear(&hidden.narrow(1, tokens.len() - 1, 1)?)?
+            .i((0, 0))?
+            .broadcast_add(&self.suppress_tokens)?;
+        Ok(logits.argmax(0)?.to_scalar::<u32>()?)
+    }
+}
+
+/// Decoding work counted across windows
+#[derive(Debug, Clone, Default)]
+pub struct DecodeStats {
+    /// Windows decoded
+    pub windows: usize,
+    /// Encoder forward passes (one per window)
+    pub encoder_passes: usize,
+    /// Decode attempts, temperature fallbacks included
+    pub attempts: usize,
+    /// Time spent in the encoder
+    pub encoder_time: Duration,
+}
+
+impl DecodeStats {
+    /// Mean time of one encoder pass
+    pub fn encoder_pass_time(&self) -> Duration {
+        if self.encoder_passes == 0 {
+            Duration::ZERO
+        } else {
+            self.encoder_time / self.encoder_passes as u32
+        }
+    }
+}
+
+/// Switches for comparing decoding paths in benches/ (all off in normal use)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BenchOptions {
+    /// Fall back through every temperature, whatever the output quality
+    pub force_fallback: bool,
+    /// Encode the window again for every fallback attempt (the old path)
+    pub encode_per_attempt: bool,
+}
+
+impl std::fmt::Display for DecodeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} windows, {} decode attempts, {} encoder passes ({:.1?} each)",
+            self.windows,
+            self.attempts,
+            self.encoder_passes,
+            self.encoder_pass_time()
+        )
+    }
+}
+
 /// Candle-based Whisper transcription engine
 pub struct CandleEngine {
     device: Device,
     model: Model,
     tokenizer: Tokenizer,
     config: Config,
+    /// Language code, or "auto" to detect it per window
     language: String,
+    /// Languages detection may pick, with their token ids
+    language_candidates: Vec<(String, u32)>,
+    /// Language of the first window decoded by the last call (detection only)
+    detected: Option<DetectedLanguage>,
+    /// Transcribe, or translate to English
+    task: Task,
+    /// Hypotheses kept by beam search at temperature 0 (1 = greedy)
+    beam_size: usize,
+    /// Length penalty of beam search ranking (None = divide by length)
+    length_penalty: Option<f32>,
+    /// Proposes tokens for greedy decoding, if a usable draft model is configured
+    draft: Option<Draft>,
+    /// Never download model files (the draft model included)
+    offline: bool,
+    /// Work done by the last transcription
+    decode_stats: DecodeStats,
+    /// Only set with the `bench` feature
+    bench: BenchOptions,
     initial_prompt: Option<String>,
     mel_filters: Vec<f32>,
     suppress_tokens: Tensor,
+    /// Suppress mask for timestamp mode (keeps timestamp tokens)
+    timestamp_suppress_tokens: Tensor,
+    /// Where the weights came from, for loading the alignment decoder
+    weights: WeightsFile,
+    /// From `generation_config.json`, if the model ships one
+    alignment_heads: Option<Vec<(usize, usize)>>,
+    /// Loaded on the first request for word timestamps
+    aligner: Option<AlignmentDecoder>,
+}
+
+/// Config, tokenizer, model, weights file and `generation_config.json` path
+type LoadedModel = (Config, Tokenizer, Model, WeightsFile, Option<PathBuf>);
+
+/// Sampled tokens of one window plus the quality metrics used for fallback
+struct Decoded {
+    /// Tokens after the initial sequence, timestamps included
+    tokens: Vec<u32>,
+    /// Probability of each token when it was sampled
+    probs: Vec<f32>,
+    /// Language code the window was decoded as
+    language: Option<String>,
+    /// Text without timestamps
+    text: String,
+    avg_logprob: f64,
+    compression_ratio: f64,
+    /// Encoder output the tokens were decoded from
+    audio_features: Option<Tensor>,
 }
 
 impl CandleEngine {
@@ -73,38 +270,131 @@ impl CandleEngine {
     ///
     /// # Arguments
     /// * `model_id` - Local file path to GGUF model OR HuggingFace model ID
-    ///   - Local: "/path/to/model.gguf" or "/path/to/model.bin"
+    ///   - Local: "/path/to/model.gguf" or "/path/to/model.bin", with its config
+    ///     and tokenizer next to it (see [`crate::model::find_companion`])
     ///   - HuggingFace: "openai/whisper-large-v3-turbo" (downloads safetensors)
     ///   - HuggingFace quantized: "Demonthos/candle-quantized-whisper-large-v3-turbo" (downloads GGUF)
     /// * `language` - Language code (e.g., "en", "es", "fr")
     /// * `initial_prompt` - Optional technical vocabulary prompt to bias transcription
+    /// * `offline` - Never touch the network; every file must be on disk already
     pub fn with_options(
         model_id: &str,
         language: &str,
         initial_prompt: Option<String>,
+        offline: bool,
     ) -> Result<Self> {
         let device = Self::get_device()?;
         info!("Using device: {:?}", device);
 
+        let (config, tokenizer, model, weights, generation_config) =
+            Self::load_model(model_id, &device, offline)?;
+        info!("Model loaded successfully");
+
+        // Create mel filterbank from pre-computed bytes
+        let mel_filters_vec = Self::load_mel_filters(config.num_mel_bins)?;
+        info!("Mel filters loaded: {} filters", mel_filters_vec.len());
+
+        // Build suppress tokens mask to prevent unwanted tokens (like 199)
+        let vocab_size = tokenizer.get_vocab_size(true);
+        let no_ts_token = tokenizer
+            .token_to_id("<|notimestamps|>")
+            .ok_or_else(|| anyhow::anyhow!("No timestamps token not found"))?;
+
+        let mut suppress_list = vec![220u32]; // Blank token only (NOT EOT - we need that!)
+        // Suppress all timestamp tokens (everything after <|notimestamps|>)
+        for i in (no_ts_token + 1)..vocab_size as u32 {
+            suppress_list.push(i);
+        }
+
+        let mut mask = vec![0f32; vocab_size];
+        for &token in &suppress_list {
+            mask[token as usize] = f32::NEG_INFINITY;
+        }
+        let suppress_tokens = Tensor::new(&mask[..], &device)?;
+        info!(
+            "Suppress mask created: {} tokens suppressed",
+            suppress_list.len()
+        );
+
+        // Timestamp mode: keep timestamps, but never sample other special tokens
+        let eot_token = tokenizer
+            .token_to_id("<|endoftext|>")
+            .ok_or_else(|| anyhow::anyhow!("End of text token not found"))?;
+        let mut timestamp_mask = vec![0f32; vocab_size];
+        timestamp_mask[220] = f32::NEG_INFINITY;
+        for token in (eot_token + 1)..=no_ts_token {
+            timestamp_mask[token as usize] = f32::NEG_INFINITY;
+        }
+        let timestamp_suppress_tokens = Tensor::new(&timestamp_mask[..], &device)?;
+
+        // English-only models have no language tokens
+        let language_candidates: Vec<(String, u32)> = LANGUAGES
+            .iter()
+            .filter_map(|&code| {
+                let token = tokenizer.token_to_id(&format!("<|{}|>", code))?;
+                Some((code.to_string(), token))
+            })
+            .collect();
+        if language == AUTO_LANGUAGE && language_candidates.is_empty() {
+            anyhow::bail!("language = \"auto\" needs a multilingual model");
+        }
+
+        let alignment_heads = generation_config.and_then(|path| {
+            read_alignment_heads(&path)
+                .map_err(|e| debug!("No alignment heads in {}: {}", path.display(), e))
+                .ok()
+        });
+
+        info!("CandleEngine initialization complete - ready for transcription");
+
+        Ok(Self {
+            device,
+            model,
+            tokenizer,
+            config,
+            language: language.to_string(),
+            language_candidates,
+            detected: None,
+            task: Task::Transcribe,
+            beam_size: 1,
+            length_penalty: None,
+            draft: None,
+            offline,
+            decode_stats: DecodeStats::default(),
+            bench: BenchOptions::default(),
+            initial_prompt,
+            mel_filters: mel_filters_vec,
+            suppress_tokens,
+            timestamp_suppress_tokens,
+            weights,
+            alignment_heads,
+            aligner: None,
+        })
+    }
+
+    /// Load config, tokenizer and weights from a local file/directory or HuggingFace
+    ///
+    /// Also returns where the weights came from and the path of
+    /// `generation_config.json`, if the model ships one.
+    ///
+    /// With `offline`, nothing is downloaded: files must be next to local
+    /// weights or already in the HuggingFace cache.
+    fn load_model(model_id: &str, device: &Device, offline: bool) -> Result<LoadedModel> {
         // Check if model_id is a local file path
         let is_local_file = Path::new(model_id).exists();
         let is_quantized =
             is_local_file && (model_id.ends_with(".gguf") || model_id.ends_with(".bin"));
 
-        let (config, tokenizer, model) = if is_local_file {
+        let loaded = if is_local_file {
             info!("Loading model from local file: {}", model_id);
 
             if is_quantized {
                 // Load GGUF quantized model from local file
                 info!("Detected quantized model (GGUF/GGML format)");
 
-                // For quantized models, we need config and tokenizer from HuggingFace
-                // Use a base model for config/tokenizer
-                let api = Api::new()?;
-                let repo = api.repo(Repo::model("openai/whisper-large-v3-turbo".to_string()));
-
-                let config_filename = repo.get("config.json")?;
-                let tokenizer_filename = repo.get("tokenizer.json")?;
+                // The weights file doesn't carry the config and tokenizer
+                let (config_filename, tokenizer_filename, generation_config) =
+                    Self::local_companions(Path::new(model_id), offline)?;
 
                 let config: Config =
                     serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
@@ -113,14 +403,15 @@ impl CandleEngine {
 
                 // Load quantized weights
                 let vb = candle_transformers::quantized_var_builder::VarBuilder::from_gguf(
-                    model_id, &device,
+                    model_id, device,
                 )?;
                 let model = Model::Quantized(whisper::quantized_model::Whisper::load(
                     &vb,
                     config.clone(),
                 )?);
 
-                (config, tokenizer, model)
+                let weights = WeightsFile::Gguf(model_id.into());
+                (config, tokenizer, model, weights, generation_config)
             } else {
                 // Load safetensors from local directory
                 info!("Loading local safetensors model");
@@ -129,6 +420,8 @@ impl CandleEngine {
                 let config_path = model_path.join("config.json");
                 let tokenizer_path = model_path.join("tokenizer.json");
                 let weights_path = model_path.join("model.safetensors");
+                let generation_config =
+                    Some(model_path.join("generation_config.json")).filter(|path| path.exists());
 
                 // Verify files exist
                 if !config_path.exists() {
@@ -146,93 +439,235 @@ impl CandleEngine {
                     .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
 
                 let vb = unsafe {
-                    VarBuilder::from_mmaped_safetensors(&[weights_path], whisper::DTYPE, &device)?
+                    VarBuilder::from_mmaped_safetensors(
+                        std::slice::from_ref(&weights_path),
+                        whisper::DTYPE,
+                        device,
+                    )?
                 };
                 let model = Model::Normal(whisper::model::Whisper::load(&vb, config.clone())?);
 
-                (config, tokenizer, model)
+                let weights = WeightsFile::Safetensors(weights_path);
+                (config, tokenizer, model, weights, generation_config)
             }
         } else {
-            // Download from HuggingFace
-            info!("Downloading model from HuggingFace: {}", model_id);
+            // Download from HuggingFace (or only its cache when offline)
+            if offline {
+                info!("Loading model from the HuggingFace cache: {}", model_id);
+            } else {
+                info!("Downloading model from HuggingFace: {}", model_id);
+            }
 
-            let api = Api::new()?;
-            let repo = api.repo(Repo::model(model_id.to_string()));
+            let api = if offline { None } else { Some(Api::new()?) };
+            let cache = Cache::from_env().model(model_id.to_string());
+            let get = |name: &str| -> Result<PathBuf> {
+                match &api {
+                    Some(api) => Ok(api.model(model_id.to_string()).get(name)?),
+                    None => cache.get(name).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "{} of {} is not in the HuggingFace cache (offline mode); load the model once while online",
+                            name,
+                            model_id
+                        )
+                    }),
+                }
+            };
 
-            let config_filename = repo.get("config.json")?;
-            let tokenizer_filename = repo.get("tokenizer.json")?;
+            let config_filename = get(CONFIG_FILE)?;
+            let tokenizer_filename = get(TOKENIZER_FILE)?;
+            let generation_config = get(GENERATION_CONFIG_FILE).ok();
 
             let config: Config = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
             let tokenizer = Tokenizer::from_file(tokenizer_filename)
                 .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {}", e))?;
 
             // Try to load quantized model first, fall back to safetensors
-            let model = if let Ok(weights_filename) = repo.get("model.gguf") {
+            let (model, weights) = if let Ok(weights_filename) = get("model.gguf") {
                 info!("Found GGUF model, loading quantized variant");
                 let vb = candle_transformers::quantized_var_builder::VarBuilder::from_gguf(
                     &weights_filename,
-                    &device,
+                    device,
                 )?;
-                Model::Quantized(whisper::quantized_model::Whisper::load(
+                let model = Model::Quantized(whisper::quantized_model::Whisper::load(
                     &vb,
                     config.clone(),
-                )?)
+                )?);
+                (model, WeightsFile::Gguf(weights_filename))
             } else {
                 info!("Loading safetensors model (normal precision)");
-                let weights_filename = repo.get("model.safetensors")?;
+                let weights_filename = get("model.safetensors")?;
                 let vb = unsafe {
                     VarBuilder::from_mmaped_safetensors(
-                        &[weights_filename],
+                        std::slice::from_ref(&weights_filename),
                         whisper::DTYPE,
-                        &device,
+                        device,
                     )?
                 };
-                Model::Normal(whisper::model::Whisper::load(&vb, config.clone())?)
+                let model = Model::Normal(whisper::model::Whisper::load(&vb, config.clone())?);
+                (model, WeightsFile::Safetensors(weights_filename))
             };
 
-            (config, tokenizer, model)
+            (config, tokenizer, model, weights, generation_config)
         };
+        Ok(loaded)
+    }
 
-        info!("Model loaded successfully");
+    /// Config, tokenizer and generation config stored next to local weights
+    ///
+    /// Missing ones are fetched from the model's HuggingFace repo and kept
+    /// there for next time, unless `offline`.
+    fn local_companions(
+        weights: &Path,
+        offline: bool,
+    ) -> Result<(PathBuf, PathBuf, Option<PathBuf>)> {
+        let find = |name| find_companion(weights, name);
+        if find(CONFIG_FILE).is_none() || find(TOKENIZER_FILE).is_none() {
+            if offline {
+                anyhow::bail!(
+                    "No {} and {} next to {} (offline mode)\nRun `hyprvoice download` for this model while online, or copy them there",
+                    CONFIG_FILE,
+                    TOKENIZER_FILE,
+                    weights.display()
+                );
+            }
 
-        // Create mel filterbank from pre-computed bytes
-        let mel_filters_vec = Self::load_mel_filters(config.num_mel_bins)?;
-        info!("Mel filters loaded: {} filters", mel_filters_vec.len());
+            let model = match ModelInfo::for_weights(weights) {
+                Some(model) => model,
+                None => {
+                    warn!(
+                        "Unknown model {}, using the {} config and tokenizer",
+                        weights.display(),
+                        FALLBACK_CONFIG_MODEL
+                    );
+                    ModelInfo::find(FALLBACK_CONFIG_MODEL).ok_or_else(|| {
+                        anyhow::anyhow!("Unknown model: {}", FALLBACK_CONFIG_MODEL)
+                    })?
+                },
+            };
+            info!(
+                "Fetching config and tokenizer from {}, stored next to the weights",
+                model.config_repo
+            );
+            download_companions(model, weights)?;
+        }
 
-        // Build suppress tokens mask to prevent unwanted tokens (like 199)
-        let vocab_size = tokenizer.get_vocab_size(true);
-        let no_ts_token = tokenizer
-            .token_to_id("<|notimestamps|>")
-            .ok_or_else(|| anyhow::anyhow!("No timestamps token not found"))?;
+        let required = |name| {
+            find(name)
+                .ok_or_else(|| anyhow::anyhow!("{} not found next to {}", name, weights.display()))
+        };
+        Ok((
+            required(CONFIG_FILE)?,
+            required(TOKENIZER_FILE)?,
+            find(GENERATION_CONFIG_FILE),
+        ))
+    }
 
-        let mut suppress_list = vec![220u32]; // Blank token only (NOT EOT - we need that!)
-        // Suppress all timestamp tokens (everything after <|notimestamps|>)
-        for i in (no_ts_token + 1)..vocab_size as u32 {
-            suppress_list.push(i);
+    /// Only let language detection pick one of `languages` (empty = any)
+    pub fn with_allowed_languages(mut self, languages: &[String]) -> Result<Self> {
+        if languages.is_empty() {
+            return Ok(self);
         }
 
-        let mut mask = vec![0f32; vocab_size];
-        for &token in &suppress_list {
-            mask[token as usize] = f32::NEG_INFINITY;
+        for code in languages {
+            if !self.language_candidates.iter().any(|(c, _)| c == code) {
+                anyhow::bail!("Unknown language in [model] languages: {}", code);
+            }
         }
-        let suppress_tokens = Tensor::new(&mask[..], &device)?;
-        info!(
-            "Suppress mask created: {} tokens suppressed",
-            suppress_list.len()
-        );
+        self.language_candidates
+            .retain(|(code, _)| languages.contains(code));
+        info!("Language detection limited to {:?}", languages);
+        Ok(self)
+    }
 
-        info!("CandleEngine initialization complete - ready for transcription");
+    /// Start out with `task` instead of transcribing
+    pub fn with_task(mut self, task: Task) -> Result<Self> {
+        self.set_task(task)?;
+        Ok(self)
+    }
 
-        Ok(Self {
-            device,
-            model,
-            tokenizer,
-            config,
-            language: language.to_string(),
-            initial_prompt,
-            mel_filters: mel_filters_vec,
-            suppress_tokens,
-        })
+    /// Decode with beam search of `beam_size` hypotheses at temperature 0
+    ///
+    /// Higher fallback temperatures and `beam_size = 1` stay greedy.
+    pub fn with_beam_search(
+        mut self,
+        beam_size: usize,
+        length_penalty: Option<f32>,
+    ) -> Result<Self> {
+        if beam_size == 0 {
+            anyhow::bail!("beam_size must be at least 1");
+        }
+        if beam_size > 1 {
+            info!(
+                "Beam search with {} beams (length penalty: {:?})",
+                beam_size, length_penalty
+            );
+        }
+        self.beam_size = beam_size;
+        self.length_penalty = length_penalty;
+        Ok(self)
+    }
+
+    /// Speed up greedy decoding with the draft model at `path` (speculative decoding)
+    ///
+    /// A missing, unloadable or incompatible draft only disables speculation.
+    pub fn with_draft_model(mut self, path: Option<&Path>) -> Self {
+        let Some(path) = path else {
+            return self;
+        };
+        if !path.exists() {
+            info!(
+                "Draft model {} not found, speculative decoding disabled",
+                path.display()
+            );
+            return self;
+        }
+
+        match Draft::load(path, &self.tokenizer, &self.device, self.offline) {
+            Ok(draft) => {
+                info!("Speculative decoding with draft model {}", path.display());
+                self.draft = Some(draft);
+            },
+            Err(e) => warn!(
+                "Draft model {} not usable, speculative decoding disabled: {:#}",
+                path.display(),
+                e
+            ),
+        }
+        self
+    }
+
+    /// Whether the model can do `task`
+    ///
+    /// English-only (`.en`) models have a smaller vocabulary and never learned
+    /// to translate.
+    pub fn check_task(&self, task: Task) -> Result<()> {
+        if task == Task::Translate {
+            if self.config.vocab_size < MULTILINGUAL_VOCAB_SIZE {
+                anyhow::bail!("English-only models can't translate; use a multilingual model");
+            }
+            if self.tokenizer.token_to_id(task.token()).is_none() {
+                anyhow::bail!("Token not found: {}", task.token());
+            }
+        }
+        Ok(())
+    }
+
+    /// Decoding work of the last transcription
+    pub fn decode_stats(&self) -> &DecodeStats {
+        &self.decode_stats
+    }
+
+    /// Switch decoding paths for a benchmark
+    #[cfg(feature = "bench")]
+    #[allow(dead_code)] // The CLI binary compiles this module too, but never benchmarks
+    pub fn set_bench_options(&mut self, options: BenchOptions) {
+        self.bench = options;
+    }
+
+    /// Forget what the last transcription detected and counted
+    fn start_transcription(&mut self) {
+        self.detected = None;
+        self.decode_stats = DecodeStats::default();
     }
 
     fn get_device() -> Result<Device> {
@@ -296,19 +731,58 @@ impl CandleEngine {
 
         let sot_token = token_id("<|startoftranscript|>")?;
         let eot_token = token_id("<|endoftext|>")?;
-        let transcribe_token = token_id("<|transcribe|>")?;
+        let task_token = token_id(self.task.token())?;
         let no_timestamps_token = token_id("<|notimestamps|>")?;
-        let language_token = token_id(&format!("<|{}|>", self.language))?;
 
         Ok(SpecialTokens {
             sot_token,
             eot_token,
-            transcribe_token,
+            task_token,
             no_timestamps_token,
-            language_token,
         })
     }
 
+    /// Token id of `<|code|>`
+    fn language_token(&self, code: &str) -> Result<u32> {
+        self.tokenizer
+            .token_to_id(&format!("<|{}|>", code))
+            .ok_or_else(|| anyhow::anyhow!("Language not supported by the model: {}", code))
+    }
+
+    /// Language to decode a window as: the configured one, or the detected
+    /// one with `language = "auto"`
+    fn window_language(&mut self, audio_features: &Tensor, sot_token: u32) -> Result<String> {
+        if self.language != AUTO_LANGUAGE {
+            return Ok(self.language.clone());
+        }
+
+        // Language-ID step: logits of the token following SOT
+        let input = Tensor::new(&[sot_token], &self.device)?.unsqueeze(0)?;
+        let hidden = self.model.decoder_forward(&input, audio_features, true)?;
+        let logits = self
+            .model
+            .decoder_final_linear(&hidden)?
+            .i((0, 0))?
+            .to_vec1::<f32>()?;
+
+        let tokens: Vec<u32> = self.language_candidates.iter().map(|(_, t)| *t).collect();
+        let (index, probability) = pick_language(&logits, &tokens)
+            .ok_or_else(|| anyhow::anyhow!("Language detection failed"))?;
+        let detected = DetectedLanguage {
+            code: self.language_candidates[index].0.clone(),
+            probability,
+        };
+        info!(
+            "Detected language: {} ({:.0}%)",
+            detected.code,
+            detected.probability * 100.0
+        );
+
+        let code = detected.code.clone();
+        self.detected.get_or_insert(detected);
+        Ok(code)
+    }
+
     /// Encode the initial prompt if provided
     fn encode_initial_prompt(&self) -> Result<Vec<u32>> {
         if let Some(ref prompt) = self.initial_prompt {
@@ -339,14 +813,21 @@ impl CandleEngine {
         }
     }
 
+    /// Greedy decode of one encoded window; `timestamps` samples timestamp tokens too
+    ///
+    /// With `draft_features` (the draft model's encoder output) greedy
+    /// decoding is speculative.
     fn decode_at_temperature(
         &mut self,
-        mel: &Tensor,
+        audio_features: &Tensor,
+        language: &str,
         temperature: f64,
-    ) -> Result<(String, f64, f64)> {
+        timestamps: bool,
+        draft_features: Option<&Tensor>,
+    ) -> Result<Decoded> {
         debug!(
-            "decode_at_temperature() called with mel shape: {:?}, temp: {}",
-            mel.shape(),
+            "decode_at_temperature() called with audio features shape: {:?}, temp: {}",
+            audio_features.shape(),
             temperature
         );
         debug!("Starting decode process with temperature {}", temperature);
@@ -366,45 +847,33 @@ impl CandleEngine {
         };
         validate_token("SOT", special_tokens.sot_token);
         validate_token("EOT", special_tokens.eot_token);
-        validate_token("Language", special_tokens.language_token);
-        validate_token("Transcribe", special_tokens.transcribe_token);
+        validate_token("Task", special_tokens.task_token);
         validate_token("NoTimestamps", special_tokens.no_timestamps_token);
 
         info!(
-            "Special tokens: SOT={}, EOT={}, Lang={}, Transcribe={}, NoTS={}",
+            "Special tokens: SOT={}, EOT={}, Task={}, NoTS={}",
             special_tokens.sot_token,
             special_tokens.eot_token,
-            special_tokens.language_token,
-            special_tokens.transcribe_token,
+            special_tokens.task_token,
             special_tokens.no_timestamps_token
         );
 
         let prompt_tokens = self.encode_initial_prompt()?;
         debug!("Got {} prompt tokens", prompt_tokens.len());
 
-        // 1. Run Encoder
-        debug!(
-            "Running encoder forward pass on mel shape: {:?}",
-            mel.shape()
-        );
-        info!("MEL SHAPE BEFORE ENCODER: {:?}", mel.shape());
-        debug!("Calling encoder.forward()");
-        let audio_features = self.model.encoder_forward(mel, true)?;
-        debug!("encoder.forward() returned");
-        info!("ENCODER OUTPUT SHAPE: {:?}", audio_features.shape());
-        info!(
-            "ENCODER OUTPUT DIMS: batch={}, frames={}, d_model={}",
-            audio_features.dim(0)?,
-            audio_features.dim(1)?,
-            audio_features.dim(2)?
-        );
+        // 1. The encoder already ran (once per window, in decode_with_fallback)
+        let language_token = self.language_token(language)?;
 
         // 2. Build initial token sequence following Whisper spec:
-        // <|startoftranscript|><|language|><|transcribe|><|notimestamps|>[optional_prompt_tokens]
+        // <|startoftranscript|><|language|><|transcribe|/|translate|><|notimestamps|>[optional_prompt_tokens]
+        // (<|notimestamps|> is left out in timestamp mode)
         let mut current_tokens = vec![special_tokens.sot_token];
-        current_tokens.push(special_tokens.language_token);
-        current_tokens.push(special_tokens.transcribe_token);
-        current_tokens.push(special_tokens.no_timestamps_token);
+        current_tokens.push(language_token);
+        current_tokens.push(special_tokens.task_token);
+        if !timestamps {
+            current_tokens.push(special_tokens.no_timestamps_token);
+        }
+        let special_count = current_tokens.len();
 
         // Add initial prompt tokens for technical vocabulary biasing
         if !prompt_tokens.is_empty() {
@@ -417,13 +886,19 @@ impl CandleEngine {
 
         info!(
             "Initial token sequence: {} special tokens + {} prompt tokens = {} total",
-            4,
+            special_count,
             prompt_tokens.len(),
             current_tokens.len()
         );
 
         // 3. Greedy decoding loop with quality metrics
+        let timestamp_begin = special_tokens.no_timestamps_token + 1;
+        let timestamp_rules = timestamps.then_some(TimestampRules {
+            timestamp_begin,
+            eot: special_tokens.eot_token,
+        });
         let mut result_tokens = Vec::new();
+        let mut result_probs = Vec::new();
         let start_result_idx = current_tokens.len(); // Track where actual transcription starts
 
         // Calculate max tokens accounting for initial sequence (special + prompt tokens)
@@ -435,6 +910,49 @@ impl CandleEngine {
             start_result_idx, max_tokens
         );
 
+        if temperature == 0.0 && self.beam_size > 1 {
+            let best = self.beam_search(
+                audio_features,
+                &current_tokens,
+                timestamp_rules.as_ref(),
+                special_tokens.eot_token,
+                max_tokens,
+            )?;
+            let avg_logprob = best.avg_logprob();
+            return self.decoded(
+                best.tokens,
+                best.probs,
+                avg_logprob,
+                language.to_string(),
+                audio_features.clone(),
+                timestamp_begin,
+            );
+        }
+
+        if let Some(draft_features) = draft_features {
+            match self.speculative_greedy(
+                audio_features,
+                draft_features,
+                &current_tokens,
+                special_tokens.eot_token,
+                max_tokens,
+            ) {
+                Ok(Some(output)) => {
+                    let avg_logprob = output.avg_logprob();
+                    return self.decoded(
+                        output.tokens,
+                        output.probs,
+                        avg_logprob,
+                        language.to_string(),
+                        audio_features.clone(),
+                        timestamp_begin,
+                    );
+                },
+                Ok(None) => debug!("Draft can't follow this prefix, decoding without it"),
+                Err(e) => warn!("Speculative decoding failed, decoding without draft: {}", e),
+            }
+        }
+
         // Quality metrics tracking
         let mut sum_logprob = 0.0f64;
         let mut logprob_count = 0;
@@ -480,7 +998,7 @@ impl CandleEngine {
             // CRITICAL: Only flush KV cache on first iteration to maintain context
             let decoder_output =
                 self.model
-                    .decoder_forward(&input, &audio_features, iteration == 0)?;
+                    .decoder_forward(&input, audio_features, iteration == 0)?;
 
             // Project hidden states to vocabulary logits [batch, seq_len, vocab_size=51866]
             let logits = self.model.decoder_final_linear(&decoder_output)?;
@@ -502,7 +1020,14 @@ impl CandleEngine {
             }
 
             // Apply suppress mask BEFORE temperature/argmax (prevents token 199 and other unwanted tokens)
-            last_logit = last_logit.broadcast_add(&self.suppress_tokens)?;
+            if let Some(rules) = &timestamp_rules {
+                last_logit = last_logit.broadcast_add(&self.timestamp_suppress_tokens)?;
+                let mut values = last_logit.to_vec1::<f32>()?;
+                rules.apply(&mut values, &result_tokens);
+                last_logit = Tensor::new(values.as_slice(), &self.device)?;
+            } else {
+                last_logit = last_logit.broadcast_add(&self.suppress_tokens)?;
+            }
 
             // Apply temperature (if temp > 0)
             if temperature > 0.0 {
@@ -564,9 +1089,176 @@ impl CandleEngine {
             // Only add tokens after the initial sequence to result
             if current_tokens.len() > start_result_idx {
                 result_tokens.push(next_token);
+                result_probs.push(token_logprob as f32);
             }
         }
 
+        let avg_logprob = if logprob_count > 0 {
+            sum_logprob / logprob_count as f64
+        } else {
+            0.0
+        };
+        self.decoded(
+            result_tokens,
+            result_probs,
+            avg_logprob,
+            language.to_string(),
+            audio_features.clone(),
+            timestamp_begin,
+        )
+    }
+
+    /// Beam search over one window (see [`BeamSearch`])
+    ///
+    /// All beams run as one batch. The batch always has `beam_size` rows
+    /// (the last live beam fills in for missing ones), so the decoder's cached
+    /// cross-attention keys fit every step.
+    fn beam_search(
+        &mut self,
+        audio_features: &Tensor,
+        prefix: &[u32],
+        timestamp_rules: Option<&TimestampRules>,
+        eot_token: u32,
+        max_tokens: usize,
+    ) -> Result<Hypothesis> {
+        let beam_size = self.beam_size;
+        let audio_features = audio_features.repeat((beam_size, 1, 1))?;
+        let suppress = if timestamp_rules.is_some() {
+            self.timestamp_suppress_tokens.clone()
+        } else {
+            self.suppress_tokens.clone()
+        };
+
+        let mut search = BeamSearch::new(beam_size, eot_token, self.length_penalty);
+        for step in 0..max_tokens {
+            let live = search.live();
+            let len = prefix.len() + live[0].tokens.len();
+            let rows: Vec<u32> = (0..beam_size)
+                .flat_map(|row| {
+                    let hypothesis = &live[row.min(live.len() - 1)];
+                    prefix.iter().chain(&hypothesis.tokens).copied()
+                })
+                .collect();
+            let input = Tensor::from_vec(rows, (beam_size, len), &self.device)?;
+
+            let hidden = self
+                .model
+                .decoder_forward(&input, &audio_features, step == 0)?;
+            let logits = self
+                .model
+                .decoder_final_linear(&hidden.narrow(1, len - 1, 1)?)?
+                .squeeze(1)?
+                .broadcast_add(&suppress)?
+                .to_vec2::<f32>()?;
+
+            let logprobs: Vec<Vec<f32>> = live
+                .iter()
+                .zip(logits)
+                .map(|(hypothesis, mut logits)| {
+                    if let Some(rules) = timestamp_rules {
+                        rules.apply(&mut logits, &hypothesis.tokens);
+                    }
+                    log_softmax(&logits)
+                })
+                .collect();
+            search.step(&logprobs);
+            if search.is_done() {
+                debug!("Beam search finished after {} steps", step + 1);
+                break;
+            }
+        }
+        Ok(search.best())
+    }
+
+    /// Greedy decoding with draft proposals (see [`crate::transcribe::speculative`])
+    ///
+    /// Emits the same tokens as the greedy loop of `decode_at_temperature`
+    /// (temperature 0, no timestamps), up to floating-point rounding: each one
+    /// is the main model's argmax, the draft only decides how many are checked
+    /// per decoder pass. None if the draft can't express `prefix`.
+    fn speculative_greedy(
+        &mut self,
+        audio_features: &Tensor,
+        draft_features: &Tensor,
+        prefix: &[u32],
+        eot_token: u32,
+        max_tokens: usize,
+    ) -> Result<Option<GreedyOutput>> {
+        let Some(draft) = self.draft.as_mut() else {
+            return Ok(None);
+        };
+        let Some(draft_prefix) = draft.map_prefix(&self.tokenizer, prefix, eot_token) else {
+            return Ok(None);
+        };
+        if max_tokens == 0 {
+            return Ok(None);
+        }
+        let draft_positions = draft.config.max_target_positions;
+
+        let mut output = GreedyOutput::new(eot_token, max_tokens);
+        let mut draft_started = false;
+        let propose = |generated: &[u32], budget: usize| -> Result<Vec<u32>> {
+            let budget = budget
+                .min(draft_positions.saturating_sub(draft_prefix.len() + generated.len() + 1));
+            let mut draft_tokens = [draft_prefix.as_slice(), generated].concat();
+            let mut proposals = Vec::with_capacity(budget);
+            while proposals.len() < budget {
+                let token = draft.next_token(&draft_tokens, draft_features, !draft_started)?;
+                draft_started = true;
+                proposals.push(token);
+                draft_tokens.push(token);
+                if token == eot_token {
+                    break;
+                }
+            }
+            Ok(proposals)
+        };
+
+        // One main pass scores the position after each proposal
+        let (model, device, suppress_tokens) =
+            (&mut self.model, &self.device, &self.suppress_tokens);
+        let mut main_started = false;
+        let verify = |generated: &[u32], proposals: &[u32]| -> Result<Vec<(u32, f32)>> {
+            let tokens = [prefix, generated, proposals].concat();
+            let input = Tensor::new(tokens.as_slice(), device)?.unsqueeze(0)?;
+            let hidden = model.decoder_forward(&input, audio_features, !main_started)?;
+            main_started = true;
+            let positions = proposals.len() + 1;
+            let logits = model
+                .decoder_final_linear(&hidden.narrow(1, tokens.len() - positions, positions)?)?
+                .squeeze(0)?
+                .broadcast_add(suppress_tokens)?;
+            let verified = logits.argmax(1)?.to_vec1::<u32>()?;
+            let probs = candle_nn::ops::softmax_last_dim(&logits)?.to_vec2::<f32>()?;
+            Ok(verified
+                .into_iter()
+                .zip(probs)
+                .map(|(token, probs)| (token, probs[token as usize]))
+                .collect())
+        };
+        let stats = speculative::decode(&mut output, propose, verify)?;
+
+        info!(
+            "Speculative decoding: {}/{} draft tokens accepted ({:.0}%), {} tokens in {} passes",
+            stats.accepted,
+            stats.proposed,
+            stats.acceptance_rate() * 100.0,
+            output.steps(),
+            stats.passes
+        );
+        Ok(Some(output))
+    }
+
+    /// Text and quality metrics of the sampled tokens of one window
+    fn decoded(
+        &self,
+        result_tokens: Vec<u32>,
+        result_probs: Vec<f32>,
+        avg_logprob: f64,
+        language: String,
+        audio_features: Tensor,
+        timestamp_begin: u32,
+    ) -> Result<Decoded> {
         // 4. Decode tokens to text
         // skip_special_tokens = true to remove any remaining special tokens
         info!("Decoding {} result tokens to text", result_tokens.len());
@@ -576,10 +1268,12 @@ impl CandleEngine {
             info!("First 20 result tokens: {:?}", &result_tokens[..20]);
         }
 
-        let decoded = self
-            .tokenizer
-            .decode(&result_tokens, true)
-            .map_err(|e| anyhow::anyhow!("Decoding error: {}", e))?;
+        let text_tokens: Vec<u32> = result_tokens
+            .iter()
+            .copied()
+            .filter(|&t| t < timestamp_begin)
+            .collect();
+        let decoded = self.decode_text(&text_tokens)?;
 
         let text = decoded.trim().to_string();
         info!(
@@ -589,12 +1283,6 @@ impl CandleEngine {
         );
 
         // 5. Calculate quality metrics
-        let avg_logprob = if logprob_count > 0 {
-            sum_logprob / logprob_count as f64
-        } else {
-            0.0
-        };
-
         let compression_ratio = if !text.is_empty() {
             result_tokens.len() as f64 / text.len() as f64
         } else {
@@ -606,37 +1294,76 @@ impl CandleEngine {
             avg_logprob, compression_ratio
         );
 
-        Ok((text, avg_logprob, compression_ratio))
+        Ok(Decoded {
+            tokens: result_tokens,
+            probs: result_probs,
+            language: Some(language),
+            text,
+            avg_logprob,
+            compression_ratio,
+            audio_features: Some(audio_features),
+        })
+    }
+
+    /// Text of a run of tokens (special tokens skipped)
+    fn decode_text(&self, tokens: &[u32]) -> Result<String> {
+        self.tokenizer
+            .decode(tokens, true)
+            .map_err(|e| anyhow::anyhow!("Decoding error: {}", e))
     }
 
     /// Decode with temperature fallback for improved quality
     ///
     /// Tries temperatures [0.0, 0.2, 0.4, 0.6, 0.8, 1.0] until quality thresholds are met
-    fn decode_with_fallback(&mut self, mel: &Tensor) -> Result<String> {
+    /// `draft_features` enables speculative decoding at temperature 0.
+    /// The encoder and language detection only depend on the audio, so they
+    /// run once and every attempt decodes against the same features.
+    fn decode_with_fallback(
+        &mut self,
+        mel: &Tensor,
+        timestamps: bool,
+        draft_features: Option<&Tensor>,
+    ) -> Result<Decoded> {
+        let (mut audio_features, language) = self.encode_window(mel)?;
+        self.decode_stats.windows += 1;
+
         for (i, &temp) in TEMPERATURES.iter().enumerate() {
-            match self.decode_at_temperature(mel, temp) {
-                Ok((text, avg_logprob, compression_ratio)) => {
+            if i > 0 && self.bench.encode_per_attempt {
+                audio_features = self.encode_window(mel)?.0;
+            }
+            let draft_features = draft_features.filter(|_| temp == 0.0);
+            self.decode_stats.attempts += 1;
+            let decoded = self.decode_at_temperature(
+                &audio_features,
+                &language,
+                temp,
+                timestamps,
+                draft_features,
+            );
+            match decoded {
+                Ok(decoded) => {
                     // Last temperature - accept whatever we get
                     if i == TEMPERATURES.len() - 1 {
                         info!("Using last temperature {} (no fallback left)", temp);
-                        return Ok(text);
+                        return Ok(decoded);
                     }
 
                     // Check quality metrics
-                    let needs_fallback = compression_ratio > COMPRESSION_RATIO_THRESHOLD
-                        || avg_logprob < LOGPROB_THRESHOLD;
+                    let needs_fallback = decoded.compression_ratio > COMPRESSION_RATIO_THRESHOLD
+                        || decoded.avg_logprob < LOGPROB_THRESHOLD
+                        || self.bench.force_fallback;
 
                     if !needs_fallback {
                         info!(
                             "Decoding succeeded at temperature {} (logprob={:.3}, compression={:.3})",
-                            temp, avg_logprob, compression_ratio
+                            temp, decoded.avg_logprob, decoded.compression_ratio
                         );
-                        return Ok(text);
+                        return Ok(decoded);
                     }
 
                     warn!(
                         "Quality check failed at temp {} (logprob={:.3}, compression={:.3}), trying next temperature",
-                        temp, avg_logprob, compression_ratio
+                        temp, decoded.avg_logprob, decoded.compression_ratio
                     );
                 },
                 Err(e) => {
@@ -649,12 +1376,156 @@ impl CandleEngine {
         anyhow::bail!("All temperature fallbacks failed")
     }
 
+    /// Encoder features of a window, and the language to decode it in
+    fn encode_window(&mut self, mel: &Tensor) -> Result<(Tensor, String)> {
+        debug!(
+            "Running encoder forward pass on mel shape: {:?}",
+            mel.shape()
+        );
+        let started = Instant::now();
+        let audio_features = self.model.encoder_forward(mel, true)?;
+        self.decode_stats.encoder_passes += 1;
+        self.decode_stats.encoder_time += started.elapsed();
+        info!(
+            "ENCODER OUTPUT DIMS: batch={}, frames={}, d_model={}",
+            audio_features.dim(0)?,
+            audio_features.dim(1)?,
+            audio_features.dim(2)?
+        );
+
+        let sot_token = self.get_special_tokens()?.sot_token;
+        let language = self.window_language(&audio_features, sot_token)?;
+        Ok((audio_features, language))
+    }
+
     /// Transcribe a single chunk of audio (max 30 seconds)
     fn transcribe_chunk(&mut self, audio: &[f32]) -> Result<String> {
-        debug!("transcribe_chunk() called with {} samples", audio.len());
+        Ok(self.decode_chunk(audio, false)?.text)
+    }
+
+    /// Timed segments of a single chunk (max 30 seconds), relative to its start
+    fn chunk_segments(&mut self, audio: &[f32], words: bool) -> Result<Vec<Segment>> {
+        let decoded = self.decode_chunk(audio, true)?;
+        let timestamp_begin = self.get_special_tokens()?.no_timestamps_token + 1;
+        let mut segments = parse_segments(
+            &decoded.tokens,
+            timestamp_begin,
+            audio.len() as f64 / SAMPLE_RATE as f64,
+            |tokens| self.decode_text(tokens),
+        )?;
+
+        if words && !segments.is_empty() {
+            // Segments are still useful without word timings
+            let words = self
+                .align_words(&decoded, timestamp_begin, audio.len())
+                .unwrap_or_else(|e| {
+                    warn!("Word alignment failed: {}", e);
+                    Vec::new()
+                });
+            for (word, tokens) in words {
+                if let Some((segment, _)) = segments
+                    .iter_mut()
+                    .find(|(_, range)| range.contains(&tokens.start))
+                {
+                    segment.words.push(word);
+                }
+            }
+        }
+
+        Ok(segments.into_iter().map(|(segment, _)| segment).collect())
+    }
+
+    /// Words of a decoded chunk, timed relative to its start
+    ///
+    /// Each word comes with the range of its tokens among the text tokens.
+    fn align_words(
+        &mut self,
+        decoded: &Decoded,
+        timestamp_begin: u32,
+        samples: usize,
+    ) -> Result<Vec<(Word, Range<usize>)>> {
+        let (text_tokens, probs): (Vec<u32>, Vec<f32>) = decoded
+            .tokens
+            .iter()
+            .zip(&decoded.probs)
+            .filter(|&(&token, _)| token < timestamp_begin)
+            .unzip();
+        let (Some(audio_features), Some(language)) = (&decoded.audio_features, &decoded.language)
+        else {
+            return Ok(Vec::new());
+        };
+        let words = split_words(&text_tokens, uses_spaces(language), |tokens| {
+            self.decode_text(tokens)
+        })?;
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Teacher-forced pass over the text, without the prompt
+        let special = self.get_special_tokens()?;
+        let prefix = [
+            special.sot_token,
+            self.language_token(language)?,
+            special.task_token,
+        ];
+        let mut sequence = prefix.to_vec();
+        sequence.push(special.no_timestamps_token);
+        sequence.extend_from_slice(&text_tokens);
+        sequence.push(special.eot_token);
+
+        let aligner = match self.aligner.take() {
+            Some(aligner) => aligner,
+            None => AlignmentDecoder::load(
+                &self.weights,
+                &self.config,
+                self.alignment_heads.clone(),
+                &self.device,
+            )?,
+        };
+        let frames = samples * FRAMES_PER_SECOND as usize / SAMPLE_RATE;
+        let heads = aligner.attention(audio_features, &sequence, frames);
+        self.aligner = Some(aligner);
+        let heads = heads?;
+
+        let cols = heads.first().map_or(0, |head| head.len() / sequence.len());
+        let matrix = alignment_matrix(&heads, sequence.len(), cols);
+
+        // One row per text token (the row predicting it) plus the end of text
+        let rows = text_tokens.len() + 1;
+        let matrix = &matrix[prefix.len() * cols..(prefix.len() + rows) * cols];
+        let ranges: Vec<Range<usize>> = words.iter().map(|(_, range)| range.clone()).collect();
+        let times = word_times(matrix, rows, cols, &ranges);
+
+        Ok(words
+            .into_iter()
+            .zip(times)
+            .map(|((text, range), (start, end))| {
+                let probability = probs[range.clone()].iter().sum::<f32>() / range.len() as f32;
+                let word = Word {
+                    start,
+                    end,
+                    text,
+                    probability,
+                };
+                (word, range)
+            })
+            .collect())
+    }
+
+    /// Decode a single chunk of audio (max 30 seconds)
+    fn decode_chunk(&mut self, audio: &[f32], timestamps: bool) -> Result<Decoded> {
+        debug!("decode_chunk() called with {} samples", audio.len());
 
         if audio.is_empty() {
-            return Ok(String::new());
+            return Ok(Decoded {
+                tokens: Vec::new(),
+                probs: Vec::new(),
+                language: None,
+                text: String::new(),
+                avg_logprob: 0.0,
+                compression_ratio: 0.0,
+                audio_features: None,
+            });
         }
 
         // Pad audio to exactly 30 seconds (480000 samples at 16kHz) as Whisper expects
@@ -718,13 +1589,28 @@ impl CandleEngine {
         let mel = mel.unsqueeze(0)?; // Add batch dimension
         info!("MEL TENSOR SHAPE (after batch dim): {:?}", mel.shape());
 
+        // Speculation only covers plain greedy decoding
+        let draft_features = match self.draft.as_mut() {
+            Some(draft) if !timestamps && self.beam_size == 1 => {
+                match draft.encode(&padded_audio, &mel) {
+                    Ok(features) => Some(features),
+                    Err(e) => {
+                        warn!("Draft encoder failed, decoding without draft: {}", e);
+                        None
+                    },
+                }
+            },
+            _ => None,
+        };
+
         // 2. Decode with temperature fallback
-        self.decode_with_fallback(&mel)
+        self.decode_with_fallback(&mel, timestamps, draft_features.as_ref())
     }
 }
 
-impl Transcriber for CandleEngine {
-    fn transcribe(&mut self, audio: &[f32]) -> Result<String> {
+impl CandleEngine {
+    /// Transcribe audio of any length, taking windows found in `cache` as decoded
+    fn transcribe_long(&mut self, audio: &[f32], cache: Option<&WindowCache>) -> Result<String> {
         if audio.is_empty() {
             return Ok(String::new());
         }
@@ -743,7 +1629,7 @@ impl Transcriber for CandleEngine {
         );
 
         // Check if we need chunking (audio > 30 seconds)
-        if duration_secs <= CHUNK_LENGTH_SECS {
+        if audio.len() <= CHUNK_SAMPLES {
             // Short audio - process directly
             debug!("Audio <= 30s, processing without chunking");
             return self.transcribe_chunk(audio);
@@ -755,24 +1641,33 @@ impl Transcriber for CandleEngine {
             duration_secs, CHUNK_LENGTH_SECS, CHUNK_OVERLAP_SECS
         );
 
-        let chunk_samples = (CHUNK_LENGTH_SECS * SAMPLE_RATE as f32) as usize;
-        let overlap_samples = (CHUNK_OVERLAP_SECS * SAMPLE_RATE as f32) as usize;
-        let stride = chunk_samples - overlap_samples; // Step size between chunks
-
+        let windows = window_ranges(audio.len());
         let mut results = Vec::new();
-        let mut offset = 0;
+        let mut reused = 0;
+
+        for (index, range) in windows.iter().enumerate() {
+            let chunk = &audio[range.clone()];
 
-        while offset < audio.len() {
-            let end = (offset + chunk_samples).min(audio.len());
-            let chunk = &audio[offset..end];
-            let chunk_duration = chunk.len() as f32 / SAMPLE_RATE as f32;
+            if let Some(cached) = cache.and_then(|c| c.get(audio, range.clone())) {
+                debug!("Chunk {}: decoded during recording", index + 1);
+                reused += 1;
+                // As if detected now, so the first window's language still wins
+                if self.detected.is_none() {
+                    self.detected = cached.language.clone();
+                }
+                if !cached.text.is_empty() {
+                    results.push(cached.text.clone());
+                }
+                continue;
+            }
 
             info!(
-                "Processing chunk {}: {:.1}s-{:.1}s ({:.1}s duration, {} samples)",
-                results.len() + 1,
-                offset as f32 / SAMPLE_RATE as f32,
-                end as f32 / SAMPLE_RATE as f32,
-                chunk_duration,
+                "Processing chunk {}/{}: {:.1}s-{:.1}s ({:.1}s duration, {} samples)",
+                index + 1,
+                windows.len(),
+                range.start as f32 / SAMPLE_RATE as f32,
+                range.end as f32 / SAMPLE_RATE as f32,
+                chunk.len() as f32 / SAMPLE_RATE as f32,
                 chunk.len()
             );
 
@@ -785,49 +1680,146 @@ impl Transcriber for CandleEngine {
                 Err(e) => {
                     warn!(
                         "Chunk {} failed: {}, continuing with next chunk",
-                        results.len() + 1,
+                        index + 1,
                         e
                     );
                 },
             }
-
-            // Move to next chunk (with overlap)
-            offset += stride;
-
-            // If we're close to the end, process the remainder and stop
-            if offset + chunk_samples > audio.len() && offset < audio.len() {
-                let remaining = &audio[offset..];
-                if remaining.len() > overlap_samples {
-                    info!("Processing final chunk: {} samples", remaining.len());
-                    if let Ok(text) = self.transcribe_chunk(remaining) {
-                        if !text.is_empty() {
-                            results.push(text);
-                        }
-                    }
-                }
-                break;
-            }
         }
 
-        // Concatenate all chunks with space separator
-        let final_text = results.join(" ");
+        // Join the chunks, keeping words heard by two of them once
+        let final_text = stitch_texts(&results);
         info!(
-            "Long-form transcription complete: {} chunks, {} characters",
-            results.len(),
+            "Long-form transcription complete: {} chunks ({} decoded during recording), {} characters",
+            windows.len(),
+            reused,
             final_text.len()
         );
 
         Ok(final_text)
     }
+
+    /// Timed segments of audio of any length
+    ///
+    /// Windows are laid out as in `transcribe`; segments in the audio two
+    /// windows share are kept once (see [`stitch_windows`]).
+    fn segments_long(&mut self, audio: &[f32], words: bool) -> Result<Vec<Segment>> {
+        if audio.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let windows = window_ranges(audio.len());
+        info!(
+            "Transcribing {:.1}s with timestamps ({} windows)",
+            audio.len() as f32 / SAMPLE_RATE as f32,
+            windows.len()
+        );
+
+        let secs = |samples: usize| samples as f64 / SAMPLE_RATE as f64;
+        let mut decoded = Vec::with_capacity(windows.len());
+        for (index, range) in windows.iter().enumerate() {
+            let offset = secs(range.start);
+            let segments = match self.chunk_segments(&audio[range.clone()], words) {
+                Ok(segments) => segments,
+                Err(e) => {
+                    warn!(
+                        "Chunk {} failed: {}, continuing with next chunk",
+                        index + 1,
+                        e
+                    );
+                    Vec::new()
+                },
+            };
+
+            let segments = segments
+                .into_iter()
+                .map(|segment| Segment {
+                    start: segment.start + offset,
+                    end: segment.end + offset,
+                    text: segment.text,
+                    words: segment
+                        .words
+                        .into_iter()
+                        .map(|word| Word {
+                            start: word.start + offset,
+                            end: word.end + offset,
+                            ..word
+                        })
+                        .collect(),
+                })
+                .collect();
+            decoded.push(((offset, secs(range.end)), segments));
+        }
+
+        Ok(stitch_windows(decoded))
+    }
+}
+
+impl Transcriber for CandleEngine {
+    fn transcribe(&mut self, audio: &[f32]) -> Result<Transcription> {
+        self.start_transcription();
+        let text = self.transcribe_long(audio, None)?;
+        Ok(Transcription {
+            language: self.detected.clone(),
+            ..Transcription::from_text(text)
+        })
+    }
+
+    /// Single pass over the last 30s; no chunking, since the text is replaced
+    /// by the next partial anyway
+    fn transcribe_partial(&mut self, audio: &[f32]) -> Result<String> {
+        self.start_transcription();
+        // Greedy keeps live feedback quick; beam search is saved for the final text
+        let beam_size = std::mem::replace(&mut self.beam_size, 1);
+        let text = self.transcribe_chunk(&audio[audio.len().saturating_sub(CHUNK_SAMPLES)..]);
+        self.beam_size = beam_size;
+        text
+    }
+
+    fn transcribe_window(&mut self, window: &[f32]) -> Result<Transcription> {
+        self.start_transcription();
+        let text = self.transcribe_chunk(window)?;
+        Ok(Transcription {
+            language: self.detected.clone(),
+            ..Transcription::from_text(text)
+        })
+    }
+
+    fn transcribe_cached(&mut self, audio: &[f32], cache: &WindowCache) -> Result<Transcription> {
+        self.start_transcription();
+        let text = self.transcribe_long(audio, Some(cache))?;
+        Ok(Transcription {
+            language: self.detected.clone(),
+            ..Transcription::from_text(text)
+        })
+    }
+
+    fn transcribe_timed(&mut self, audio: &[f32], words: bool) -> Result<Transcription> {
+        self.start_transcription();
+        let segments = self.segments_long(audio, words)?;
+        Ok(Transcription {
+            language: self.detected.clone(),
+            ..Transcription::from_segments(segments)
+        })
+    }
+
+    fn set_task(&mut self, task: Task) -> Result<()> {
+        self.check_task(task)?;
+        if task != self.task {
+            info!("Task: {}", task);
+            self.task = task;
+        }
+        Ok(())
+    }
 }
 
 /// Special token IDs used in Whisper decoding
 struct SpecialTokens {
     sot_token: u32,
     eot_token: u32,
-    transcribe_token: u32,
+    /// `<|transcribe|>` or `<|translate|>`
+    task_token: u32,
     no_timestamps_token: u32,
-    language_token: u32,
 }
 
 #[cfg(test)]
@@ -0,0 +1,92 @@
+//! Spoken language identification
+//!
+//! With `language = "auto"` each window starts with Whisper's language-ID
+//! step: a single decoder pass after `<|startoftranscript|>`, comparing only
+//! the language tokens.
+
+use serde::{Deserialize, Serialize};
+
+/// `[model] language` value that enables detection
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Language codes with a `<|code|>` token, in tokenizer order
+pub const LANGUAGES: [&str; 100] = [
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln",
+    "ha", "ba", "jw", "su", "yue",
+];
+
+/// Language picked by the language-ID step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    /// Language code, e.g. "de"
+    pub code: String,
+    /// Probability among the candidate languages
+    pub probability: f32,
+}
+
+/// Most likely of `candidates` (token ids) given the logits after SOT
+///
+/// Returns its index in `candidates` and its probability among them.
+pub fn pick_language(logits: &[f32], candidates: &[u32]) -> Option<(usize, f32)> {
+    let scores: Vec<f32> = candidates
+        .iter()
+        .map(|&token| {
+            logits
+                .get(token as usize)
+                .copied()
+                .unwrap_or(f32::NEG_INFINITY)
+        })
+        .collect();
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if !max.is_finite() {
+        return None;
+    }
+
+    let total: f32 = scores.iter().map(|&s| (s - max).exp()).sum();
+    let (best, _) = scores
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    Some((best, 1.0 / total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_language_among_candidates() {
+        let mut logits = vec![0.0f32; 10];
+        logits[3] = 2.0;
+        logits[5] = 2.0;
+        logits[7] = 9.0; // Not a candidate
+
+        let (index, probability) = pick_language(&logits, &[3, 5, 6]).unwrap();
+        assert!(index == 0 || index == 1);
+        let expected = 1.0 / (2.0 + (-2.0f32).exp());
+        assert!((probability - expected).abs() < 1e-6, "{}", probability);
+
+        let (index, probability) = pick_language(&logits, &[6, 7]).unwrap();
+        assert_eq!(index, 1);
+        assert!(probability > 0.99);
+    }
+
+    #[test]
+    fn test_pick_language_without_candidates() {
+        assert_eq!(pick_language(&[1.0, 2.0], &[]), None);
+        assert_eq!(pick_language(&[1.0, 2.0], &[5]), None);
+    }
+
+    #[test]
+    fn test_language_codes_are_unique() {
+        let mut codes = LANGUAGES.to_vec();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), LANGUAGES.len());
+    }
+}
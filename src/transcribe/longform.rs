@@ -0,0 +1,348 @@
+//! Long-form chunking shared by one-shot and incremental transcription
+//!
+//! Audio longer than Whisper's 30s window is decoded as overlapping windows
+//! whose texts are joined, keeping the words two windows both heard once.
+//! The layout only depends on where a window starts, so the complete windows
+//! of a recording still in progress can be decoded early and reused once it
+//! stops, giving the same text as a one-shot run.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+
+use super::Transcription;
+
+/// Whisper's input sample rate
+pub const SAMPLE_RATE: usize = 16000;
+
+/// Maximum audio per window (Whisper limit)
+pub const CHUNK_LENGTH_SECS: f32 = 30.0;
+
+/// Audio shared by consecutive windows
+pub const CHUNK_OVERLAP_SECS: f32 = 5.0;
+
+/// Samples per full window
+pub const CHUNK_SAMPLES: usize = (CHUNK_LENGTH_SECS as usize) * SAMPLE_RATE;
+
+/// Samples shared by consecutive windows
+pub const OVERLAP_SAMPLES: usize = (CHUNK_OVERLAP_SECS as usize) * SAMPLE_RATE;
+
+/// Step between window starts
+const STRIDE: usize = CHUNK_SAMPLES - OVERLAP_SAMPLES;
+
+/// Words searched at each side of a window boundary (5s of fast speech, with margin)
+const MAX_OVERLAP_WORDS: usize = 40;
+
+/// Shortest run of shared words taken as the overlap
+///
+/// A single shared word is too often a coincidence ("the", "and"), or a word
+/// really said twice across the boundary ("... that | that is ...").
+const MIN_OVERLAP_RUN: usize = 2;
+
+/// Windows a clip of `len` samples is decoded as, in order
+///
+/// Clips up to 30s are a single window. Longer ones get a full window every
+/// 25s, plus the remainder if it holds more than the overlap.
+pub fn window_ranges(len: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    if len <= CHUNK_SAMPLES {
+        ranges.push(0..len);
+        return ranges;
+    }
+
+    let mut offset = 0;
+    while offset < len {
+        ranges.push(offset..(offset + CHUNK_SAMPLES).min(len));
+        offset += STRIDE;
+
+        // Close to the end: take the remainder and stop
+        if offset + CHUNK_SAMPLES > len && offset < len {
+            if len - offset > OVERLAP_SAMPLES {
+                ranges.push(offset..len);
+            }
+            break;
+        }
+    }
+    ranges
+}
+
+/// Full windows that fit in the first `len` samples
+///
+/// Every one of them is also a window of any longer clip with the same start.
+pub fn complete_windows(len: usize) -> impl Iterator<Item = Range<usize>> {
+    (0..)
+        .map(|index| index * STRIDE..index * STRIDE + CHUNK_SAMPLES)
+        .take_while(move |range| range.end <= len)
+}
+
+/// Where the text of a window picks up from the previous one
+///
+/// Returns `(keep, resume)`: the joined sequence is `previous[..keep]` followed
+/// by `next[resume..]`. Both windows decode the shared audio, so the end of
+/// `previous` and the start of `next` repeat a run of tokens; the longest such
+/// run is kept once, as `previous` has it (more context, so better casing and
+/// punctuation). What either window adds around the run comes from audio cut
+/// off at its edge and is dropped in favour of the other window's text.
+/// Of equally long runs, the one ending last in `previous` and first in
+/// `next` wins, so a phrase repeated within the overlap loses nothing in
+/// between. None if the sequences share no convincing run (e.g. silence in
+/// the overlap).
+pub fn overlap_cut<T: PartialEq>(previous: &[T], next: &[T]) -> Option<(usize, usize)> {
+    let tail = previous.len().saturating_sub(MAX_OVERLAP_WORDS);
+    let head = next.len().min(MAX_OVERLAP_WORDS);
+
+    // Longest common run: runs[j + 1] is the length of the run ending at
+    // previous[i] and next[j]
+    let mut runs = vec![0; head + 1];
+    let (mut best, mut keep, mut resume) = (0, 0, 0);
+    for (i, token) in previous.iter().enumerate().skip(tail) {
+        for j in (0..head).rev() {
+            runs[j + 1] = if *token == next[j] { runs[j] + 1 } else { 0 };
+            // `>=`: later in `previous` and (j descending) earlier in `next`
+            if runs[j + 1] > 0 && runs[j + 1] >= best {
+                best = runs[j + 1];
+                keep = i + 1;
+                resume = j + 1;
+            }
+        }
+    }
+
+    (best >= MIN_OVERLAP_RUN).then_some((keep, resume))
+}
+
+/// Join the texts of consecutive windows, resolving the words they share
+///
+/// Words are matched ignoring case and punctuation. Windows without a clear
+/// overlap are joined whole rather than risk dropping words.
+pub fn stitch_texts<S: AsRef<str>>(texts: &[S]) -> String {
+    let key = |word: &str| -> String {
+        word.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    let mut words: Vec<&str> = Vec::new();
+    let mut keys: Vec<String> = Vec::new();
+    for text in texts {
+        let next: Vec<&str> = text.as_ref().split_whitespace().collect();
+        let next_keys: Vec<String> = next.iter().map(|word| key(word)).collect();
+        let resume = match overlap_cut(&keys, &next_keys) {
+            Some((keep, resume)) => {
+                words.truncate(keep);
+                keys.truncate(keep);
+                resume
+            },
+            None => 0,
+        };
+        words.extend_from_slice(&next[resume..]);
+        keys.extend(next_keys.into_iter().skip(resume));
+    }
+    words.join(" ")
+}
+
+/// Identity of a window's exact samples
+fn fingerprint(samples: &[f32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for sample in samples {
+        sample.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[derive(Debug)]
+struct CachedWindow {
+    start: usize,
+    fingerprint: u64,
+    transcription: Transcription,
+}
+
+/// Texts (and detected languages) of windows decoded ahead of time
+///
+/// A cached text is only used for a window with exactly the same samples, so a
+/// recording whose start changed after the fact is simply decoded again.
+#[derive(Debug, Default)]
+pub struct WindowCache {
+    windows: Vec<CachedWindow>,
+}
+
+impl WindowCache {
+    /// Number of decoded windows
+    pub fn decoded(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// First complete window of `audio` that has not been decoded yet
+    pub fn next_missing(&self, audio: &[f32]) -> Option<Range<usize>> {
+        complete_windows(audio.len()).find(|range| self.get(audio, range.clone()).is_none())
+    }
+
+    /// Transcription of `audio[range]`, if its samples are unchanged
+    pub fn get(&self, audio: &[f32], range: Range<usize>) -> Option<&Transcription> {
+        let cached = self.windows.iter().find(|w| w.start == range.start)?;
+        (range.len() == CHUNK_SAMPLES && cached.fingerprint == fingerprint(&audio[range]))
+            .then_some(&cached.transcription)
+    }
+
+    /// Remember the transcription of the full window `audio[range]`
+    pub fn insert(&mut self, audio: &[f32], range: Range<usize>, transcription: Transcription) {
+        let window = CachedWindow {
+            start: range.start,
+            fingerprint: fingerprint(&audio[range]),
+            transcription,
+        };
+        match self.windows.iter_mut().find(|w| w.start == window.start) {
+            Some(existing) => *existing = window,
+            None => self.windows.push(window),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcribe::DetectedLanguage;
+
+    const SECOND: usize = SAMPLE_RATE;
+
+    #[test]
+    fn test_short_clip_is_one_window() {
+        assert_eq!(window_ranges(10 * SECOND), vec![0..10 * SECOND]);
+        assert_eq!(window_ranges(CHUNK_SAMPLES), vec![0..CHUNK_SAMPLES]);
+    }
+
+    #[test]
+    fn test_long_clip_windows() {
+        // 62s: full windows at 0s and 25s, remainder 50-62s
+        assert_eq!(
+            window_ranges(62 * SECOND),
+            vec![
+                0..30 * SECOND,
+                25 * SECOND..55 * SECOND,
+                50 * SECOND..62 * SECOND
+            ]
+        );
+
+        // 55s: the 5s remainder from 50s is all overlap and dropped
+        assert_eq!(
+            window_ranges(55 * SECOND),
+            vec![0..30 * SECOND, 25 * SECOND..55 * SECOND]
+        );
+    }
+
+    #[test]
+    fn test_complete_windows_match_final_layout() {
+        let final_windows = window_ranges(200 * SECOND);
+        for len in [31 * SECOND, 80 * SECOND, 200 * SECOND] {
+            for window in complete_windows(len) {
+                assert!(final_windows.contains(&window), "{:?}", window);
+            }
+        }
+        assert_eq!(complete_windows(29 * SECOND).count(), 0);
+        assert_eq!(complete_windows(80 * SECOND).count(), 3);
+    }
+
+    /// Speech as token ids, with one window's share of it
+    fn window(speech: &[u32], range: Range<usize>) -> Vec<u32> {
+        speech[range].to_vec()
+    }
+
+    fn stitch(previous: &[u32], next: &[u32]) -> Vec<u32> {
+        match overlap_cut(previous, next) {
+            Some((keep, resume)) => [&previous[..keep], &next[resume..]].concat(),
+            None => [previous, next].concat(),
+        }
+    }
+
+    #[test]
+    fn test_overlap_is_kept_once() {
+        let speech: Vec<u32> = (0..60).collect();
+        let first = window(&speech, 0..35);
+        let second = window(&speech, 28..60);
+        assert_eq!(stitch(&first, &second), speech);
+    }
+
+    #[test]
+    fn test_words_cut_at_window_edges_are_dropped() {
+        // Each window mishears the word cut off at its edge
+        let speech: Vec<u32> = (0..60).collect();
+        let first = [window(&speech, 0..35), vec![900]].concat();
+        let second = [vec![901], window(&speech, 29..60)].concat();
+        assert_eq!(stitch(&first, &second), speech);
+    }
+
+    #[test]
+    fn test_no_shared_run_keeps_every_word() {
+        // Silence in the overlap: nothing in common
+        assert_eq!(overlap_cut(&[1, 2, 3], &[4, 5, 6]), None);
+        assert_eq!(stitch(&[1, 2, 3], &[4, 5, 6]), vec![1, 2, 3, 4, 5, 6]);
+
+        // A lone common word is not an overlap, even at the boundary: it may
+        // really have been said twice ("... that | that is ...")
+        assert_eq!(overlap_cut(&[1, 7, 2, 3], &[4, 7, 5]), None);
+        assert_eq!(stitch(&[1, 2, 3], &[3, 4]), vec![1, 2, 3, 3, 4]);
+
+        assert_eq!(overlap_cut::<u32>(&[], &[1]), None);
+        assert_eq!(overlap_cut::<u32>(&[1], &[]), None);
+    }
+
+    #[test]
+    fn test_repeated_phrase_in_overlap() {
+        // "4 5" is said twice near the end of the first window; the second
+        // window only heard the last time
+        let first = [1, 2, 3, 4, 5, 8, 9, 4, 5];
+        assert_eq!(overlap_cut(&first, &[4, 5, 6]), Some((9, 2)));
+        assert_eq!(
+            stitch(&first, &[4, 5, 6]),
+            vec![1, 2, 3, 4, 5, 8, 9, 4, 5, 6]
+        );
+
+        // Repeated at the start of the second window: resume after the first
+        assert_eq!(
+            stitch(&[1, 2, 3, 4], &[3, 4, 7, 3, 4, 8]),
+            vec![1, 2, 3, 4, 7, 3, 4, 8]
+        );
+    }
+
+    #[test]
+    fn test_stitch_texts() {
+        let texts = [
+            "So the plan for today is to fix the",
+            "the plan for today is to fix the parser, then test it.",
+            "Then test it. And ship.",
+        ];
+        assert_eq!(
+            stitch_texts(&texts),
+            "So the plan for today is to fix the parser, then test it. And ship."
+        );
+        assert_eq!(
+            stitch_texts(&["Hello there.", "General Kenobi."]),
+            "Hello there. General Kenobi."
+        );
+        assert_eq!(stitch_texts::<&str>(&[]), "");
+    }
+
+    #[test]
+    fn test_cache_requires_identical_samples() {
+        let audio: Vec<f32> = (0..60 * SECOND).map(|i| (i % 100) as f32 / 100.0).collect();
+        let mut cache = WindowCache::default();
+
+        let first = cache.next_missing(&audio).unwrap();
+        assert_eq!(first, 0..CHUNK_SAMPLES);
+        let hello = Transcription {
+            language: Some(DetectedLanguage {
+                code: "en".to_string(),
+                probability: 0.9,
+            }),
+            ..Transcription::from_text("hello".to_string())
+        };
+        cache.insert(&audio, first.clone(), hello.clone());
+
+        // The detected language is kept with the text
+        assert_eq!(cache.get(&audio, first.clone()), Some(&hello));
+        assert_eq!(cache.next_missing(&audio), Some(25 * SECOND..55 * SECOND));
+
+        let mut changed = audio.clone();
+        changed[10] += 0.5;
+        assert_eq!(cache.get(&changed, first), None);
+    }
+}
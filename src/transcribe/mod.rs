@@ -1,13 +1,82 @@
 use anyhow::Result;
 
+pub mod alignment;
+pub mod beam;
 pub mod candle_engine;
+pub mod language;
+pub mod longform;
+pub mod segments;
+pub mod speculative;
+pub mod task;
+pub mod timestamps;
 pub mod whisper;
+pub mod words;
+
+pub use language::DetectedLanguage;
+pub use longform::WindowCache;
+pub use segments::{Segment, TranscriptFormat, Transcription, Word};
+pub use task::Task;
 
 /// Trait to abstract transcription engines
 pub trait Transcriber: Send + Sync {
-    /// Transcribe 16kHz mono f32 audio data to text
+    /// Transcribe 16kHz mono f32 audio data
+    ///
+    /// Segments are left empty; `transcribe_timed` decodes timings.
     ///
     /// Note: `&mut self` is required for Candle's stateful encoder/decoder forward passes.
     /// The model maintains internal state during inference that must be mutated.
-    fn transcribe(&mut self, audio: &[f32]) -> Result<String>;
+    fn transcribe(&mut self, audio: &[f32]) -> Result<Transcription>;
+
+    /// Quick hypothesis for the latest window of a recording still in progress
+    ///
+    /// Only used for live feedback; the final text always comes from `transcribe`.
+    fn transcribe_partial(&mut self, audio: &[f32]) -> Result<String> {
+        Ok(self.transcribe(audio)?.text)
+    }
+
+    /// Decode a single long-form window (see [`longform`]) ahead of the final pass
+    fn transcribe_window(&mut self, window: &[f32]) -> Result<Transcription> {
+        self.transcribe(window)
+    }
+
+    /// Same result as `transcribe`, reusing windows decoded while recording
+    fn transcribe_cached(&mut self, audio: &[f32], cache: &WindowCache) -> Result<Transcription> {
+        let _ = cache;
+        self.transcribe(audio)
+    }
+
+    /// Transcribe or translate to English in the following calls
+    ///
+    /// Fails if the engine or model can't do `task`.
+    fn set_task(&mut self, task: Task) -> Result<()> {
+        match task {
+            Task::Transcribe => Ok(()),
+            Task::Translate => anyhow::bail!("This engine can't translate"),
+        }
+    }
+
+    /// Transcribe with segment start/end times, and word timings and
+    /// confidences if `words` is set (slower than `transcribe`)
+    ///
+    /// Engines without timestamp decoding return the whole text as one
+    /// segment, without words.
+    fn transcribe_timed(&mut self, audio: &[f32], words: bool) -> Result<Transcription> {
+        let _ = words;
+        let transcription = self.transcribe(audio)?;
+        let segments = vec![Segment {
+            start: 0.0,
+            end: audio.len() as f64 / longform::SAMPLE_RATE as f64,
+            text: transcription.text,
+            words: Vec::new(),
+        }];
+        Ok(Transcription {
+            language: transcription.language,
+            ..Transcription::from_segments(
+                segments
+                    .into_iter()
+                    .filter(|segment| !segment.text.is_empty())
+                    .collect(),
+            )
+        })
+    }
 }
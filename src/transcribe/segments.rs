@@ -0,0 +1,340 @@
+//! Timed transcripts and caption output (SRT, WebVTT, JSON)
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use super::DetectedLanguage;
+
+/// Transcript with segment (and optionally word) timings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcription {
+    pub text: String,
+    /// Set when the language was detected rather than configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<DetectedLanguage>,
+    pub segments: Vec<Segment>,
+}
+
+impl Transcription {
+    /// Text without timings
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_segments(segments: Vec<Segment>) -> Self {
+        Self {
+            text: join_text(&segments),
+            language: None,
+            segments,
+        }
+    }
+}
+
+/// A stretch of speech with its position in the audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Seconds from the start of the audio
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// Empty unless word timestamps were requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+}
+
+/// A word with its timing and how sure the model was of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub start: f64,
+    pub end: f64,
+    /// Text as decoded, including its leading space
+    pub text: String,
+    /// Mean probability of the word's tokens when they were sampled
+    pub probability: f32,
+}
+
+/// Output format of `hyprvoice transcribe`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// Plain text
+    #[default]
+    Text,
+    Srt,
+    Vtt,
+    /// Full text plus segments with start/end seconds
+    Json,
+}
+
+impl TranscriptFormat {
+    /// Whether the format needs segment timestamps
+    pub fn is_timed(self) -> bool {
+        self != TranscriptFormat::Text
+    }
+}
+
+impl fmt::Display for TranscriptFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TranscriptFormat::Text => "text",
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::Vtt => "vtt",
+            TranscriptFormat::Json => "json",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for TranscriptFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Ok(TranscriptFormat::Text),
+            "srt" => Ok(TranscriptFormat::Srt),
+            "vtt" | "webvtt" => Ok(TranscriptFormat::Vtt),
+            "json" => Ok(TranscriptFormat::Json),
+            other => Err(format!(
+                "unknown output format '{}' (expected text, srt, vtt or json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Join the segments of overlapping windows into one timeline
+///
+/// `windows` holds each window's time span and its segments (already in
+/// absolute time). Speech in the audio shared by two windows is decoded by
+/// both; each side keeps only the segments starting on its half of the
+/// overlap, so it appears once.
+pub fn stitch_windows(windows: Vec<((f64, f64), Vec<Segment>)>) -> Vec<Segment> {
+    let cuts: Vec<f64> = windows
+        .windows(2)
+        .map(|pair| (pair[1].0.0 + pair[0].0.1) / 2.0)
+        .collect();
+
+    let mut stitched: Vec<Segment> = Vec::new();
+    for (index, ((_, window_end), segments)) in windows.into_iter().enumerate() {
+        let from = if index == 0 {
+            f64::NEG_INFINITY
+        } else {
+            cuts[index - 1]
+        };
+        let to = cuts.get(index).copied().unwrap_or(f64::INFINITY);
+
+        for mut segment in segments {
+            if segment.start < from || segment.start >= to {
+                continue;
+            }
+            // Never overlap the previous segment or run past the window
+            if let Some(previous) = stitched.last() {
+                segment.start = segment.start.max(previous.end);
+            }
+            segment.end = segment.end.min(window_end).max(segment.start);
+            for word in &mut segment.words {
+                word.start = word.start.clamp(segment.start, segment.end);
+                word.end = word.end.clamp(word.start, segment.end);
+            }
+            stitched.push(segment);
+        }
+    }
+    stitched
+}
+
+/// Render a transcript in `format`
+///
+/// WebVTT cues of segments with words carry karaoke-style inline timestamps.
+pub fn format_transcript(transcription: &Transcription, format: TranscriptFormat) -> String {
+    let segments = &transcription.segments;
+    match format {
+        TranscriptFormat::Text => transcription.text.clone() + "\n",
+        TranscriptFormat::Srt => {
+            let mut out = String::new();
+            for (index, segment) in segments.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "{}\n{} --> {}\n{}\n",
+                    index + 1,
+                    timecode(segment.start, ','),
+                    timecode(segment.end, ','),
+                    segment.text
+                );
+            }
+            out
+        },
+        TranscriptFormat::Vtt => {
+            let mut out = String::from("WEBVTT\n\n");
+            for segment in segments {
+                let _ = writeln!(
+                    out,
+                    "{} --> {}\n{}\n",
+                    timecode(segment.start, '.'),
+                    timecode(segment.end, '.'),
+                    cue_text(segment)
+                );
+            }
+            out
+        },
+        TranscriptFormat::Json => {
+            // Serializing plain values never fails
+            serde_json::to_string_pretty(transcription).unwrap_or_default() + "\n"
+        },
+    }
+}
+
+/// Segment text, with `<timestamp>` tags before each word after the first
+fn cue_text(segment: &Segment) -> String {
+    if segment.words.is_empty() {
+        return segment.text.clone();
+    }
+
+    let mut text = String::new();
+    for (index, word) in segment.words.iter().enumerate() {
+        if index == 0 {
+            text.push_str(word.text.trim_start());
+        } else {
+            let _ = write!(text, "<{}>{}", timecode(word.start, '.'), word.text);
+        }
+    }
+    text
+}
+
+/// Plain text of all segments
+fn join_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `HH:MM:SS<sep>mmm`
+fn timecode(secs: f64, separator: char) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str) -> Segment {
+        Segment {
+            start,
+            end,
+            text: text.to_string(),
+            words: Vec::new(),
+        }
+    }
+
+    fn word(start: f64, end: f64, text: &str) -> Word {
+        Word {
+            start,
+            end,
+            text: text.to_string(),
+            probability: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_stitch_drops_overlap_duplicates() {
+        // Windows 0-30s and 25-55s share 25-30s; the cut is at 27.5s
+        let stitched = stitch_windows(vec![
+            (
+                (0.0, 30.0),
+                vec![
+                    segment(0.0, 20.0, "one"),
+                    segment(20.0, 26.0, "two"),
+                    segment(28.0, 30.0, "three"),
+                ],
+            ),
+            (
+                (25.0, 55.0),
+                vec![
+                    segment(25.5, 26.0, "two"),
+                    segment(27.9, 31.0, "three"),
+                    segment(31.0, 40.0, "four"),
+                ],
+            ),
+        ]);
+
+        let texts: Vec<&str> = stitched.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["one", "two", "three", "four"]);
+        assert_eq!(stitched[2].start, 27.9);
+        assert!(stitched.windows(2).all(|p| p[0].end <= p[1].start));
+    }
+
+    #[test]
+    fn test_srt_and_vtt() {
+        let transcription = Transcription::from_segments(vec![
+            segment(0.0, 2.5, "Hello there."),
+            segment(3661.25, 3662.0, "Later."),
+        ]);
+
+        assert_eq!(
+            format_transcript(&transcription, TranscriptFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n\
+             2\n01:01:01,250 --> 01:01:02,000\nLater.\n\n"
+        );
+        assert_eq!(
+            format_transcript(&transcription, TranscriptFormat::Vtt),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello there.\n\n\
+             01:01:01.250 --> 01:01:02.000\nLater.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt_karaoke_cues() {
+        let mut timed = segment(0.0, 2.5, "Hello there.");
+        timed.words = vec![word(0.1, 0.8, " Hello"), word(1.2, 2.4, " there.")];
+        let transcription = Transcription::from_segments(vec![timed]);
+
+        assert_eq!(
+            format_transcript(&transcription, TranscriptFormat::Vtt),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nHello<00:00:01.200> there.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_json_output() {
+        let mut second = segment(1.0, 2.0, "b");
+        second.words = vec![word(1.0, 2.0, " b")];
+        let transcription = Transcription::from_segments(vec![segment(0.0, 1.0, "a"), second]);
+        let json: serde_json::Value =
+            serde_json::from_str(&format_transcript(&transcription, TranscriptFormat::Json))
+                .unwrap();
+
+        assert_eq!(json["text"], "a b");
+        assert_eq!(json["segments"][1]["start"], 1.0);
+        assert_eq!(json["segments"][1]["text"], "b");
+        assert!(json["segments"][0].get("words").is_none());
+        assert_eq!(json["segments"][1]["words"][0]["text"], " b");
+        assert!(
+            (json["segments"][1]["words"][0]["probability"]
+                .as_f64()
+                .unwrap()
+                - 0.9)
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("webvtt".parse(), Ok(TranscriptFormat::Vtt));
+        assert_eq!("SRT".parse(), Ok(TranscriptFormat::Srt));
+        assert!("docx".parse::<TranscriptFormat>().is_err());
+        assert!(!TranscriptFormat::Text.is_timed());
+    }
+}
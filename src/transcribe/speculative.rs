@@ -0,0 +1,293 @@
+//! Speculative decoding bookkeeping
+//!
+//! A small draft model guesses the next few tokens; the main model scores
+//! them all in one decoder pass. Its own argmax at each position decides:
+//! draft tokens are kept while they agree with it, and the first
+//! disagreement is replaced by the main model's token. Every emitted token is
+//! the main model's greedy choice, so the text matches plain greedy decoding;
+//! a good draft just gets several tokens out of each pass.
+
+/// Draft tokens proposed per main-model pass
+pub const DRAFT_TOKENS: usize = 5;
+
+/// A token may not repeat more than this many times in a row (as in greedy decoding)
+const MAX_REPEATS: usize = 3;
+
+/// Number of leading `proposals` the main model agrees with
+///
+/// `verified[i]` is the main model's choice after the first `i` proposals.
+pub fn accepted_len(proposals: &[u32], verified: &[u32]) -> usize {
+    proposals
+        .iter()
+        .zip(verified)
+        .take_while(|(proposed, verified)| proposed == verified)
+        .count()
+}
+
+/// Tokens emitted so far, with the stopping rules of the greedy decoding loop
+pub struct GreedyOutput {
+    pub tokens: Vec<u32>,
+    pub probs: Vec<f32>,
+    eot: u32,
+    max_steps: usize,
+    steps: usize,
+    sum_logprob: f64,
+    logprob_count: usize,
+    repeat_count: usize,
+}
+
+impl GreedyOutput {
+    pub fn new(eot: u32, max_steps: usize) -> Self {
+        Self {
+            tokens: Vec::new(),
+            probs: Vec::new(),
+            eot,
+            max_steps,
+            steps: 0,
+            sum_logprob: 0.0,
+            logprob_count: 0,
+            repeat_count: 0,
+        }
+    }
+
+    /// Tokens picked so far, the end of text included
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Tokens that may still be picked
+    pub fn remaining(&self) -> usize {
+        self.max_steps.saturating_sub(self.steps)
+    }
+
+    /// Take the main model's next token; false once decoding has ended
+    pub fn push(&mut self, token: u32, prob: f32) -> bool {
+        self.steps += 1;
+        if prob > 0.0 {
+            self.sum_logprob += (prob as f64).ln();
+            self.logprob_count += 1;
+        }
+        if token == self.eot {
+            return false;
+        }
+
+        if self.tokens.last() == Some(&token) {
+            self.repeat_count += 1;
+            if self.repeat_count >= MAX_REPEATS {
+                return false;
+            }
+        } else {
+            self.repeat_count = 0;
+        }
+
+        self.tokens.push(token);
+        self.probs.push(prob);
+        self.steps < self.max_steps
+    }
+
+    pub fn avg_logprob(&self) -> f64 {
+        if self.logprob_count > 0 {
+            self.sum_logprob / self.logprob_count as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Run speculative decoding into `output` until it ends
+///
+/// `propose(tokens, budget)` returns at most `budget` draft tokens following
+/// the emitted `tokens`, stopping after an end of text. `verify(tokens,
+/// proposals)` returns the main model's token and its probability after
+/// `tokens` followed by each leading run of `proposals` (`proposals.len() + 1`
+/// entries, computed in one pass).
+pub fn decode(
+    output: &mut GreedyOutput,
+    mut propose: impl FnMut(&[u32], usize) -> anyhow::Result<Vec<u32>>,
+    mut verify: impl FnMut(&[u32], &[u32]) -> anyhow::Result<Vec<(u32, f32)>>,
+) -> anyhow::Result<SpeculationStats> {
+    let mut stats = SpeculationStats::default();
+    while output.remaining() > 0 {
+        // Leave room for the main model's own next token
+        let proposals = propose(&output.tokens, DRAFT_TOKENS.min(output.remaining() - 1))?;
+        let verified = verify(&output.tokens, &proposals)?;
+
+        let choices: Vec<u32> = verified.iter().map(|&(token, _)| token).collect();
+        let accepted = accepted_len(&proposals, &choices);
+        stats.record(proposals.len(), accepted);
+        let more = verified[..=accepted]
+            .iter()
+            .all(|&(token, prob)| output.push(token, prob));
+        if !more {
+            break;
+        }
+    }
+    Ok(stats)
+}
+
+/// How well the draft predicted the main model over one window
+#[derive(Debug, Default)]
+pub struct SpeculationStats {
+    /// Main-model decoder passes
+    pub passes: usize,
+    pub proposed: usize,
+    pub accepted: usize,
+}
+
+impl SpeculationStats {
+    pub fn record(&mut self, proposed: usize, accepted: usize) {
+        self.passes += 1;
+        self.proposed += proposed;
+        self.accepted += accepted;
+    }
+
+    /// Share of draft tokens the main model agreed with
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.proposed == 0 {
+            0.0
+        } else {
+            self.accepted as f64 / self.proposed as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EOT: u32 = 100;
+
+    #[test]
+    fn test_accepted_len() {
+        assert_eq!(accepted_len(&[1, 2, 3], &[1, 2, 3, 4]), 3);
+        assert_eq!(accepted_len(&[1, 2, 3], &[1, 5, 3, 4]), 1);
+        assert_eq!(accepted_len(&[7], &[1, 2]), 0);
+        assert_eq!(accepted_len(&[], &[1]), 0);
+    }
+
+    #[test]
+    fn test_output_stops_like_greedy() {
+        // End of text is counted in the metrics but not emitted
+        let mut output = GreedyOutput::new(EOT, 10);
+        assert!(output.push(1, 0.5));
+        assert!(!output.push(EOT, 0.5));
+        assert_eq!(output.tokens, vec![1]);
+        assert!((output.avg_logprob() - 0.5f64.ln()).abs() < 1e-9);
+
+        // A token may appear three times in a row, not four
+        let mut output = GreedyOutput::new(EOT, 10);
+        assert!(output.push(2, 0.9) && output.push(2, 0.9) && output.push(2, 0.9));
+        assert!(!output.push(2, 0.9));
+        assert_eq!(output.tokens, vec![2, 2, 2]);
+
+        // The token budget ends decoding too
+        let mut output = GreedyOutput::new(EOT, 2);
+        assert!(output.push(1, 0.9));
+        assert!(!output.push(2, 0.9));
+        assert_eq!(output.tokens, vec![1, 2]);
+    }
+
+    /// Toy model: follows `script`, then ends the text
+    fn scripted(script: &[u32]) -> impl Fn(&[u32]) -> (u32, f32) + '_ {
+        |tokens| {
+            let token = script.get(tokens.len()).copied().unwrap_or(EOT);
+            (token, 0.5 + (tokens.len() % 5) as f32 / 10.0)
+        }
+    }
+
+    /// Plain greedy decoding with `model`
+    fn greedy(model: impl Fn(&[u32]) -> (u32, f32), max_steps: usize) -> GreedyOutput {
+        let mut output = GreedyOutput::new(EOT, max_steps);
+        loop {
+            let (token, prob) = model(&output.tokens);
+            if !output.push(token, prob) {
+                return output;
+            }
+        }
+    }
+
+    /// Speculative decoding with `model`, the draft guessing `draft` instead
+    fn speculative(
+        model: impl Fn(&[u32]) -> (u32, f32),
+        draft: impl Fn(&[u32]) -> u32,
+        max_steps: usize,
+    ) -> (GreedyOutput, SpeculationStats) {
+        let mut output = GreedyOutput::new(EOT, max_steps);
+        let stats = decode(
+            &mut output,
+            |tokens, budget| {
+                let mut tokens = tokens.to_vec();
+                let mut proposals = Vec::new();
+                while proposals.len() < budget {
+                    let token = draft(&tokens);
+                    proposals.push(token);
+                    tokens.push(token);
+                    if token == EOT {
+                        break;
+                    }
+                }
+                Ok(proposals)
+            },
+            |tokens, proposals| {
+                Ok((0..=proposals.len())
+                    .map(|k| model(&[tokens, &proposals[..k]].concat()))
+                    .collect())
+            },
+        )
+        .unwrap();
+        (output, stats)
+    }
+
+    #[test]
+    fn test_speculative_matches_greedy() {
+        let scripts: [&[u32]; 4] = [
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+            // Repeats are cut off after three
+            &[1, 2, 2, 2, 2, 2, 3],
+            &[],
+            &[9; 2],
+        ];
+        for script in scripts {
+            let model = scripted(script);
+            let perfect = |tokens: &[u32]| model(tokens).0;
+            // Wrong at every third position
+            let sloppy = |tokens: &[u32]| match tokens.len() % 3 {
+                2 => 42,
+                _ => model(tokens).0,
+            };
+            let early_end = |_: &[u32]| EOT;
+
+            for max_steps in [1, 4, 100] {
+                let expected = greedy(&model, max_steps);
+                for draft in [&perfect as &dyn Fn(&[u32]) -> u32, &sloppy, &early_end] {
+                    let (output, _) = speculative(&model, draft, max_steps);
+                    assert_eq!(output.tokens, expected.tokens, "{:?}", script);
+                    assert_eq!(output.probs, expected.probs);
+                    assert_eq!(output.steps(), expected.steps());
+                    assert_eq!(output.avg_logprob(), expected.avg_logprob());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_good_draft_saves_passes() {
+        let script: Vec<u32> = (1..=20).collect();
+        let model = scripted(&script);
+        let (output, stats) = speculative(&model, |tokens| model(tokens).0, 100);
+        assert_eq!(output.tokens, script);
+        // 21 tokens (end of text included), up to six per pass
+        assert_eq!(stats.passes, 4);
+        assert_eq!(stats.acceptance_rate(), 1.0);
+    }
+
+    #[test]
+    fn test_acceptance_rate() {
+        let mut stats = SpeculationStats::default();
+        assert_eq!(stats.acceptance_rate(), 0.0);
+        stats.record(5, 5);
+        stats.record(5, 0);
+        assert_eq!(stats.passes, 2);
+        assert_eq!(stats.acceptance_rate(), 0.5);
+    }
+}
@@ -0,0 +1,67 @@
+//! What the decoder is asked to do with the speech
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Whisper task token placed after the language token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Task {
+    /// Text in the spoken language
+    #[default]
+    Transcribe,
+    /// English text, whatever the spoken language (multilingual models only)
+    Translate,
+}
+
+impl Task {
+    /// Special token selecting the task
+    pub fn token(self) -> &'static str {
+        match self {
+            Task::Transcribe => "<|transcribe|>",
+            Task::Translate => "<|translate|>",
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Task::Transcribe => "transcribe",
+            Task::Translate => "translate",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Task {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "transcribe" => Ok(Task::Transcribe),
+            "translate" => Ok(Task::Translate),
+            other => Err(format!(
+                "unknown task '{}' (expected transcribe or translate)",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_names() {
+        assert_eq!("Translate".parse(), Ok(Task::Translate));
+        assert_eq!(Task::Transcribe.to_string().parse(), Ok(Task::Transcribe));
+        assert!("summarize".parse::<Task>().is_err());
+        assert_eq!(
+            serde_json::to_string(&Task::Translate).unwrap(),
+            "\"translate\""
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Whisper timestamp-token decoding
+//!
+//! In timestamp mode the decoder interleaves `<|t|>` tokens (0.02s steps from
+//! the start of the window) with text: `<|0.00|> Hello<|1.20|><|1.40|> world<|2.60|>`.
+//! [`TimestampRules`] constrains sampling to that grammar (the logit rules of
+//! the reference implementation) and [`parse_segments`] turns the tokens back
+//! into timed segments.
+
+use anyhow::Result;
+use std::ops::Range;
+
+use super::Segment;
+
+/// Seconds per timestamp token step
+pub const TIMESTAMP_STEP_SECS: f64 = 0.02;
+
+/// The first timestamp may not be later than this (in steps, 1.0s)
+const MAX_INITIAL_TIMESTAMP_STEPS: usize = 50;
+
+/// Logit constraints that keep timestamp tokens well-formed
+#[derive(Debug, Clone, Copy)]
+pub struct TimestampRules {
+    /// Id of `<|0.00|>`; every id from here on is a timestamp
+    pub timestamp_begin: u32,
+    /// Id of `<|endoftext|>`; every id below it is text
+    pub eot: u32,
+}
+
+impl TimestampRules {
+    fn is_timestamp(&self, token: u32) -> bool {
+        token >= self.timestamp_begin
+    }
+
+    /// Mask `logits` for the token following `generated` (sampled tokens only)
+    pub fn apply(&self, logits: &mut [f32], generated: &[u32]) {
+        let begin = (self.timestamp_begin as usize).min(logits.len());
+        let eot = (self.eot as usize).min(begin);
+        let mask = |logits: &mut [f32]| logits.fill(f32::NEG_INFINITY);
+
+        // The first token must be an early timestamp
+        if generated.is_empty() {
+            mask(&mut logits[..begin]);
+            let latest = (begin + MAX_INITIAL_TIMESTAMP_STEPS + 1).min(logits.len());
+            mask(&mut logits[latest..]);
+            return;
+        }
+
+        // Timestamps come in pairs (end of one segment, start of the next),
+        // except right before the end of text
+        let last_was_timestamp = generated.last().is_some_and(|&t| self.is_timestamp(t));
+        let penultimate_was_timestamp =
+            generated.len() < 2 || self.is_timestamp(generated[generated.len() - 2]);
+        if last_was_timestamp {
+            if penultimate_was_timestamp {
+                mask(&mut logits[begin..]);
+            } else {
+                mask(&mut logits[..eot]);
+            }
+        }
+
+        // Time never goes backwards; a new segment starts after the last one
+        if let Some(&last) = generated.iter().rev().find(|&&t| self.is_timestamp(t)) {
+            let earliest = if last_was_timestamp && !penultimate_was_timestamp {
+                last
+            } else {
+                last + 1
+            };
+            let earliest = (earliest as usize).min(logits.len());
+            mask(&mut logits[begin..earliest]);
+        }
+
+        // Prefer a timestamp when all timestamps together outweigh any text token
+        let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        if max == f32::NEG_INFINITY {
+            return;
+        }
+        let timestamp_mass: f32 = logits[begin..].iter().map(|&l| (l - max).exp()).sum();
+        let max_text = logits[..begin]
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max);
+        if timestamp_mass > 0.0 && timestamp_mass.ln() > max_text - max {
+            mask(&mut logits[..begin]);
+        }
+    }
+}
+
+/// Split the sampled tokens of one window into segments
+///
+/// Times are relative to the window start. Text without a start timestamp
+/// starts at the previous one, text after the last timestamp runs to
+/// `window_secs`. `decode` turns a run of text tokens into text.
+///
+/// Each segment comes with the range of its tokens among the text tokens
+/// (timestamps left out).
+pub fn parse_segments(
+    tokens: &[u32],
+    timestamp_begin: u32,
+    window_secs: f64,
+    mut decode: impl FnMut(&[u32]) -> Result<String>,
+) -> Result<Vec<(Segment, Range<usize>)>> {
+    let mut segments = Vec::new();
+    let mut start: Option<f64> = None;
+    let mut last_time = 0.0;
+    let mut text_tokens = Vec::new();
+    let mut text_start = 0;
+
+    for &token in tokens {
+        if token < timestamp_begin {
+            text_tokens.push(token);
+            continue;
+        }
+
+        let time = (token - timestamp_begin) as f64 * TIMESTAMP_STEP_SECS;
+        if text_tokens.is_empty() {
+            // Start of a segment (or a repeated start)
+            start = Some(time);
+        } else {
+            let text = decode(&text_tokens)?;
+            let range = text_start..text_start + text_tokens.len();
+            push_segment(
+                &mut segments,
+                start.unwrap_or(last_time),
+                time,
+                &text,
+                range,
+            );
+            text_start += text_tokens.len();
+            text_tokens.clear();
+            start = None;
+        }
+        last_time = time;
+    }
+
+    if !text_tokens.is_empty() {
+        let start = start.unwrap_or(last_time);
+        let range = text_start..text_start + text_tokens.len();
+        push_segment(
+            &mut segments,
+            start,
+            window_secs.max(start),
+            &decode(&text_tokens)?,
+            range,
+        );
+    }
+
+    Ok(segments)
+}
+
+fn push_segment(
+    segments: &mut Vec<(Segment, Range<usize>)>,
+    start: f64,
+    end: f64,
+    text: &str,
+    tokens: Range<usize>,
+) {
+    let text = text.trim();
+    if !text.is_empty() {
+        let segment = Segment {
+            start,
+            end: end.max(start),
+            text: text.to_string(),
+            words: Vec::new(),
+        };
+        segments.push((segment, tokens));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tiny vocabulary: 0-9 text, 10 EOT, 11-12 other specials, 13+ timestamps
+    const RULES: TimestampRules = TimestampRules {
+        timestamp_begin: 13,
+        eot: 10,
+    };
+    const VOCAB: usize = 13 + 100;
+
+    fn allowed(generated: &[u32]) -> Vec<usize> {
+        let mut logits = vec![0.0f32; VOCAB];
+        // Make text and EOT attractive so the timestamp preference doesn't kick in
+        logits[..=10].fill(10.0);
+        RULES.apply(&mut logits, generated);
+        (0..VOCAB).filter(|&i| logits[i].is_finite()).collect()
+    }
+
+    #[test]
+    fn test_first_token_is_early_timestamp() {
+        let allowed = allowed(&[]);
+        assert_eq!(allowed.first(), Some(&13));
+        assert_eq!(allowed.last(), Some(&(13 + 50)));
+    }
+
+    #[test]
+    fn test_timestamps_come_in_pairs() {
+        // After a start timestamp: text only
+        let after_start = allowed(&[13]);
+        assert!(after_start.contains(&3));
+
+        // After text and an end timestamp: only a timestamp or EOT
+        let after_end = allowed(&[13, 3, 40]);
+        assert!(!after_end.contains(&3));
+        assert!(after_end.contains(&10));
+        assert!(after_end.contains(&40) && !after_end.contains(&39));
+
+        // After an end/start pair: text only
+        let after_pair = allowed(&[13, 3, 40, 40]);
+        assert!(after_pair.iter().all(|&t| t < 13));
+    }
+
+    #[test]
+    fn test_timestamps_never_decrease() {
+        let allowed = allowed(&[13, 3, 4]);
+        assert!(!allowed.contains(&13));
+        assert!(allowed.contains(&14));
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        let decode = |tokens: &[u32]| -> Result<String> {
+            Ok(tokens.iter().map(|t| format!(" w{}", t)).collect())
+        };
+
+        // <|0.00|> w1 w2 <|1.00|><|1.20|> w3 <|2.00|> w4 (unterminated)
+        let tokens = [13, 1, 2, 63, 73, 3, 113, 4];
+        let segments = parse_segments(&tokens, 13, 5.0, decode).unwrap();
+
+        let expected = [
+            (0.0, 1.0, "w1 w2", 0..2),
+            (1.2, 2.0, "w3", 2..3),
+            (2.0, 5.0, "w4", 3..4),
+        ];
+        assert_eq!(segments.len(), expected.len());
+        for ((segment, tokens), (start, end, text, range)) in segments.iter().zip(expected) {
+            assert!((segment.start - start).abs() < 1e-9, "{:?}", segment);
+            assert!((segment.end - end).abs() < 1e-9, "{:?}", segment);
+            assert_eq!(segment.text, text);
+            assert_eq!(*tokens, range);
+        }
+    }
+}
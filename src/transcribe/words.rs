@@ -0,0 +1,340 @@
+//! Word-level timestamps from cross-attention alignment
+//!
+//! Follows the reference implementation: the decoder's alignment heads attend
+//! to the audio frames each text token was read from. Their weights are
+//! normalized, smoothed and averaged into one token × frame matrix, and the
+//! cheapest monotonic path through it (DTW) gives the frame where each token,
+//! and so each word, starts.
+
+use anyhow::Result;
+use std::ops::Range;
+
+/// Encoder frames per second of audio
+pub const FRAMES_PER_SECOND: f64 = 50.0;
+
+/// Median filter width applied along the frame axis
+const MEDIAN_FILTER_WIDTH: usize = 7;
+
+/// Punctuation attached to the following word
+const PREPEND_PUNCTUATION: &str = "\"'“¿([{-";
+
+/// Punctuation attached to the preceding word
+const APPEND_PUNCTUATION: &str = "\"'.。,，!！?？:：”)]}、";
+
+/// Languages written without spaces between words, split per character
+pub fn uses_spaces(language: &str) -> bool {
+    !matches!(language, "zh" | "ja" | "th" | "lo" | "my" | "yue")
+}
+
+/// Group text tokens into words
+///
+/// Returns each word's text (with its leading space) and token range.
+/// Tokens are first joined into complete characters, since one character can
+/// span several byte-level tokens.
+pub fn split_words(
+    tokens: &[u32],
+    spaces: bool,
+    mut decode: impl FnMut(&[u32]) -> Result<String>,
+) -> Result<Vec<(String, Range<usize>)>> {
+    let mut pieces: Vec<(String, Range<usize>)> = Vec::new();
+    let mut start = 0;
+    for end in 1..=tokens.len() {
+        let text = decode(&tokens[start..end])?;
+        if !text.contains('\u{fffd}') || end == tokens.len() {
+            pieces.push((text, start..end));
+            start = end;
+        }
+    }
+
+    if !spaces {
+        return Ok(merge_punctuation(pieces));
+    }
+
+    let mut words: Vec<(String, Range<usize>)> = Vec::new();
+    for (text, range) in pieces {
+        let new_word = text.starts_with(' ') || is_punctuation(text.trim());
+        match words.last_mut() {
+            Some((word, word_range)) if !new_word => {
+                word.push_str(&text);
+                word_range.end = range.end;
+            },
+            _ => words.push((text, range)),
+        }
+    }
+    Ok(merge_punctuation(words))
+}
+
+fn is_punctuation(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_punctuation())
+}
+
+/// Attach opening punctuation to the next word and closing punctuation to the previous one
+fn merge_punctuation(words: Vec<(String, Range<usize>)>) -> Vec<(String, Range<usize>)> {
+    let is_all = |text: &str, set: &str| {
+        let text = text.trim();
+        !text.is_empty() && text.chars().all(|c| set.contains(c))
+    };
+
+    let mut merged: Vec<(String, Range<usize>)> = Vec::new();
+    let mut pending: Option<(String, Range<usize>)> = None;
+    for (text, range) in words {
+        if is_all(&text, APPEND_PUNCTUATION) && !text.starts_with(' ') {
+            if let Some((previous, previous_range)) = merged.last_mut() {
+                previous.push_str(&text);
+                previous_range.end = range.end;
+                continue;
+            }
+        }
+
+        let (text, range) = match pending.take() {
+            Some((prefix, prefix_range)) => (prefix + &text, prefix_range.start..range.end),
+            None => (text, range),
+        };
+        if text.starts_with(' ') && is_all(&text, PREPEND_PUNCTUATION) {
+            pending = Some((text, range));
+        } else {
+            merged.push((text, range));
+        }
+    }
+    merged.extend(pending);
+    merged
+}
+
+/// Average attention heads into one alignment matrix
+///
+/// Each head is a row-major `rows × cols` (token × frame) matrix of attention
+/// weights. Every frame is standardized across tokens and every token's row
+/// median-filtered before averaging.
+pub fn alignment_matrix(heads: &[Vec<f32>], rows: usize, cols: usize) -> Vec<f32> {
+    let mut matrix = vec![0.0f32; rows * cols];
+    if heads.is_empty() {
+        return matrix;
+    }
+
+    for head in heads {
+        let mut head = head.clone();
+        for col in 0..cols {
+            let mean = (0..rows).map(|r| head[r * cols + col]).sum::<f32>() / rows as f32;
+            let variance = (0..rows)
+                .map(|r| (head[r * cols + col] - mean).powi(2))
+                .sum::<f32>()
+                / rows as f32;
+            let std = variance.sqrt().max(f32::EPSILON);
+            for row in 0..rows {
+                head[row * cols + col] = (head[row * cols + col] - mean) / std;
+            }
+        }
+
+        for (row, out) in head.chunks(cols).zip(matrix.chunks_mut(cols)) {
+            for (value, filtered) in out.iter_mut().zip(median_filter(row, MEDIAN_FILTER_WIDTH)) {
+                *value += filtered / heads.len() as f32;
+            }
+        }
+    }
+    matrix
+}
+
+/// Sliding median with reflected edges
+fn median_filter(values: &[f32], width: usize) -> Vec<f32> {
+    let half = width / 2;
+    if values.len() <= half {
+        return values.to_vec();
+    }
+
+    let reflect = |i: isize| -> f32 {
+        let last = values.len() as isize - 1;
+        let i = if i < 0 {
+            -i
+        } else if i > last {
+            2 * last - i
+        } else {
+            i
+        };
+        values[i as usize]
+    };
+
+    let mut window = Vec::with_capacity(width);
+    (0..values.len() as isize)
+        .map(|center| {
+            window.clear();
+            window.extend((center - half as isize..=center + half as isize).map(reflect));
+            window.sort_by(f32::total_cmp);
+            window[half]
+        })
+        .collect()
+}
+
+/// Cheapest monotonic path from the top-left to the bottom-right of `cost`
+///
+/// Returns the `(row, col)` cells visited, in order.
+pub fn dtw(cost: &[f32], rows: usize, cols: usize) -> Vec<(usize, usize)> {
+    let width = cols + 1;
+    let mut total = vec![f32::INFINITY; (rows + 1) * width];
+    let mut trace = vec![0u8; (rows + 1) * width];
+    total[0] = 0.0;
+
+    for col in 1..=cols {
+        for row in 1..=rows {
+            let diagonal = total[(row - 1) * width + col - 1];
+            let up = total[(row - 1) * width + col];
+            let left = total[row * width + col - 1];
+            let (best, step) = if diagonal < up && diagonal < left {
+                (diagonal, 0)
+            } else if up < diagonal && up < left {
+                (up, 1)
+            } else {
+                (left, 2)
+            };
+            total[row * width + col] = cost[(row - 1) * cols + col - 1] + best;
+            trace[row * width + col] = step;
+        }
+    }
+
+    // The origin is the only finite cell on the border, so every path ends there
+    let mut path = Vec::with_capacity(rows + cols);
+    let (mut row, mut col) = (rows, cols);
+    while row > 0 && col > 0 {
+        path.push((row - 1, col - 1));
+        match trace[row * width + col] {
+            0 => {
+                row -= 1;
+                col -= 1;
+            },
+            1 => row -= 1,
+            _ => col -= 1,
+        }
+    }
+    path.reverse();
+    path
+}
+
+/// Start and end seconds of each word
+///
+/// `matrix` has one row per text token plus one for the end of text.
+/// `words` are token ranges, in order.
+pub fn word_times(
+    matrix: &[f32],
+    rows: usize,
+    cols: usize,
+    words: &[Range<usize>],
+) -> Vec<(f64, f64)> {
+    if rows == 0 || cols == 0 {
+        return vec![(0.0, 0.0); words.len()];
+    }
+
+    let cost: Vec<f32> = matrix.iter().map(|v| -v).collect();
+    let path = dtw(&cost, rows, cols);
+
+    // Frame at which the path first reaches each token
+    let mut token_start = vec![None; rows];
+    for (row, col) in path {
+        token_start[row].get_or_insert(col);
+    }
+    let mut last = 0;
+    let token_start: Vec<f64> = token_start
+        .into_iter()
+        .map(|frame| {
+            last = frame.unwrap_or(last);
+            last as f64 / FRAMES_PER_SECOND
+        })
+        .collect();
+
+    let at = |token: usize| token_start[token.min(rows - 1)];
+    words
+        .iter()
+        .map(|range| (at(range.start), at(range.end)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_words_on_spaces() {
+        // " Hel" "lo" "," " world" "."
+        let pieces = [" Hel", "lo", ",", " world", "."];
+        let decode = |tokens: &[u32]| -> Result<String> {
+            Ok(tokens.iter().map(|&t| pieces[t as usize]).collect())
+        };
+
+        let split = split_words(&[0, 1, 2, 3, 4], true, decode).unwrap();
+        let texts: Vec<String> = split.iter().map(|(t, _)| t.clone()).collect();
+        assert_eq!(texts, words(&[" Hello,", " world."]));
+        assert_eq!(split[0].1, 0..3);
+        assert_eq!(split[1].1, 3..5);
+    }
+
+    #[test]
+    fn test_split_words_keeps_characters_whole() {
+        // Token 0 and 1 are the two halves of one character
+        let decode = |tokens: &[u32]| -> Result<String> {
+            Ok(match tokens {
+                [0] => "\u{fffd}".to_string(),
+                [0, 1] => "日".to_string(),
+                [2] => "本".to_string(),
+                _ => unreachable!("{:?}", tokens),
+            })
+        };
+
+        let split = split_words(&[0, 1, 2], false, decode).unwrap();
+        assert_eq!(
+            split,
+            vec![("日".to_string(), 0..2), ("本".to_string(), 2..3)]
+        );
+    }
+
+    #[test]
+    fn test_opening_punctuation_joins_next_word() {
+        let merged = merge_punctuation(vec![
+            (" (".to_string(), 0..1),
+            ("see".to_string(), 1..2),
+            (")".to_string(), 2..3),
+        ]);
+        assert_eq!(merged, vec![(" (see)".to_string(), 0..3)]);
+    }
+
+    #[test]
+    fn test_dtw_follows_cheap_diagonal() {
+        // 3 tokens over 6 frames, each token cheap on two frames
+        #[rustfmt::skip]
+        let cost = [
+            0.0, 0.0, 9.0, 9.0, 9.0, 9.0,
+            9.0, 9.0, 0.0, 0.0, 9.0, 9.0,
+            9.0, 9.0, 9.0, 9.0, 0.0, 0.0,
+        ];
+        let path = dtw(&cost, 3, 6);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(2, 5)));
+        assert!(path.contains(&(1, 2)) && path.contains(&(2, 4)));
+    }
+
+    #[test]
+    fn test_word_times_from_alignment() {
+        // Two one-token words plus the end-of-text row; attention peaks at
+        // frames 0-24, 25-49 and 50-59
+        let (rows, cols) = (3, 60);
+        let mut matrix = vec![-1.0f32; rows * cols];
+        for col in 0..cols {
+            let row = (col / 25).min(2);
+            matrix[row * cols + col] = 1.0;
+        }
+
+        let times = word_times(&matrix, rows, cols, &[0..1, 1..2]);
+        assert_eq!(times, vec![(0.0, 0.5), (0.5, 1.0)]);
+    }
+
+    #[test]
+    fn test_alignment_matrix_smooths_spikes() {
+        // One token row with a single-frame spike, one flat row
+        let (rows, cols) = (2, 9);
+        let mut head = vec![0.0f32; rows * cols];
+        head[4] = 1.0;
+        let matrix = alignment_matrix(&[head], rows, cols);
+        assert!(matrix.iter().all(|v| v.abs() < 1e-6), "{:?}", matrix);
+    }
+}
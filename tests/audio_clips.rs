@@ -0,0 +1,165 @@
+//! Integration tests for saved audio clips
+//!
+//! Saves synthetic recordings to a temp directory and checks naming, decoding
+//! and retention pruning.
+
+use hyprvoice::audio::{ClipFormat, ClipRetention, load_audio_file, prune_clips, save_clip};
+use std::f32::consts::PI;
+use std::fs::File;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+fn tone(secs: f32) -> Vec<f32> {
+    (0..(secs * 16000.0) as usize)
+        .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 16000.0).sin())
+        .collect()
+}
+
+/// Backdate a file so pruning sees it as older
+fn set_age(path: &Path, age: Duration) {
+    let file = File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() - age).unwrap();
+}
+
+#[test]
+fn test_flac_clip_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let samples = tone(1.0);
+
+    let path = save_clip(&samples, dir.path(), 16000, ClipFormat::Flac).unwrap();
+    assert_eq!(path.extension().unwrap(), "flac");
+
+    let decoded = load_audio_file(&path).unwrap();
+    assert_eq!(decoded.len(), samples.len());
+    let max_error = samples
+        .iter()
+        .zip(&decoded)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0f32, f32::max);
+    assert!(max_error < 1e-3, "16-bit quantization error: {}", max_error);
+}
+
+#[test]
+fn test_compressed_clips_are_smaller() {
+    let dir = tempfile::tempdir().unwrap();
+    let samples = tone(2.0);
+
+    let size = |format| {
+        let path = save_clip(&samples, dir.path(), 16000, format).unwrap();
+        std::fs::metadata(path).unwrap().len()
+    };
+    let wav32 = size(ClipFormat::Wav32);
+    let wav16 = size(ClipFormat::Wav16);
+    let flac = size(ClipFormat::Flac);
+
+    assert!(
+        wav16 < wav32 / 2 + 100,
+        "wav16 {} vs wav32 {}",
+        wav16,
+        wav32
+    );
+    assert!(flac < wav16, "flac {} vs wav16 {}", flac, wav16);
+}
+
+#[test]
+fn test_clip_names_are_unique() {
+    let dir = tempfile::tempdir().unwrap();
+    let samples = tone(0.1);
+
+    let paths: Vec<_> = (0..5)
+        .map(|_| save_clip(&samples, dir.path(), 16000, ClipFormat::Wav16).unwrap())
+        .collect();
+
+    let mut unique = paths.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), paths.len());
+}
+
+#[test]
+fn test_prune_by_count_keeps_newest() {
+    let dir = tempfile::tempdir().unwrap();
+    let samples = tone(0.1);
+
+    let paths: Vec<_> = (0..4)
+        .map(|_| save_clip(&samples, dir.path(), 16000, ClipFormat::Flac).unwrap())
+        .collect();
+    for (i, path) in paths.iter().enumerate() {
+        set_age(path, Duration::from_secs(60 * (4 - i as u64)));
+    }
+
+    let retention = ClipRetention {
+        max_count: Some(2),
+        ..Default::default()
+    };
+
+    let report = prune_clips(dir.path(), &retention, true).unwrap();
+    assert_eq!(report.removed, 2);
+    assert!(paths.iter().all(|p| p.exists()), "Dry run must not delete");
+
+    let report = prune_clips(dir.path(), &retention, false).unwrap();
+    assert_eq!((report.removed, report.kept), (2, 2));
+    assert!(!paths[0].exists() && !paths[1].exists());
+    assert!(paths[2].exists() && paths[3].exists());
+}
+
+#[test]
+fn test_prune_by_age_and_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let samples = tone(0.5);
+
+    let old = save_clip(&samples, dir.path(), 16000, ClipFormat::Wav16).unwrap();
+    set_age(&old, Duration::from_secs(3 * 24 * 60 * 60));
+    let recent = save_clip(&samples, dir.path(), 16000, ClipFormat::Wav16).unwrap();
+
+    let report = prune_clips(
+        dir.path(),
+        &ClipRetention {
+            max_age: Some(Duration::from_secs(24 * 60 * 60)),
+            ..Default::default()
+        },
+        false,
+    )
+    .unwrap();
+    assert_eq!(report.removed, 1);
+    assert!(!old.exists() && recent.exists());
+
+    // A second clip pushes the directory over a limit that fits only one
+    let clip_size = std::fs::metadata(&recent).unwrap().len();
+    set_age(&recent, Duration::from_secs(60));
+    let newest = save_clip(&samples, dir.path(), 16000, ClipFormat::Wav16).unwrap();
+
+    let report = prune_clips(
+        dir.path(),
+        &ClipRetention {
+            max_total_bytes: Some(clip_size + clip_size / 2),
+            ..Default::default()
+        },
+        false,
+    )
+    .unwrap();
+    assert_eq!(report.removed, 1);
+    assert!(!recent.exists() && newest.exists());
+}
+
+#[test]
+fn test_prune_ignores_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "keep me").unwrap();
+    let clip = save_clip(&tone(0.1), dir.path(), 16000, ClipFormat::Flac).unwrap();
+
+    let report = prune_clips(
+        dir.path(),
+        &ClipRetention {
+            max_count: Some(0),
+            ..Default::default()
+        },
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(report.removed, 1);
+    assert!(!clip.exists());
+    assert!(notes.exists());
+}
@@ -0,0 +1,109 @@
+//! Integration tests for sample format conversion and channel downmixing
+//!
+//! Uses synthetic interleaved buffers, so no audio device is required.
+
+use hyprvoice::audio::{downmix_to_mono, samples_to_f32};
+
+/// Interleave per-channel signals into a single buffer
+fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+    let frames = channels[0].len();
+    (0..frames)
+        .flat_map(|i| channels.iter().map(move |ch| ch[i]))
+        .collect()
+}
+
+#[test]
+fn test_mono_passthrough() {
+    let samples = vec![0.1, -0.2, 0.3];
+    assert_eq!(downmix_to_mono(&samples, 1, None), samples);
+}
+
+#[test]
+fn test_stereo_average() {
+    let samples = interleave(&[vec![1.0, 0.5], vec![0.0, -0.5]]);
+    assert_eq!(downmix_to_mono(&samples, 2, None), vec![0.5, 0.0]);
+}
+
+#[test]
+fn test_six_channel_average() {
+    let channels: Vec<Vec<f32>> = (0..6).map(|c| vec![c as f32 * 0.1; 4]).collect();
+    let mono = downmix_to_mono(&interleave(&channels), 6, None);
+
+    // Mean of 0.0..0.5 in 0.1 steps
+    assert_eq!(mono.len(), 4);
+    for sample in mono {
+        assert!((sample - 0.25).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_select_single_channel() {
+    let channels = vec![
+        vec![0.0; 3],
+        vec![0.0; 3],
+        vec![0.7, 0.8, 0.9],
+        vec![0.0; 3],
+    ];
+    let mono = downmix_to_mono(&interleave(&channels), 4, Some(2));
+    assert_eq!(mono, vec![0.7, 0.8, 0.9]);
+}
+
+#[test]
+fn test_partial_frame_dropped() {
+    let samples = vec![0.2, 0.4, 0.6, 0.8, 1.0];
+    assert_eq!(downmix_to_mono(&samples, 2, None).len(), 2);
+}
+
+#[test]
+fn test_integer_formats_to_f32() {
+    let mut out = Vec::new();
+
+    samples_to_f32(&[i16::MIN, 0, i16::MAX], &mut out);
+    assert_eq!(out[0], -1.0);
+    assert_eq!(out[1], 0.0);
+    assert!((out[2] - 1.0).abs() < 1e-4);
+
+    // Unsigned formats are centred on the midpoint
+    samples_to_f32(&[0u16, 32768, u16::MAX], &mut out);
+    assert_eq!(out[0], -1.0);
+    assert_eq!(out[1], 0.0);
+    assert!((out[2] - 1.0).abs() < 1e-4);
+
+    samples_to_f32(&[u8::MIN, 128u8], &mut out);
+    assert_eq!(out, vec![-1.0, 0.0]);
+
+    samples_to_f32(&[i32::MIN, 0], &mut out);
+    assert_eq!(out, vec![-1.0, 0.0]);
+}
+
+#[test]
+fn test_i24_to_f32() {
+    let mut out = Vec::new();
+    let samples = [
+        cpal::I24::new(-(1 << 23)).unwrap(),
+        cpal::I24::new(0).unwrap(),
+        cpal::I24::new(1 << 22).unwrap(),
+    ];
+    samples_to_f32(&samples, &mut out);
+    assert_eq!(out, vec![-1.0, 0.0, 0.5]);
+}
+
+#[test]
+fn test_float_formats_to_f32() {
+    let mut out = vec![9.0; 8];
+    samples_to_f32(&[0.25f64, -0.5], &mut out);
+    // Output buffer is replaced, not appended to
+    assert_eq!(out, vec![0.25, -0.5]);
+}
+
+#[test]
+fn test_converted_multichannel_downmix() {
+    // Stereo i16 device: left at half scale, right silent
+    let raw: Vec<i16> = [16384i16, 0].repeat(4);
+    let mut converted = Vec::new();
+    samples_to_f32(&raw, &mut converted);
+
+    let mono = downmix_to_mono(&converted, 2, None);
+    assert_eq!(mono, vec![0.25; 4]);
+    assert_eq!(downmix_to_mono(&converted, 2, Some(0)), vec![0.5; 4]);
+}
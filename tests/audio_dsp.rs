@@ -0,0 +1,203 @@
+//! Integration tests for the audio preprocessing chain
+//!
+//! Each stage is enabled on its own and checked against synthetic signals.
+
+use hyprvoice::audio::{DspOptions, Normalization, apply_dsp};
+use std::f32::consts::PI;
+
+const SAMPLE_RATE: u32 = 16000;
+
+/// Generate a sine wave at specified frequency and amplitude
+fn sine(freq: f32, amplitude: f32, duration_secs: f32) -> Vec<f32> {
+    let num_samples = (duration_secs * SAMPLE_RATE as f32) as usize;
+    (0..num_samples)
+        .map(|i| amplitude * (2.0 * PI * freq * i as f32 / SAMPLE_RATE as f32).sin())
+        .collect()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|&s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()))
+}
+
+fn mean(samples: &[f32]) -> f32 {
+    samples.iter().sum::<f32>() / samples.len() as f32
+}
+
+fn dbfs(level: f32) -> f32 {
+    20.0 * level.log10()
+}
+
+fn process(mut samples: Vec<f32>, options: DspOptions) -> Vec<f32> {
+    apply_dsp(&mut samples, SAMPLE_RATE, &options);
+    samples
+}
+
+#[test]
+fn test_default_options_leave_audio_untouched() {
+    let input = sine(440.0, 0.3, 0.5);
+    assert_eq!(process(input.clone(), DspOptions::default()), input);
+}
+
+#[test]
+fn test_high_pass_removes_dc_offset() {
+    let input: Vec<f32> = sine(440.0, 0.2, 1.0).iter().map(|s| s + 0.3).collect();
+    let output = process(
+        input,
+        DspOptions {
+            high_pass_hz: Some(80.0),
+            ..Default::default()
+        },
+    );
+
+    // Ignore the filter's settling time
+    let settled = &output[1600..];
+    assert!(mean(settled).abs() < 0.005, "DC remains: {}", mean(settled));
+    assert!(
+        (rms(settled) - 0.2 / 2f32.sqrt()).abs() < 0.01,
+        "Speech-band tone should pass, RMS {}",
+        rms(settled)
+    );
+}
+
+#[test]
+fn test_high_pass_attenuates_rumble() {
+    let output = process(
+        sine(20.0, 0.5, 1.0),
+        DspOptions {
+            high_pass_hz: Some(80.0),
+            ..Default::default()
+        },
+    );
+
+    // 20Hz is two octaves below cutoff: ~24dB down for a 2nd-order filter
+    let attenuation = dbfs(rms(&output[3200..]) / (0.5 / 2f32.sqrt()));
+    assert!(attenuation < -20.0, "Rumble only reduced {}dB", attenuation);
+}
+
+#[test]
+fn test_peak_normalization() {
+    let output = process(
+        sine(440.0, 0.1, 0.5),
+        DspOptions {
+            normalize: Normalization::Peak,
+            ..Default::default()
+        },
+    );
+    assert!(
+        (dbfs(peak(&output)) + 1.0).abs() < 0.1,
+        "Peak at {}dBFS",
+        dbfs(peak(&output))
+    );
+}
+
+#[test]
+fn test_rms_normalization_with_target() {
+    let output = process(
+        sine(440.0, 0.01, 0.5),
+        DspOptions {
+            normalize: Normalization::Rms,
+            normalize_target_db: Some(-20.0),
+            ..Default::default()
+        },
+    );
+    assert!(
+        (dbfs(rms(&output)) + 20.0).abs() < 0.1,
+        "RMS at {}dBFS",
+        dbfs(rms(&output))
+    );
+}
+
+#[test]
+fn test_rms_normalization_never_clips() {
+    // A single loud click limits the gain available to a quiet signal
+    let mut input = sine(440.0, 0.01, 0.5);
+    input[100] = 0.9;
+    let output = process(
+        input,
+        DspOptions {
+            normalize: Normalization::Rms,
+            ..Default::default()
+        },
+    );
+    assert!(peak(&output) <= 1.0 + 1e-4, "Clipped at {}", peak(&output));
+}
+
+#[test]
+fn test_normalization_of_silence_is_noop() {
+    let output = process(
+        vec![0.0; 1600],
+        DspOptions {
+            normalize: Normalization::Peak,
+            ..Default::default()
+        },
+    );
+    assert!(output.iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_agc_evens_out_levels() {
+    // Quiet speaker followed by a loud one
+    let mut input = sine(300.0, 0.02, 3.0);
+    input.extend(sine(300.0, 0.8, 3.0));
+
+    let output = process(
+        input,
+        DspOptions {
+            agc: true,
+            ..Default::default()
+        },
+    );
+
+    // Compare the settled ends of each section
+    let quiet = dbfs(rms(&output[32000..48000]));
+    let loud = dbfs(rms(&output[80000..96000]));
+    assert!(
+        (quiet - loud).abs() < 3.0,
+        "quiet {}dB vs loud {}dB",
+        quiet,
+        loud
+    );
+    assert!(
+        (quiet + 20.0).abs() < 3.0,
+        "Expected ~-20dBFS, got {}",
+        quiet
+    );
+    assert!(peak(&output) <= 1.0);
+}
+
+#[test]
+fn test_agc_does_not_boost_background_noise() {
+    let input = sine(300.0, 0.001, 1.0);
+    let output = process(
+        input.clone(),
+        DspOptions {
+            agc: true,
+            ..Default::default()
+        },
+    );
+    assert!((rms(&output) - rms(&input)).abs() < 1e-5);
+}
+
+#[test]
+fn test_noise_gate_mutes_quiet_sections() {
+    let mut input = sine(300.0, 0.002, 1.0); // -57dBFS hiss
+    input.extend(sine(300.0, 0.3, 1.0));
+    input.extend(sine(300.0, 0.002, 1.0));
+
+    let output = process(
+        input,
+        DspOptions {
+            noise_gate_db: Some(-50.0),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(peak(&output[..16000]), 0.0, "Leading hiss should be muted");
+    assert!((rms(&output[16000..32000]) - rms(&sine(300.0, 0.3, 1.0))).abs() < 0.01);
+    // Hold keeps the gate open briefly, then it closes
+    assert_eq!(peak(&output[40000..]), 0.0, "Trailing hiss should be muted");
+}
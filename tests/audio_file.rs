@@ -0,0 +1,106 @@
+//! Integration tests for audio file decoding
+//!
+//! Writes synthetic WAV files to a temp directory and checks they come back
+//! as 16kHz mono.
+
+use hyprvoice::audio::load_audio_file;
+use std::f32::consts::PI;
+use std::path::Path;
+
+fn write_wav(path: &Path, spec: hound::WavSpec, secs: f32) {
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let frames = (secs * spec.sample_rate as f32) as usize;
+
+    for i in 0..frames {
+        let value = 0.5 * (2.0 * PI * 440.0 * i as f32 / spec.sample_rate as f32).sin();
+        for _ in 0..spec.channels {
+            match spec.sample_format {
+                hound::SampleFormat::Float => writer.write_sample(value).unwrap(),
+                hound::SampleFormat::Int => writer
+                    .write_sample((value * i16::MAX as f32) as i16)
+                    .unwrap(),
+            }
+        }
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_load_float_wav_like_saved_clips() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("clip.wav");
+    write_wav(
+        &path,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        },
+        1.0,
+    );
+
+    let samples = load_audio_file(&path).unwrap();
+    assert_eq!(samples.len(), 16000);
+    assert!((samples[4] - 0.5 * (2.0 * PI * 440.0 * 4.0 / 16000.0).sin()).abs() < 1e-6);
+}
+
+#[test]
+fn test_load_int_stereo_wav_resamples() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("stereo.wav");
+    write_wav(
+        &path,
+        hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+        2.0,
+    );
+
+    let samples = load_audio_file(&path).unwrap();
+    assert!(
+        samples.len() > 31000 && samples.len() < 33500,
+        "Expected ~32000 samples, got {}",
+        samples.len()
+    );
+
+    let peak = samples.iter().fold(0.0f32, |m, &s| m.max(s.abs()));
+    assert!(peak > 0.4 && peak < 0.6, "Unexpected peak {}", peak);
+}
+
+#[test]
+fn test_load_near_16khz_wav_resamples() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("near.wav");
+    write_wav(
+        &path,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: 15000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+        2.0,
+    );
+
+    // 2s at 15kHz must still become 2s at 16kHz, not 1.875s
+    let samples = load_audio_file(&path).unwrap();
+    assert!(
+        samples.len() > 31500 && samples.len() < 32500,
+        "Expected ~32000 samples, got {}",
+        samples.len()
+    );
+}
+
+#[test]
+fn test_load_invalid_file_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.wav");
+    std::fs::write(&path, b"not audio at all").unwrap();
+
+    assert!(load_audio_file(&path).is_err());
+    assert!(load_audio_file(&dir.path().join("missing.wav")).is_err());
+}
@@ -0,0 +1,185 @@
+//! Integration tests for raw PCM input
+//!
+//! Feeds in-memory byte streams through `PcmSource` and the recorder, as
+//! `hyprvoice transcribe --stdin` does with real pipes.
+
+use hyprvoice::audio::{
+    AudioSource, PcmFormat, PcmSource, PcmSpec, RecorderOptions, VadOptions, record_segments,
+};
+use std::f32::consts::PI;
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
+fn spec(sample_rate: u32, channels: u16, format: PcmFormat) -> PcmSpec {
+    PcmSpec {
+        sample_rate,
+        channels,
+        format,
+    }
+}
+
+fn source(bytes: Vec<u8>, spec: PcmSpec) -> PcmSource {
+    PcmSource::new("test", Box::new(Cursor::new(bytes)), spec).unwrap()
+}
+
+/// Run a source to EOF and collect the raw interleaved samples
+fn collect(mut source: PcmSource) -> Vec<f32> {
+    let mut out = Vec::new();
+    source
+        .run(&mut |data| out.extend_from_slice(data), &|| false)
+        .unwrap();
+    out
+}
+
+/// 16-bit mono PCM: `tone_secs` of a 300Hz tone followed by `silence_secs` of silence
+fn s16_speech(tone_secs: f32, silence_secs: f32) -> Vec<u8> {
+    let tone = (0..(tone_secs * 16000.0) as usize)
+        .map(|i| (0.5 * (2.0 * PI * 300.0 * i as f32 / 16000.0).sin() * 32767.0) as i16);
+    let silence = std::iter::repeat_n(0i16, (silence_secs * 16000.0) as usize);
+    tone.chain(silence).flat_map(i16::to_le_bytes).collect()
+}
+
+#[test]
+fn test_pcm_format_parsing() {
+    assert_eq!("s16le".parse::<PcmFormat>().unwrap(), PcmFormat::S16le);
+    assert_eq!("F32".parse::<PcmFormat>().unwrap(), PcmFormat::F32le);
+    assert!("s16be".parse::<PcmFormat>().is_err());
+    assert_eq!(PcmFormat::S24le.to_string(), "s24le");
+}
+
+#[test]
+fn test_pcm_decodes_each_format() {
+    let cases: Vec<(PcmFormat, Vec<u8>)> = vec![
+        (PcmFormat::U8, vec![128, 192, 64]),
+        (
+            PcmFormat::S16le,
+            [0i16, 16384, -16384]
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+        ),
+        (
+            PcmFormat::S24le,
+            vec![0, 0, 0, 0x00, 0x00, 0x40, 0x00, 0x00, 0xC0],
+        ),
+        (
+            PcmFormat::S32le,
+            [0i32, 1 << 30, -(1 << 30)]
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+        ),
+        (
+            PcmFormat::F32le,
+            [0.0f32, 0.5, -0.5]
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+        ),
+    ];
+
+    for (format, bytes) in cases {
+        let samples = collect(source(bytes, spec(16000, 1, format)));
+        assert_eq!(samples, vec![0.0, 0.5, -0.5], "{}", format);
+    }
+}
+
+/// Reader that hands out one byte per call, splitting every frame
+struct Trickle(Cursor<Vec<u8>>);
+
+impl Read for Trickle {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(1);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+#[test]
+fn test_pcm_reassembles_split_frames() {
+    let bytes: Vec<u8> = [1000i16, -1000, 2000, -2000]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let source = PcmSource::new(
+        "trickle",
+        Box::new(Trickle(Cursor::new(bytes))),
+        spec(16000, 2, PcmFormat::S16le),
+    )
+    .unwrap();
+
+    let expected: Vec<f32> = [1000.0, -1000.0, 2000.0, -2000.0]
+        .iter()
+        .map(|s| s / 32768.0)
+        .collect();
+    assert_eq!(collect(source), expected);
+}
+
+#[test]
+fn test_pcm_rejects_invalid_layout() {
+    let result = PcmSource::new(
+        "test",
+        Box::new(Cursor::new(Vec::new())),
+        spec(16000, 0, PcmFormat::S16le),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_stream_resampled_to_one_recording() {
+    // 1s of 48kHz stereo f32 becomes a single 1s 16kHz mono recording
+    let bytes: Vec<u8> = (0..48000)
+        .flat_map(|i| {
+            let v = 0.5 * (2.0 * PI * 440.0 * i as f32 / 48000.0).sin();
+            [v, v]
+        })
+        .flat_map(f32::to_le_bytes)
+        .collect();
+    let mut source = source(bytes, spec(48000, 2, PcmFormat::F32le));
+
+    let mut recordings = Vec::new();
+    record_segments(&mut source, &RecorderOptions::default(), |r| {
+        recordings.push(r);
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(recordings.len(), 1);
+    assert_eq!(recordings[0].samples.len(), 16000);
+    assert!(recordings[0].source_ended);
+}
+
+#[test]
+fn test_chunked_stream_splits_on_pauses() {
+    let mut bytes = s16_speech(0.0, 0.5);
+    bytes.extend(s16_speech(1.0, 1.0));
+    bytes.extend(s16_speech(0.8, 2.0));
+    // Trailing utterance cut off by EOF without a pause
+    bytes.extend(s16_speech(0.6, 0.0));
+
+    let options = RecorderOptions {
+        vad: Some(VadOptions {
+            silence: Duration::from_millis(500),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut lengths = Vec::new();
+    record_segments(
+        &mut source(bytes, spec(16000, 1, PcmFormat::S16le)),
+        &options,
+        |r| {
+            lengths.push(r.samples.len());
+            Ok(())
+        },
+    )
+    .unwrap();
+
+    // A segment ends once 0.5s of silence follows speech (read in 1024-frame chunks);
+    // untrimmed, so it also keeps the silence left over from the previous pause
+    let expected = [32000, 28800, 33600];
+    assert_eq!(lengths.len(), 3, "segments: {:?}", lengths);
+    for (length, expected) in lengths.iter().zip(expected) {
+        assert!(length.abs_diff(expected) <= 1024, "segments: {:?}", lengths);
+    }
+}
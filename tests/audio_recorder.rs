@@ -0,0 +1,443 @@
+//! Integration tests for the audio recorder
+//!
+//! Uses synthetic sources plugged in through the `AudioSource` trait, so no
+//! microphone is required.
+
+use anyhow::Result;
+use hyprvoice::audio::{
+    AudioGap, AudioRecorder, AudioSource, DspOptions, Normalization, RecorderOptions, TrimOptions,
+    VadOptions, stable_prefix,
+};
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
+/// Interleaved sine tone, optionally finite, delivered in 10ms chunks
+struct ToneSource {
+    sample_rate: u32,
+    channels: u16,
+    total_frames: Option<usize>,
+    /// Emit silence from this frame on (emulates the speaker pausing)
+    silent_after: Option<usize>,
+    /// Delay between chunks (emulates a realtime device)
+    chunk_delay: Duration,
+    /// Reported as lost audio (emulates a device dropping out)
+    gaps: Vec<AudioGap>,
+}
+
+impl AudioSource for ToneSource {
+    fn name(&self) -> String {
+        "synthetic tone".to_string()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        let chunk_frames = self.sample_rate as usize / 100;
+        let mut frame = 0usize;
+
+        while !should_stop() {
+            if self.total_frames.is_some_and(|total| frame >= total) {
+                break;
+            }
+
+            let mut chunk = Vec::with_capacity(chunk_frames * self.channels as usize);
+            for _ in 0..chunk_frames {
+                let t = frame as f32 / self.sample_rate as f32;
+                let value = if self.silent_after.is_some_and(|at| frame >= at) {
+                    0.0
+                } else {
+                    0.5 * (2.0 * PI * 440.0 * t).sin()
+                };
+                for _ in 0..self.channels {
+                    chunk.push(value);
+                }
+                frame += 1;
+            }
+            sink(&chunk);
+
+            if !self.chunk_delay.is_zero() {
+                std::thread::sleep(self.chunk_delay);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn take_gaps(&mut self) -> Vec<AudioGap> {
+        std::mem::take(&mut self.gaps)
+    }
+}
+
+/// Fixed 16kHz mono clip delivered in 10ms chunks
+struct BufferSource(Vec<f32>);
+
+impl AudioSource for BufferSource {
+    fn name(&self) -> String {
+        "synthetic clip".to_string()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        16000
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        for chunk in self.0.chunks(160) {
+            if should_stop() {
+                break;
+            }
+            sink(chunk);
+        }
+        Ok(())
+    }
+}
+
+/// Clip that keeps the recording open after its audio, until stopped
+struct HeldBufferSource(BufferSource);
+
+impl AudioSource for HeldBufferSource {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.0.sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        self.0.channels()
+    }
+
+    fn run(&mut self, sink: &mut dyn FnMut(&[f32]), should_stop: &dyn Fn() -> bool) -> Result<()> {
+        self.0.run(sink, should_stop)?;
+        while !should_stop() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        Ok(())
+    }
+}
+
+fn tone(
+    sample_rate: u32,
+    channels: u16,
+    total_secs: Option<f32>,
+    delay_ms: u64,
+) -> Box<ToneSource> {
+    Box::new(ToneSource {
+        sample_rate,
+        channels,
+        total_frames: total_secs.map(|s| (s * sample_rate as f32) as usize),
+        silent_after: None,
+        chunk_delay: Duration::from_millis(delay_ms),
+        gaps: Vec::new(),
+    })
+}
+
+#[test]
+fn test_recorder_stops_at_max_duration() {
+    let recorder = AudioRecorder::start(
+        tone(48000, 2, None, 0),
+        RecorderOptions {
+            max_duration: Some(Duration::from_secs(2)),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let samples = recorder.wait().unwrap().samples;
+
+    // 2s of 48kHz stereo should come back as ~2s of 16kHz mono
+    assert!(
+        samples.len() > 31000 && samples.len() < 33500,
+        "Expected ~32000 samples, got {}",
+        samples.len()
+    );
+}
+
+#[test]
+fn test_recorder_ends_when_source_exhausted() {
+    let recorder =
+        AudioRecorder::start(tone(16000, 1, Some(1.0), 0), RecorderOptions::default()).unwrap();
+
+    let samples = recorder.wait().unwrap().samples;
+    assert_eq!(samples.len(), 16000);
+}
+
+#[test]
+fn test_recorder_stop_returns_audio() {
+    let recorder =
+        AudioRecorder::start(tone(16000, 1, None, 1), RecorderOptions::default()).unwrap();
+
+    std::thread::sleep(Duration::from_millis(100));
+    let samples = recorder.stop().unwrap().samples;
+
+    assert!(!samples.is_empty(), "Stopped recording should return audio");
+}
+
+#[test]
+fn test_recorder_trailing_audio_after_stop() {
+    let recorder = AudioRecorder::start(
+        tone(16000, 1, None, 1),
+        RecorderOptions {
+            trailing: Duration::from_millis(200),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    let _ = recorder.stop().unwrap();
+    assert!(
+        start.elapsed() >= Duration::from_millis(200),
+        "Stop should wait for trailing audio"
+    );
+}
+
+#[test]
+fn test_recorder_cancel_is_immediate() {
+    let recorder = AudioRecorder::start(
+        tone(16000, 1, None, 1),
+        RecorderOptions {
+            trailing: Duration::from_secs(5),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    recorder.cancel();
+    assert!(
+        start.elapsed() < Duration::from_secs(1),
+        "Cancel should skip the trailing buffer"
+    );
+}
+
+#[test]
+fn test_recorder_vad_stops_after_silence() {
+    let mut source = tone(16000, 1, Some(30.0), 0);
+    source.silent_after = Some(8000);
+
+    let recorder = AudioRecorder::start(
+        source,
+        RecorderOptions {
+            vad: Some(VadOptions {
+                silence: Duration::from_millis(300),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let recording = recorder.wait().unwrap();
+    assert!(!recording.no_speech);
+    let samples = recording.samples;
+
+    // 0.5s of speech plus ~0.3s of silence, far short of the 30s source
+    assert!(
+        samples.len() > 12000 && samples.len() < 14000,
+        "Expected ~0.8s of audio, got {} samples",
+        samples.len()
+    );
+}
+
+#[test]
+fn test_recorder_vad_discards_silence_only() {
+    let mut source = tone(16000, 1, Some(2.0), 0);
+    source.silent_after = Some(0);
+
+    let recorder = AudioRecorder::start(
+        source,
+        RecorderOptions {
+            vad: Some(VadOptions::default()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let recording = recorder.wait().unwrap();
+    assert!(recording.samples.is_empty());
+    assert!(recording.no_speech);
+}
+
+#[test]
+fn test_recorder_vad_gives_up_without_speech() {
+    let mut source = tone(16000, 1, Some(30.0), 0);
+    source.silent_after = Some(0);
+
+    let recorder = AudioRecorder::start(
+        source,
+        RecorderOptions {
+            vad: Some(VadOptions {
+                no_speech: Some(Duration::from_millis(500)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // Stopped by the timeout rather than by the 30s source running out
+    let recording = recorder.wait().unwrap();
+    assert!(recording.samples.is_empty());
+    assert!(recording.no_speech);
+    assert!(!recording.source_ended);
+}
+
+#[test]
+fn test_recorder_rejects_missing_channel() {
+    let recorder = AudioRecorder::start(
+        tone(16000, 2, Some(0.5), 0),
+        RecorderOptions {
+            channel: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let err = recorder.wait().unwrap_err();
+    assert!(err.to_string().contains("channel 3"), "{}", err);
+}
+
+#[test]
+fn test_recorder_reports_input_stats() {
+    let recording = AudioRecorder::start(tone(16000, 1, Some(0.5), 0), RecorderOptions::default())
+        .unwrap()
+        .wait()
+        .unwrap();
+
+    // 0.5 amplitude tone: healthy level, no clipping
+    assert!((recording.stats.peak_db() + 6.0).abs() < 0.1);
+    assert!(!recording.stats.is_silent());
+    assert!(recording.stats.warnings().is_empty());
+
+    let mut source = tone(16000, 1, Some(0.5), 0);
+    source.silent_after = Some(0);
+    let silent = AudioRecorder::start(source, RecorderOptions::default())
+        .unwrap()
+        .wait()
+        .unwrap();
+    assert!(silent.stats.is_silent());
+    assert!(silent.stats.warnings()[0].contains("muted or silent"));
+}
+
+#[test]
+fn test_recorder_reports_lost_audio() {
+    let gap = AudioGap {
+        position: Duration::from_millis(400),
+        lost: Duration::from_millis(1500),
+        resumed_on: Some("fallback mic".to_string()),
+    };
+    let mut source = tone(16000, 1, Some(1.0), 0);
+    source.gaps = vec![gap.clone()];
+
+    let recording = AudioRecorder::start(source, RecorderOptions::default())
+        .unwrap()
+        .wait()
+        .unwrap();
+
+    assert_eq!(recording.gaps, vec![gap]);
+    let warnings = recording.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].contains("1.5s of audio missing"),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn test_recorder_exposes_live_audio() {
+    let recorder = AudioRecorder::start(
+        tone(16000, 1, None, 1),
+        RecorderOptions {
+            live_audio: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    while recorder.captured() < Duration::from_millis(500) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        recorder.recent_audio(Duration::from_millis(250)).len(),
+        4000
+    );
+    let live = recorder.live_audio().len();
+    assert!(live >= 8000);
+    // Stopping right away may add nothing after the snapshot
+    let samples = recorder.stop().unwrap().samples;
+    assert!(samples.len() >= live, "got {} samples", samples.len());
+}
+
+#[test]
+fn test_recorder_live_audio_disabled_by_default() {
+    let recorder =
+        AudioRecorder::start(tone(16000, 1, None, 1), RecorderOptions::default()).unwrap();
+
+    while recorder.captured() < Duration::from_millis(100) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(recorder.live_audio().is_empty());
+    recorder.cancel();
+}
+
+#[test]
+fn test_stable_prefix_matches_final_audio() {
+    let options = RecorderOptions {
+        trim: Some(TrimOptions::default()),
+        dsp: DspOptions {
+            high_pass_hz: Some(80.0),
+            agc: true,
+            ..Default::default()
+        },
+        live_audio: true,
+        ..Default::default()
+    };
+
+    // 0.5s of silence, then tone: trimming moves the start
+    let mut samples = vec![0.0; 8000];
+    samples.extend((0..40000).map(|i| 0.3 * (2.0 * PI * 300.0 * i as f32 / 16000.0).sin()));
+    let recorder = AudioRecorder::start(
+        Box::new(HeldBufferSource(BufferSource(samples.clone()))),
+        options.clone(),
+    )
+    .unwrap();
+    while recorder.captured_samples() < samples.len() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    // Snapshot taken while still recording, as the daemon does
+    let live = recorder.live_audio();
+    assert_eq!(live.len(), samples.len());
+    let recording = recorder.stop().unwrap();
+    assert!(recording.samples.len() > 24000);
+
+    let prefix = stable_prefix(live[..24000].to_vec(), &options).unwrap();
+    assert!(prefix.len() > 16000, "prefix: {}", prefix.len());
+    assert_eq!(prefix[..], recording.samples[..prefix.len()]);
+
+    // Silence only: the trim start isn't known yet
+    assert!(stable_prefix(vec![0.0; 16000], &options).is_none());
+
+    // Normalization depends on the whole recording
+    let normalized = RecorderOptions {
+        dsp: DspOptions {
+            normalize: Normalization::Peak,
+            ..Default::default()
+        },
+        ..options
+    };
+    assert!(stable_prefix(live, &normalized).is_none());
+}
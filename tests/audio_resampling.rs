@@ -3,6 +3,7 @@
 //! Tests resampling accuracy, quality, and edge cases using actual
 //! audio processing pipelines.
 
+use hyprvoice::audio::StreamResampler;
 use std::f32::consts::PI;
 
 /// Generate a sine wave at specified frequency and sample rate
@@ -288,3 +289,60 @@ fn test_resampling_nyquist_frequency() {
     let rms = calculate_rms(&signal);
     assert!(rms > 0.6, "Signal below Nyquist should be preserved");
 }
+
+/// Run a signal through the streaming resampler in chunks of `chunk` samples
+fn stream_resample(samples: &[f32], from: u32, to: u32, chunk: usize) -> Vec<f32> {
+    let mut resampler = StreamResampler::new(from, to).unwrap();
+    let mut out = Vec::new();
+    for block in samples.chunks(chunk) {
+        resampler.process(block, &mut out).unwrap();
+    }
+    resampler.finish(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn test_stream_resampler_exact_length() {
+    for (from, secs) in [(48000, 2.0), (44100, 1.5), (22050, 0.3), (8000, 1.0)] {
+        let input = generate_sine_wave(440.0, secs, from);
+        let output = stream_resample(&input, from, 16000, 480);
+        let expected = (input.len() as u64 * 16000 / from as u64) as usize;
+        assert_eq!(output.len(), expected, "{}Hz -> 16000Hz", from);
+    }
+}
+
+#[test]
+fn test_stream_resampler_chunking_is_transparent() {
+    let input = generate_sine_wave(440.0, 1.0, 48000);
+
+    // Callback-sized, odd-sized and one-shot feeding produce identical audio
+    let small = stream_resample(&input, 48000, 16000, 441);
+    let odd = stream_resample(&input, 48000, 16000, 1777);
+    let whole = stream_resample(&input, 48000, 16000, input.len());
+
+    assert_eq!(small, whole);
+    assert_eq!(odd, whole);
+}
+
+#[test]
+fn test_stream_resampler_preserves_signal() {
+    let input = generate_sine_wave(440.0, 1.0, 48000);
+    let output = stream_resample(&input, 48000, 16000, 480);
+
+    // Delay is compensated: the tone starts at the beginning, not after silence
+    let head_rms = calculate_rms(&output[..800]);
+    assert!(head_rms > 0.5, "Leading audio missing, RMS {}", head_rms);
+
+    let freq = estimate_frequency(&output, 16000);
+    assert!((freq - 440.0).abs() < 10.0, "Expected ~440Hz, got {}", freq);
+
+    let rms = calculate_rms(&output[800..output.len() - 800]);
+    assert!((rms - 0.707).abs() < 0.05, "RMS changed to {}", rms);
+}
+
+#[test]
+fn test_stream_resampler_passthrough_and_empty() {
+    let input = generate_sine_wave(440.0, 0.5, 16000);
+    assert_eq!(stream_resample(&input, 16000, 16000, 160), input);
+    assert!(stream_resample(&[], 48000, 16000, 480).is_empty());
+}
@@ -2,7 +2,9 @@
 //!
 //! Tests request/response serialization, error handling, and protocol contracts.
 
+use hyprvoice::audio::{PcmFormat, PcmSpec};
 use hyprvoice::daemon::protocol::{DaemonRequest, DaemonResponse};
+use hyprvoice::transcribe::{DetectedLanguage, Segment, Task, Transcription, Word};
 
 #[test]
 fn test_request_ping_serialization() {
@@ -18,13 +20,44 @@ fn test_request_ping_serialization() {
 
 #[test]
 fn test_request_start_recording_serialization() {
-    let request = DaemonRequest::StartRecording { max_duration: 300 };
+    let request = DaemonRequest::StartRecording {
+        max_duration: 300,
+        auto_stop: true,
+        task: Some(Task::Translate),
+    };
     let json = serde_json::to_string(&request).unwrap();
+    assert!(json.contains(r#""task":"translate""#));
     let parsed: DaemonRequest = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonRequest::StartRecording { max_duration } => {
+        DaemonRequest::StartRecording {
+            max_duration,
+            auto_stop,
+            task,
+        } => {
             assert_eq!(max_duration, 300);
+            assert!(auto_stop);
+            assert_eq!(task, Some(Task::Translate));
+        },
+        _ => panic!("Expected StartRecording variant"),
+    }
+}
+
+#[test]
+fn test_request_start_recording_without_auto_stop() {
+    // Requests from older clients omit auto_stop
+    let json = r#"{"type":"start_recording","max_duration":60}"#;
+    let parsed: DaemonRequest = serde_json::from_str(json).unwrap();
+
+    match parsed {
+        DaemonRequest::StartRecording {
+            max_duration,
+            auto_stop,
+            task,
+        } => {
+            assert_eq!(max_duration, 60);
+            assert!(!auto_stop);
+            assert_eq!(task, None);
         },
         _ => panic!("Expected StartRecording variant"),
     }
@@ -54,6 +87,258 @@ fn test_request_shutdown_serialization() {
     }
 }
 
+#[test]
+fn test_request_status_serialization() {
+    let json = serde_json::to_string(&DaemonRequest::Status).unwrap();
+    assert_eq!(json, r#"{"type":"status"}"#);
+}
+
+#[test]
+fn test_response_status_serialization() {
+    let response = DaemonResponse::Status {
+        recording: false,
+        mic_open: true,
+        preroll_ms: 500,
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
+
+    match parsed {
+        DaemonResponse::Status {
+            recording,
+            mic_open,
+            preroll_ms,
+        } => {
+            assert!(!recording);
+            assert!(mic_open);
+            assert_eq!(preroll_ms, 500);
+        },
+        _ => panic!("Expected Status variant"),
+    }
+}
+
+#[test]
+fn test_request_subscribe_levels_serialization() {
+    let json = serde_json::to_string(&DaemonRequest::SubscribeLevels).unwrap();
+    assert_eq!(json, r#"{"type":"subscribe_levels"}"#);
+}
+
+#[test]
+fn test_response_level_serialization() {
+    let response = DaemonResponse::Level {
+        recording: true,
+        rms_db: -24.5,
+        peak_db: -6.0,
+        percent: 59,
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    assert!(json.contains(r#""status":"level""#));
+
+    match serde_json::from_str(&json).unwrap() {
+        DaemonResponse::Level {
+            recording,
+            rms_db,
+            peak_db,
+            percent,
+        } => {
+            assert!(recording);
+            assert_eq!(rms_db, -24.5);
+            assert_eq!(peak_db, -6.0);
+            assert_eq!(percent, 59);
+        },
+        _ => panic!("Expected Level variant"),
+    }
+}
+
+#[test]
+fn test_request_subscribe_partials_serialization() {
+    let json = serde_json::to_string(&DaemonRequest::SubscribePartials).unwrap();
+    assert_eq!(json, r#"{"type":"subscribe_partials"}"#);
+}
+
+#[test]
+fn test_response_partial_serialization() {
+    let response = DaemonResponse::Partial {
+        recording: true,
+        text: "so far so".to_string(),
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    assert_eq!(
+        json,
+        r#"{"status":"partial","recording":true,"text":"so far so"}"#
+    );
+
+    match serde_json::from_str(r#"{"status":"partial","recording":false,"text":""}"#).unwrap() {
+        DaemonResponse::Partial { recording, text } => {
+            assert!(!recording);
+            assert!(text.is_empty());
+        },
+        _ => panic!("Expected Partial variant"),
+    }
+}
+
+#[test]
+fn test_request_transcribe_file_serialization() {
+    let request = DaemonRequest::TranscribeFile {
+        path: "/tmp/clip.wav".into(),
+        timestamps: true,
+        words: true,
+        task: None,
+    };
+    let json = serde_json::to_string(&request).unwrap();
+    let parsed: DaemonRequest = serde_json::from_str(&json).unwrap();
+
+    match parsed {
+        DaemonRequest::TranscribeFile {
+            path,
+            timestamps,
+            words,
+            task,
+        } => {
+            assert_eq!(task, None);
+            assert_eq!(path, std::path::Path::new("/tmp/clip.wav"));
+            assert!(timestamps);
+            assert!(words);
+        },
+        _ => panic!("Expected TranscribeFile variant"),
+    }
+}
+
+#[test]
+fn test_request_transcribe_file_defaults_to_text() {
+    // Requests from older clients carry no timestamps field
+    let json = r#"{"type":"transcribe_file","path":"/tmp/clip.wav"}"#;
+    let parsed: DaemonRequest = serde_json::from_str(json).unwrap();
+
+    match parsed {
+        DaemonRequest::TranscribeFile {
+            timestamps, words, ..
+        } => assert!(!timestamps && !words),
+        _ => panic!("Expected TranscribeFile variant"),
+    }
+}
+
+#[test]
+fn test_response_transcript_serialization() {
+    let response = DaemonResponse::Transcript(Transcription::from_segments(vec![Segment {
+        start: 0.0,
+        end: 1.5,
+        text: "Hello.".to_string(),
+        words: vec![Word {
+            start: 0.1,
+            end: 1.4,
+            text: " Hello.".to_string(),
+            probability: 0.25,
+        }],
+    }]));
+    let json = serde_json::to_string(&response).unwrap();
+    assert!(json.contains(r#""status":"transcript""#));
+
+    let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
+    match parsed {
+        DaemonResponse::Transcript(transcription) => {
+            assert_eq!(transcription.text, "Hello.");
+            assert_eq!(transcription.segments.len(), 1);
+            assert_eq!(transcription.segments[0].end, 1.5);
+            assert_eq!(transcription.segments[0].words[0].probability, 0.25);
+        },
+        _ => panic!("Expected Transcript variant"),
+    }
+}
+
+#[test]
+fn test_request_transcribe_stream_serialization() {
+    let request = DaemonRequest::TranscribeStream {
+        spec: PcmSpec {
+            sample_rate: 48000,
+            channels: 2,
+            format: PcmFormat::S16le,
+        },
+        chunked: true,
+        task: Some(Task::Transcribe),
+    };
+    let json = serde_json::to_string(&request).unwrap();
+    assert!(json.contains(r#""type":"transcribe_stream""#));
+    assert!(json.contains(r#""format":"s16le""#));
+
+    match serde_json::from_str(&json).unwrap() {
+        DaemonRequest::TranscribeStream {
+            spec,
+            chunked,
+            task,
+        } => {
+            assert_eq!(task, Some(Task::Transcribe));
+            assert_eq!(spec.sample_rate, 48000);
+            assert_eq!(spec.channels, 2);
+            assert_eq!(spec.format, PcmFormat::S16le);
+            assert!(chunked);
+        },
+        _ => panic!("Expected TranscribeStream variant"),
+    }
+
+    // chunked is optional
+    let legacy =
+        r#"{"type":"transcribe_stream","sample_rate":16000,"channels":1,"format":"f32le"}"#;
+    assert!(matches!(
+        serde_json::from_str(legacy).unwrap(),
+        DaemonRequest::TranscribeStream { chunked: false, .. }
+    ));
+}
+
+#[test]
+fn test_response_success_warnings() {
+    let response = DaemonResponse::Success {
+        text: "hello".to_string(),
+        warnings: vec!["Input is clipping 12% of the time".to_string()],
+        language: None,
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
+
+    match parsed {
+        DaemonResponse::Success { warnings, .. } => {
+            assert_eq!(warnings, vec!["Input is clipping 12% of the time"]);
+        },
+        _ => panic!("Expected Success variant"),
+    }
+
+    // Responses without warnings keep the original wire format
+    let legacy = r#"{"status":"success","text":"hello"}"#;
+    match serde_json::from_str::<DaemonResponse>(legacy).unwrap() {
+        DaemonResponse::Success {
+            text,
+            warnings,
+            language,
+        } => {
+            assert_eq!(text, "hello");
+            assert!(warnings.is_empty());
+            assert!(language.is_none());
+        },
+        _ => panic!("Expected Success variant"),
+    }
+}
+
+#[test]
+fn test_response_success_detected_language() {
+    let response = DaemonResponse::Success {
+        text: "Guten Morgen".to_string(),
+        warnings: vec![],
+        language: Some(DetectedLanguage {
+            code: "de".to_string(),
+            probability: 0.97,
+        }),
+    };
+    let json = serde_json::to_string(&response).unwrap();
+    assert!(json.contains(r#""language":{"code":"de","probability":0.97}"#));
+
+    match serde_json::from_str::<DaemonResponse>(&json).unwrap() {
+        DaemonResponse::Success { language, .. } => {
+            assert_eq!(language.unwrap().code, "de");
+        },
+        _ => panic!("Expected Success variant"),
+    }
+}
+
 #[test]
 fn test_response_ok_serialization() {
     let response = DaemonResponse::Ok {
@@ -86,12 +371,14 @@ fn test_response_recording_serialization() {
 fn test_response_success_serialization() {
     let response = DaemonResponse::Success {
         text: "transcribed text".to_string(),
+        warnings: vec![],
+        language: None,
     };
     let json = serde_json::to_string(&response).unwrap();
     let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonResponse::Success { text } => {
+        DaemonResponse::Success { text, .. } => {
             assert_eq!(text, "transcribed text");
         },
         _ => panic!("Expected Success variant"),
@@ -139,12 +426,14 @@ fn test_empty_json() {
 fn test_response_with_special_characters() {
     let response = DaemonResponse::Success {
         text: "Text with \"quotes\" and\nnewlines\tand\ttabs".to_string(),
+        warnings: vec![],
+        language: None,
     };
     let json = serde_json::to_string(&response).unwrap();
     let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonResponse::Success { text } => {
+        DaemonResponse::Success { text, .. } => {
             assert_eq!(text, "Text with \"quotes\" and\nnewlines\tand\ttabs");
         },
         _ => panic!("Expected Success variant"),
@@ -155,12 +444,14 @@ fn test_response_with_special_characters() {
 fn test_response_with_unicode() {
     let response = DaemonResponse::Success {
         text: "Unicode: 你好世界 🎉 émojis".to_string(),
+        warnings: vec![],
+        language: None,
     };
     let json = serde_json::to_string(&response).unwrap();
     let parsed: DaemonResponse = serde_json::from_str(&json).unwrap();
 
     match parsed {
-        DaemonResponse::Success { text } => {
+        DaemonResponse::Success { text, .. } => {
             assert_eq!(text, "Unicode: 你好世界 🎉 émojis");
         },
         _ => panic!("Expected Success variant"),